@@ -1,4 +1,5 @@
 use crate::core::{validate_profile_name, validate_version_name};
+use crate::version::Channel;
 use clap::{Parser, Subcommand, ValueEnum};
 
 fn parse_profile_arg(value: &str) -> std::result::Result<String, String> {
@@ -65,6 +66,12 @@ pub enum Commands {
     ListRemote {
         #[arg(short, long, default_value = "20", help = "Number of versions to show")]
         limit: usize,
+        #[arg(
+            short,
+            long,
+            help = "Only show releases from this channel (stable/beta/nightly)"
+        )]
+        channel: Option<Channel>,
     },
 
     #[command(about = "Uninstall a version", hide = true)]
@@ -159,6 +166,12 @@ pub enum VersionAction {
     ListRemote {
         #[arg(short, long, default_value = "20", help = "Number of versions to show")]
         limit: usize,
+        #[arg(
+            short,
+            long,
+            help = "Only show releases from this channel (stable/beta/nightly)"
+        )]
+        channel: Option<Channel>,
     },
 
     #[command(about = "Uninstall a version")]
@@ -234,6 +247,8 @@ pub enum ConfigAction {
     Show {
         #[arg(help = "Profile name (default: current)", value_parser = parse_profile_arg)]
         profile: Option<String>,
+        #[arg(long, help = "Hide secrets, server addresses and subscription URLs")]
+        anonymize: bool,
     },
 
     #[command(about = "Delete a profile")]
@@ -248,6 +263,26 @@ pub enum ConfigKey {
     ConfigsDir,
 }
 
+#[derive(Clone, Copy, Debug, ValueEnum, PartialEq, Eq)]
+pub enum ConnectionSort {
+    Duration,
+    Upload,
+    Download,
+    Host,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum, PartialEq, Eq)]
+pub enum ConnectionColumn {
+    Id,
+    Host,
+    Process,
+    Chain,
+    Rule,
+    Download,
+    Upload,
+    Duration,
+}
+
 #[derive(Subcommand)]
 pub enum DoctorAction {
     #[command(about = "Run doctor checks")]
@@ -285,8 +320,8 @@ pub enum DoctorAction {
 #[cfg(test)]
 mod tests {
     use super::{
-        Cli, Commands, ConfigAction, ConfigKey, ConnectionAction, DoctorAction, ProxyAction,
-        ServiceAction, VersionAction,
+        Channel, Cli, Commands, ConfigAction, ConfigKey, ConnectionAction, ConnectionColumn,
+        ConnectionSort, DoctorAction, ProxyAction, ServiceAction, VersionAction,
     };
     use clap::{CommandFactory, Parser};
 
@@ -318,7 +353,7 @@ mod tests {
             .expect("valid profile should parse");
         match parsed.command {
             Commands::Config {
-                action: ConfigAction::Show { profile },
+                action: ConfigAction::Show { profile, .. },
             } => assert_eq!(profile.as_deref(), Some("alpha-1.2_ok")),
             _ => panic!("expected config show command"),
         }
@@ -389,6 +424,19 @@ mod tests {
             _ => panic!("expected version use command"),
         }
 
+        let remote =
+            Cli::try_parse_from(["mihomo-rs", "version", "list-remote", "--channel", "alpha"])
+                .expect("version list-remote should parse");
+        match remote.command {
+            Commands::Version {
+                action: VersionAction::ListRemote { limit, channel },
+            } => {
+                assert_eq!(limit, 20);
+                assert_eq!(channel, Some(Channel::Nightly));
+            }
+            _ => panic!("expected version list-remote command"),
+        }
+
         let service = Cli::try_parse_from(["mihomo-rs", "service", "status"])
             .expect("service status should parse");
         match service.command {
@@ -469,7 +517,7 @@ mod tests {
         .expect("connection list flags should parse");
         match list.command {
             Commands::Connection {
-                action: ConnectionAction::List { host, process },
+                action: ConnectionAction::List { host, process, .. },
             } => {
                 assert_eq!(host.as_deref(), Some("example"));
                 assert_eq!(process.as_deref(), Some("curl"));
@@ -477,6 +525,36 @@ mod tests {
             _ => panic!("expected connection list command"),
         }
 
+        let sorted = Cli::try_parse_from([
+            "mihomo-rs",
+            "connection",
+            "list",
+            "--sort",
+            "download",
+            "--columns",
+            "host,chain,download",
+        ])
+        .expect("connection list sort and columns should parse");
+        match sorted.command {
+            Commands::Connection {
+                action: ConnectionAction::List { sort, columns, .. },
+            } => {
+                assert_eq!(sort, Some(ConnectionSort::Download));
+                assert_eq!(
+                    columns,
+                    vec![
+                        ConnectionColumn::Host,
+                        ConnectionColumn::Chain,
+                        ConnectionColumn::Download
+                    ]
+                );
+            }
+            _ => panic!("expected connection list command"),
+        }
+        assert!(
+            Cli::try_parse_from(["mihomo-rs", "connection", "list", "--sort", "speed"]).is_err()
+        );
+
         let close = Cli::try_parse_from([
             "mihomo-rs",
             "connection",
@@ -634,6 +712,19 @@ pub enum ConnectionAction {
         host: Option<String>,
         #[arg(long, help = "Filter by process name")]
         process: Option<String>,
+        #[arg(
+            long,
+            value_enum,
+            help = "Sort rows (largest or longest first, host A-Z)"
+        )]
+        sort: Option<ConnectionSort>,
+        #[arg(
+            long,
+            value_enum,
+            value_delimiter = ',',
+            help = "Comma-separated columns to show (e.g. host,chain,download)"
+        )]
+        columns: Vec<ConnectionColumn>,
     },
 
     #[command(about = "Show connection statistics")]
@@ -19,6 +19,10 @@ const ERROR_HINT_RULES: &[ErrorHintRule] = &[
         code: ErrorCode::InvalidVersion,
         hint: "version can only include letters, numbers, '.', '_', '-' and '+'",
     },
+    ErrorHintRule {
+        code: ErrorCode::InvalidInstanceName,
+        hint: "instance name can only include letters, numbers, '.', '_' and '-'",
+    },
 ];
 
 fn hint_for_error_code(code: ErrorCode) -> Option<&'static str> {
@@ -1,5 +1,5 @@
 use crate::cli::{print_info, print_success, print_table, ConfigAction, ConfigKey};
-use crate::config::{ConfigDirSource, ConfigManager};
+use crate::config::{anonymize_config, ConfigDirSource, ConfigManager};
 
 pub async fn handle_config(action: ConfigAction) -> anyhow::Result<()> {
     let cm = ConfigManager::new()?;
@@ -59,7 +59,7 @@ pub async fn handle_config(action: ConfigAction) -> anyhow::Result<()> {
             cm.set_current(&profile).await?;
             print_success(&format!("Switched to profile '{}'", profile));
         }
-        ConfigAction::Show { profile } => {
+        ConfigAction::Show { profile, anonymize } => {
             let profile = if let Some(p) = profile {
                 p
             } else {
@@ -68,7 +68,11 @@ pub async fn handle_config(action: ConfigAction) -> anyhow::Result<()> {
                     .unwrap_or_else(|_| "default".to_string())
             };
             let content = cm.load(&profile).await?;
-            println!("{}", content);
+            if anonymize {
+                println!("{}", anonymize_config(&content)?);
+            } else {
+                println!("{}", content);
+            }
         }
         ConfigAction::Delete { profile } => {
             cm.delete_profile(&profile).await?;
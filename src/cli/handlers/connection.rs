@@ -1,4 +1,6 @@
-use crate::cli::{print_info, print_success, print_table, ConnectionAction};
+use crate::cli::{
+    print_info, print_success, print_table, ConnectionAction, ConnectionColumn, ConnectionSort,
+};
 use crate::config::ConfigManager;
 use crate::connection::ConnectionManager;
 use crate::core::{Connection, MihomoClient};
@@ -20,10 +22,18 @@ pub async fn handle_connection(action: ConnectionAction) -> anyhow::Result<()> {
     let conn_mgr = ConnectionManager::new(client);
 
     match action {
-        ConnectionAction::List { host, process } => {
-            let connections =
+        ConnectionAction::List {
+            host,
+            process,
+            sort,
+            columns,
+        } => {
+            let mut connections =
                 load_connections(&conn_mgr, host.as_deref(), process.as_deref()).await?;
-            render_connection_list(&connections, host.as_deref(), process.as_deref());
+            if let Some(sort) = sort {
+                sort_connections(&mut connections, sort);
+            }
+            render_connection_list(&connections, host.as_deref(), process.as_deref(), &columns);
         }
         ConnectionAction::Stats => {
             let (download, upload, count) = conn_mgr.get_statistics().await?;
@@ -91,11 +101,11 @@ pub async fn handle_connection(action: ConnectionAction) -> anyhow::Result<()> {
         }
         ConnectionAction::FilterHost { host } => {
             let connections = load_connections(&conn_mgr, Some(&host), None).await?;
-            render_connection_list(&connections, Some(&host), None);
+            render_connection_list(&connections, Some(&host), None, &[]);
         }
         ConnectionAction::FilterProcess { process } => {
             let connections = load_connections(&conn_mgr, None, Some(&process)).await?;
-            render_connection_list(&connections, None, Some(&process));
+            render_connection_list(&connections, None, Some(&process), &[]);
         }
         ConnectionAction::CloseByHost { host, force } => {
             execute_close(&conn_mgr, CloseTarget::Host(host), force).await?;
@@ -144,7 +154,69 @@ async fn load_connections(
     Ok(connections)
 }
 
-fn render_connection_list(connections: &[Connection], host: Option<&str>, process: Option<&str>) {
+/// Longest-lived, biggest or alphabetical first, depending on `sort`.
+fn sort_connections(connections: &mut [Connection], sort: ConnectionSort) {
+    match sort {
+        ConnectionSort::Duration => {
+            // Unparseable start times sort last.
+            connections.sort_by_key(|c| (c.started_at().is_none(), c.started_at()))
+        }
+        ConnectionSort::Upload => connections.sort_by_key(|c| Reverse(c.upload)),
+        ConnectionSort::Download => connections.sort_by_key(|c| Reverse(c.download)),
+        ConnectionSort::Host => connections.sort_by_cached_key(connection_host_label),
+    }
+}
+
+fn format_elapsed(seconds: i64) -> String {
+    let seconds = seconds.max(0);
+    match (seconds / 3600, seconds % 3600 / 60, seconds % 60) {
+        (0, 0, s) => format!("{}s", s),
+        (0, m, s) => format!("{}m{}s", m, s),
+        (h, m, _) => format!("{}h{}m", h, m),
+    }
+}
+
+fn connection_column_header(column: ConnectionColumn) -> &'static str {
+    match column {
+        ConnectionColumn::Id => "ID",
+        ConnectionColumn::Host => "Host",
+        ConnectionColumn::Process => "Process",
+        ConnectionColumn::Chain => "Chain",
+        ConnectionColumn::Rule => "Rule",
+        ConnectionColumn::Download => "Download",
+        ConnectionColumn::Upload => "Upload",
+        ConnectionColumn::Duration => "Duration",
+    }
+}
+
+fn connection_cell(connection: &Connection, column: ConnectionColumn) -> String {
+    match column {
+        ConnectionColumn::Id => super::truncate_for_display(&connection.id, 8),
+        ConnectionColumn::Host => connection_host_label(connection),
+        ConnectionColumn::Process => connection.metadata.process_path.clone(),
+        ConnectionColumn::Chain => connection_chain_label(connection),
+        ConnectionColumn::Rule => {
+            if connection.rule_payload.is_empty() {
+                connection.rule.clone()
+            } else {
+                format!("{}({})", connection.rule, connection.rule_payload)
+            }
+        }
+        ConnectionColumn::Download => format!("{:.1} KB", connection.download as f64 / 1024.0),
+        ConnectionColumn::Upload => format!("{:.1} KB", connection.upload as f64 / 1024.0),
+        ConnectionColumn::Duration => connection
+            .started_at()
+            .map(|start| format_elapsed((chrono::Utc::now() - start).num_seconds()))
+            .unwrap_or_else(|| "-".to_string()),
+    }
+}
+
+fn render_connection_list(
+    connections: &[Connection],
+    host: Option<&str>,
+    process: Option<&str>,
+    columns: &[ConnectionColumn],
+) {
     if connections.is_empty() {
         match (host, process) {
             (Some(host), Some(process)) => print_info(&format!(
@@ -160,35 +232,36 @@ fn render_connection_list(connections: &[Connection], host: Option<&str>, proces
         return;
     }
 
-    if process.is_some() {
-        let rows: Vec<Vec<String>> = connections
-            .iter()
-            .map(|c| {
-                vec![
-                    super::truncate_for_display(&c.id, 8),
-                    connection_host_label(c),
-                    c.metadata.process_path.clone(),
-                    format!("{:.1} KB", c.download as f64 / 1024.0),
-                    format!("{:.1} KB", c.upload as f64 / 1024.0),
-                ]
-            })
-            .collect();
-        print_table(&["ID", "Host", "Process", "Download", "Upload"], rows);
+    let default_columns = [
+        ConnectionColumn::Id,
+        ConnectionColumn::Host,
+        if process.is_some() {
+            ConnectionColumn::Process
+        } else {
+            ConnectionColumn::Chain
+        },
+        ConnectionColumn::Download,
+        ConnectionColumn::Upload,
+    ];
+    let columns = if columns.is_empty() {
+        &default_columns[..]
     } else {
-        let rows: Vec<Vec<String>> = connections
-            .iter()
-            .map(|c| {
-                vec![
-                    super::truncate_for_display(&c.id, 8),
-                    connection_host_label(c),
-                    connection_chain_label(c),
-                    format!("{:.1} KB", c.download as f64 / 1024.0),
-                    format!("{:.1} KB", c.upload as f64 / 1024.0),
-                ]
-            })
-            .collect();
-        print_table(&["ID", "Host", "Chain", "Download", "Upload"], rows);
-    }
+        columns
+    };
+    let headers: Vec<&str> = columns
+        .iter()
+        .map(|column| connection_column_header(*column))
+        .collect();
+    let rows: Vec<Vec<String>> = connections
+        .iter()
+        .map(|c| {
+            columns
+                .iter()
+                .map(|column| connection_cell(c, *column))
+                .collect()
+        })
+        .collect();
+    print_table(&headers, rows);
 
     match (host, process) {
         (Some(host), Some(process)) => println!(
@@ -320,7 +393,50 @@ async fn execute_close(
 
 #[cfg(test)]
 mod tests {
-    use super::{parse_close_target, CloseTarget};
+    use super::{format_elapsed, parse_close_target, sort_connections, CloseTarget};
+    use crate::cli::ConnectionSort;
+    use crate::core::{Connection, ConnectionMetadata};
+
+    fn connection(id: &str, host: &str, start: &str, upload: u64, download: u64) -> Connection {
+        Connection {
+            metadata: ConnectionMetadata {
+                host: host.to_string(),
+                ..ConnectionMetadata::default()
+            },
+            start: start.to_string(),
+            ..Connection::fixture(id, upload, download)
+        }
+    }
+
+    fn ids(connections: &[Connection]) -> Vec<&str> {
+        connections.iter().map(|c| c.id.as_str()).collect()
+    }
+
+    #[test]
+    fn sort_connections_orders_by_requested_key() {
+        let mut connections = vec![
+            connection("a", "b.example", "2026-10-18T08:00:00Z", 10, 300),
+            connection("b", "a.example", "", 30, 100),
+            connection("c", "c.example", "2026-10-18T07:00:00Z", 20, 200),
+        ];
+
+        sort_connections(&mut connections, ConnectionSort::Duration);
+        assert_eq!(ids(&connections), vec!["c", "a", "b"]);
+        sort_connections(&mut connections, ConnectionSort::Upload);
+        assert_eq!(ids(&connections), vec!["b", "c", "a"]);
+        sort_connections(&mut connections, ConnectionSort::Download);
+        assert_eq!(ids(&connections), vec!["a", "c", "b"]);
+        sort_connections(&mut connections, ConnectionSort::Host);
+        assert_eq!(ids(&connections), vec!["b", "a", "c"]);
+    }
+
+    #[test]
+    fn format_elapsed_uses_two_largest_units() {
+        assert_eq!(format_elapsed(-5), "0s");
+        assert_eq!(format_elapsed(42), "42s");
+        assert_eq!(format_elapsed(125), "2m5s");
+        assert_eq!(format_elapsed(3725), "1h2m");
+    }
 
     #[test]
     fn parse_close_target_accepts_new_and_legacy_forms() {
@@ -7,6 +7,10 @@ mod telemetry;
 mod version;
 
 use crate::cli::Commands;
+use std::time::Duration;
+
+/// How long commands that need a live controller wait for it to come up.
+const CONTROLLER_READY_TIMEOUT: Duration = Duration::from_secs(3);
 
 pub async fn run_cli_command(command: Commands) -> anyhow::Result<()> {
     let exit_code = run_cli_command_with_exit(command).await?;
@@ -24,7 +28,9 @@ pub async fn run_cli_command_with_exit(command: Commands) -> anyhow::Result<i32>
         Commands::Update => version::handle_update().await.map(|_| 0),
         Commands::Default { version } => version::handle_default(version).await.map(|_| 0),
         Commands::List => version::handle_list().await.map(|_| 0),
-        Commands::ListRemote { limit } => version::handle_list_remote(limit).await.map(|_| 0),
+        Commands::ListRemote { limit, channel } => {
+            version::handle_list_remote(limit, channel).await.map(|_| 0)
+        }
         Commands::Uninstall { version } => version::handle_uninstall(version).await.map(|_| 0),
         Commands::Config { action } => config::handle_config(action).await.map(|_| 0),
         Commands::Service { action } => service::handle_service(action).await.map(|_| 0),
@@ -1,12 +1,16 @@
 use crate::cli::{print_info, print_success, print_table, ProxyAction};
 use crate::config::ConfigManager;
-use crate::core::MihomoClient;
+use crate::core::{DelayOutcome, MihomoClient};
 use crate::proxy::ProxyManager;
+use tokio_util::sync::CancellationToken;
 
 pub async fn handle_proxy(action: ProxyAction) -> anyhow::Result<()> {
     let cm = ConfigManager::new()?;
     let url = cm.get_external_controller().await?;
     let client = MihomoClient::new(&url, None)?;
+    client
+        .wait_until_ready(super::CONTROLLER_READY_TIMEOUT)
+        .await?;
     let pm = ProxyManager::new(client.clone());
 
     match action {
@@ -63,12 +67,34 @@ pub async fn handle_proxy(action: ProxyAction) -> anyhow::Result<()> {
                 print_success(&format!("{}: {}ms", proxy, delay));
             } else {
                 print_info("Testing all proxies...");
-                let results = crate::proxy::test_all_delays(&client, &url, timeout).await?;
-                let mut rows: Vec<Vec<String>> = results
+                let cancel = CancellationToken::new();
+                let on_ctrl_c = {
+                    let cancel = cancel.clone();
+                    tokio::spawn(async move {
+                        if tokio::signal::ctrl_c().await.is_ok() {
+                            cancel.cancel();
+                        }
+                    })
+                };
+                let results =
+                    crate::proxy::test_all_delay_outcomes(&client, &url, timeout, &cancel).await;
+                on_ctrl_c.abort();
+                let results = results?;
+                if cancel.is_cancelled() {
+                    print_info("Cancelled, showing partial results");
+                }
+                let rows: Vec<Vec<String>> = results
                     .iter()
-                    .map(|(name, delay)| vec![name.clone(), format!("{}ms", delay)])
+                    .map(|(name, outcome)| {
+                        let delay = match outcome {
+                            DelayOutcome::Ok(delay) => format!("{}ms", delay),
+                            DelayOutcome::Timeout => "timeout".to_string(),
+                            DelayOutcome::Unreachable => "unreachable".to_string(),
+                            DelayOutcome::ApiError(_) => "error".to_string(),
+                        };
+                        vec![name.clone(), delay]
+                    })
                     .collect();
-                rows.sort_by(|a, b| a[0].cmp(&b[0]));
                 print_table(&["Proxy", "Delay"], rows);
             }
         }
@@ -1,5 +1,8 @@
 use crate::cli::{print_info, print_success, print_table, VersionAction};
-use crate::version::{Channel, VersionManager};
+use crate::core::MihomoError;
+use crate::version::{fetch_latest, Channel, InstallEvent, VersionManager};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
 
 pub async fn handle_version(action: VersionAction) -> anyhow::Result<()> {
     match action {
@@ -7,34 +10,91 @@ pub async fn handle_version(action: VersionAction) -> anyhow::Result<()> {
         VersionAction::Update => handle_update().await,
         VersionAction::Use { version } => handle_default(version).await,
         VersionAction::List => handle_list().await,
-        VersionAction::ListRemote { limit } => handle_list_remote(limit).await,
+        VersionAction::ListRemote { limit, channel } => handle_list_remote(limit, channel).await,
         VersionAction::Uninstall { version } => handle_uninstall(version).await,
     }
 }
 
 pub async fn handle_install(version: Option<String>) -> anyhow::Result<()> {
     let vm = VersionManager::new()?;
-    let version = if let Some(v) = version {
-        if let Ok(channel) = v.parse::<Channel>() {
-            print_info(&format!("Installing {} channel...", channel.as_str()));
-            vm.install_channel(channel).await?
-        } else {
-            print_info(&format!("Installing version {}...", v));
-            vm.install(&v).await?;
-            v
-        }
-    } else {
-        print_info("Installing stable channel...");
-        vm.install_channel(Channel::Stable).await?
+    let version = match version {
+        Some(v) => match v.parse::<Channel>() {
+            Ok(channel) => resolve_channel(channel).await?,
+            Err(_) => {
+                print_info(&format!("Installing version {}...", v));
+                v
+            }
+        },
+        None => resolve_channel(Channel::Stable).await?,
     };
+    install_with_progress(&vm, &version).await?;
     print_success(&format!("Installed version {}", version));
     Ok(())
 }
 
+async fn resolve_channel(channel: Channel) -> anyhow::Result<String> {
+    print_info(&format!("Installing {} channel...", channel.as_str()));
+    Ok(fetch_latest(channel).await?.version)
+}
+
+/// Installs `version` while drawing download progress on stderr. Ctrl-C aborts the
+/// download and leaves nothing installed.
+async fn install_with_progress(vm: &VersionManager, version: &str) -> anyhow::Result<()> {
+    let cancel = CancellationToken::new();
+    let on_ctrl_c = {
+        let cancel = cancel.clone();
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                cancel.cancel();
+            }
+        })
+    };
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let render = tokio::spawn(async move {
+        let mut last = None;
+        while let Some(event) = rx.recv().await {
+            let InstallEvent::Downloading { done, total } = event else {
+                continue;
+            };
+            let line = match total {
+                Some(total) if total > 0 => format!("Downloading... {:>3}%", done * 100 / total),
+                _ => format!("Downloading... {:.1} MB", done as f64 / (1024.0 * 1024.0)),
+            };
+            if last.as_ref() != Some(&line) {
+                eprint!("\r{}", line);
+                last = Some(line);
+            }
+        }
+        if last.is_some() {
+            eprintln!();
+        }
+    });
+
+    let result = vm.install_with_progress(version, tx, &cancel).await;
+    on_ctrl_c.abort();
+    let _ = render.await;
+    if result.is_err() && cancel.is_cancelled() {
+        anyhow::bail!("Installation of {} cancelled", version);
+    }
+    Ok(result?)
+}
+
 pub async fn handle_update() -> anyhow::Result<()> {
     let vm = VersionManager::new()?;
-    print_info("Updating to latest stable version...");
-    let version = vm.install_channel(Channel::Stable).await?;
+    print_info("Checking for updates...");
+    let version = match vm.latest_newer_than_installed().await {
+        Ok(Some(latest)) => {
+            vm.ensure_installed(&latest.version, false).await?;
+            latest.version
+        }
+        Ok(None) => {
+            print_success("Already up to date");
+            return Ok(());
+        }
+        Err(MihomoError::NotFound(_)) => vm.install_channel(Channel::Stable).await?,
+        Err(err) => return Err(err.into()),
+    };
     vm.set_default(&version).await?;
     print_success(&format!("Updated to version {}", version));
     Ok(())
@@ -67,9 +127,18 @@ pub async fn handle_list() -> anyhow::Result<()> {
     Ok(())
 }
 
-pub async fn handle_list_remote(limit: usize) -> anyhow::Result<()> {
-    print_info(&format!("Fetching {} latest releases...", limit));
-    let releases = crate::version::fetch_releases(limit).await?;
+pub async fn handle_list_remote(limit: usize, channel: Option<Channel>) -> anyhow::Result<()> {
+    let releases = if let Some(channel) = channel {
+        print_info(&format!(
+            "Fetching {} latest {} releases...",
+            limit,
+            channel.as_str()
+        ));
+        crate::version::fetch_releases_by_channel(limit, channel).await?
+    } else {
+        print_info(&format!("Fetching {} latest releases...", limit));
+        crate::version::fetch_releases(limit).await?
+    };
     if releases.is_empty() {
         print_info("No releases found");
     } else {
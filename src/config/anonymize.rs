@@ -0,0 +1,367 @@
+use crate::core::Result;
+use serde_yaml::{Mapping, Value};
+use std::collections::HashMap;
+
+const REDACTED: &str = "<redacted>";
+
+/// Keys whose values are credentials, identify an account, or commonly embed a token.
+const SECRET_KEYS: [&str; 16] = [
+    "secret",
+    "password",
+    "obfs-password",
+    "username",
+    "uuid",
+    "auth",
+    "auth-str",
+    "authorization",
+    "token",
+    "psk",
+    "pre-shared-key",
+    "private-key",
+    "public-key",
+    "short-id",
+    "authentication",
+    "grpc-service-name",
+];
+
+/// Keys whose values name a server; each distinct address gets its own placeholder.
+const ADDRESS_KEYS: [&str; 6] = ["server", "servername", "sni", "host", "ip", "ipv6"];
+
+/// Keys of the `dns:` section that list upstream servers. DoH and DoT URLs often
+/// carry a personal ID, so every entry is replaced.
+const DNS_SERVER_KEYS: [&str; 5] = [
+    "nameserver",
+    "fallback",
+    "default-nameserver",
+    "proxy-server-nameserver",
+    "direct-nameserver",
+];
+
+/// Sections whose entries fetch from a subscription or rule-set URL.
+const PROVIDER_SECTIONS: [&str; 2] = ["proxy-providers", "rule-providers"];
+
+/// Returns `yaml` with secrets, server addresses, DNS upstreams, `hosts:` entries and
+/// provider URLs and headers replaced, so the config can be shared. Keys, proxy
+/// names, ports and the overall structure are kept. The same address always maps to
+/// the same `serverN.example` placeholder, so repeated references remain
+/// recognisable. Comments are not preserved.
+pub fn anonymize_config(yaml: &str) -> Result<String> {
+    let mut value: Value = serde_yaml::from_str(yaml)?;
+    let mut anonymizer = Anonymizer::default();
+    anonymizer.visit(&mut value, &[]);
+    Ok(serde_yaml::to_string(&value)?)
+}
+
+#[derive(Default)]
+struct Anonymizer {
+    addresses: HashMap<String, String>,
+    dns_servers: HashMap<String, String>,
+    urls: usize,
+}
+
+impl Anonymizer {
+    fn visit(&mut self, value: &mut Value, path: &[&str]) {
+        match value {
+            Value::Mapping(mapping) => self.visit_mapping(mapping, path),
+            Value::Sequence(items) => {
+                for item in items {
+                    self.visit(item, path);
+                }
+            }
+            Value::Tagged(tagged) => self.visit(&mut tagged.value, path),
+            _ => {}
+        }
+    }
+
+    fn visit_mapping(&mut self, mapping: &mut Mapping, path: &[&str]) {
+        for (key, value) in mapping.iter_mut() {
+            let Some(key) = key.as_str().map(str::to_ascii_lowercase) else {
+                continue;
+            };
+            let parent = path.last().copied().unwrap_or_default();
+            if SECRET_KEYS.contains(&key.as_str()) {
+                redact(value, &mut |_| REDACTED.to_string());
+            } else if ADDRESS_KEYS.contains(&key.as_str()) {
+                redact(value, &mut |address| self.address(address));
+            } else if key == "header" || key == "headers" {
+                self.anonymize_headers(value);
+            } else if path.is_empty() && key == "hosts" {
+                self.anonymize_hosts(value);
+            } else if path.is_empty() && key.starts_with("external-controller") {
+                redact(value, &mut |addr| self.listen_address(addr));
+            } else if parent == "dns" && DNS_SERVER_KEYS.contains(&key.as_str()) {
+                redact(value, &mut |server| self.dns_server(server));
+            } else if parent == "dns" && key == "nameserver-policy" {
+                self.anonymize_nameserver_policy(value);
+            } else if key == "path" && parent.ends_with("-opts") {
+                // Transport paths such as ws-opts.path often carry a token.
+                redact(value, &mut |_| REDACTED.to_string());
+            } else if key == "url"
+                && path.len() == 2
+                && PROVIDER_SECTIONS.contains(&path[0].to_ascii_lowercase().as_str())
+            {
+                self.urls += 1;
+                let url = format!("https://provider{}.example/", self.urls);
+                redact(value, &mut |_| url.clone());
+            } else {
+                let mut child = path.to_vec();
+                child.push(&key);
+                self.visit(value, &child);
+            }
+        }
+    }
+
+    /// Header names are kept; `Host` values become address placeholders and every
+    /// other value is redacted.
+    fn anonymize_headers(&mut self, value: &mut Value) {
+        let Value::Mapping(headers) = value else {
+            return redact(value, &mut |_| REDACTED.to_string());
+        };
+        for (name, value) in headers.iter_mut() {
+            if name
+                .as_str()
+                .is_some_and(|name| name.eq_ignore_ascii_case("host"))
+            {
+                redact(value, &mut |address| self.address(address));
+            } else {
+                redact(value, &mut |_| REDACTED.to_string());
+            }
+        }
+    }
+
+    /// Both the domains and the addresses they resolve to are replaced.
+    fn anonymize_hosts(&mut self, value: &mut Value) {
+        let Value::Mapping(hosts) = value else {
+            return redact(value, &mut |address| self.address(address));
+        };
+        let mut anonymized = Mapping::new();
+        for (domain, mut addresses) in std::mem::take(hosts) {
+            let domain = match domain.as_str() {
+                Some(domain) => Value::String(self.address(domain)),
+                None => domain,
+            };
+            redact(&mut addresses, &mut |address| self.address(address));
+            anonymized.insert(domain, addresses);
+        }
+        *hosts = anonymized;
+    }
+
+    /// Policy keys that are domain patterns are replaced, while `geosite:` and
+    /// `rule-set:` references are kept; the servers they point to are replaced.
+    fn anonymize_nameserver_policy(&mut self, value: &mut Value) {
+        let Value::Mapping(policy) = value else {
+            return;
+        };
+        let mut anonymized = Mapping::new();
+        for (pattern, mut servers) in std::mem::take(policy) {
+            let pattern = match pattern.as_str() {
+                Some(pattern) if !pattern.contains(':') => Value::String(self.address(pattern)),
+                _ => pattern,
+            };
+            redact(&mut servers, &mut |server| self.dns_server(server));
+            anonymized.insert(pattern, servers);
+        }
+        *policy = anonymized;
+    }
+
+    fn address(&mut self, address: &str) -> String {
+        let next = self.addresses.len() + 1;
+        self.addresses
+            .entry(address.to_string())
+            .or_insert_with(|| format!("server{}.example", next))
+            .clone()
+    }
+
+    /// Keeps the scheme, so DoH, DoT and plain upstreams stay distinguishable.
+    fn dns_server(&mut self, server: &str) -> String {
+        let next = self.dns_servers.len() + 1;
+        self.dns_servers
+            .entry(server.to_string())
+            .or_insert_with(|| match server.split_once("://") {
+                Some((scheme, _)) => format!("{}://dns{}.example", scheme, next),
+                None => format!("dns{}.example", next),
+            })
+            .clone()
+    }
+
+    /// Keeps the port of a `host:port` listen address.
+    fn listen_address(&mut self, addr: &str) -> String {
+        match addr.rsplit_once(':') {
+            Some((host, port)) if port.parse::<u16>().is_ok() => {
+                format!("{}:{}", self.address(host), port)
+            }
+            _ => self.address(addr),
+        }
+    }
+}
+
+/// Replaces every scalar in `value`, keeping lists and maps in place.
+fn redact(value: &mut Value, replace: &mut impl FnMut(&str) -> String) {
+    match value {
+        Value::Null => {}
+        Value::Sequence(items) => {
+            for item in items {
+                redact(item, replace);
+            }
+        }
+        Value::Mapping(mapping) => {
+            for (_, item) in mapping.iter_mut() {
+                redact(item, replace);
+            }
+        }
+        Value::Tagged(tagged) => redact(&mut tagged.value, replace),
+        Value::String(text) => *value = Value::String(replace(text)),
+        Value::Bool(_) | Value::Number(_) => {
+            let text = serde_yaml::to_string(value).unwrap_or_default();
+            *value = Value::String(replace(text.trim()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+port: 7890
+secret: s3cret
+external-controller: 192.168.7.2:9090
+authentication: ["alice:hunter2"]
+hosts:
+  nas.home.lan: 192.168.7.20
+  printer.home.lan: [192.168.7.21]
+dns:
+  enable: true
+  default-nameserver: [223.5.5.5]
+  nameserver:
+    - https://dns.nextdns.io/abc123
+    - tls://abc123.dns.nextdns.io
+  fallback: [https://dns.nextdns.io/abc123]
+  nameserver-policy:
+    corp.internal: 10.8.0.53
+    geosite:cn: 223.5.5.5
+proxies:
+  - name: HK
+    type: vmess
+    server: hk.myhost.net
+    port: 443
+    uuid: 5f1e-aaaa
+    network: ws
+    ws-opts:
+      path: /ws?token=wstoken
+      headers:
+        Host: cdn.myhost.net
+  - name: HK-backup
+    type: trojan
+    server: hk.myhost.net
+    port: 8443
+    password: 123456
+    sni: cdn.myhost.net
+  - name: JP
+    type: vless
+    server: jp.myhost.net
+    port: 443
+    network: grpc
+    grpc-opts:
+      grpc-service-name: grpctoken
+proxy-providers:
+  sub:
+    type: http
+    url: https://sub.example.org/token/abc
+    path: ./providers/sub.yaml
+    header:
+      Authorization: [Bearer subtoken]
+      User-Agent: [clash.meta]
+    health-check:
+      url: https://www.gstatic.com/generate_204
+rules:
+  - MATCH,HK
+"#;
+
+    #[test]
+    fn strips_secrets_addresses_and_subscription_urls() {
+        let anonymized = anonymize_config(CONFIG).unwrap();
+        for leaked in [
+            "s3cret",
+            "hunter2",
+            "myhost",
+            "5f1e",
+            "123456",
+            "sub.example.org",
+            "subtoken",
+            "home.lan",
+            "192.168.7",
+            "abc123",
+            "corp.internal",
+            "10.8.0.53",
+            "wstoken",
+            "grpctoken",
+        ] {
+            assert!(
+                !anonymized.contains(leaked),
+                "{} leaked:\n{}",
+                leaked,
+                anonymized
+            );
+        }
+
+        let value: Value = serde_yaml::from_str(&anonymized).unwrap();
+        assert_eq!(value["port"].as_u64(), Some(7890));
+        assert_eq!(value["secret"].as_str(), Some(REDACTED));
+        assert_eq!(value["authentication"][0].as_str(), Some(REDACTED));
+        let controller = value["external-controller"].as_str().unwrap();
+        assert!(controller.ends_with(".example:9090"), "{}", controller);
+
+        let hosts = value["hosts"].as_mapping().unwrap();
+        assert_eq!(hosts.len(), 2);
+        assert!(hosts
+            .iter()
+            .all(|(domain, _)| domain.as_str().unwrap().ends_with(".example")));
+
+        let dns = &value["dns"];
+        assert_eq!(dns["enable"].as_bool(), Some(true));
+        assert_eq!(dns["default-nameserver"][0].as_str(), Some("dns1.example"));
+        assert_eq!(dns["nameserver"][0].as_str(), Some("https://dns2.example"));
+        assert_eq!(dns["nameserver"][1].as_str(), Some("tls://dns3.example"));
+        assert_eq!(dns["fallback"][0].as_str(), Some("https://dns2.example"));
+        assert_eq!(
+            dns["nameserver-policy"]["geosite:cn"].as_str(),
+            Some("dns1.example")
+        );
+
+        let proxies = &value["proxies"];
+        assert_eq!(proxies[0]["name"].as_str(), Some("HK"));
+        let server = proxies[0]["server"].as_str().unwrap();
+        assert!(server.ends_with(".example"));
+        assert_eq!(proxies[1]["server"].as_str(), Some(server));
+        let host = proxies[0]["ws-opts"]["headers"]["Host"].as_str().unwrap();
+        assert_ne!(host, server);
+        assert_eq!(proxies[1]["sni"].as_str(), Some(host));
+        assert_eq!(proxies[0]["ws-opts"]["path"].as_str(), Some(REDACTED));
+        assert_eq!(proxies[1]["port"].as_u64(), Some(8443));
+        assert_eq!(proxies[1]["password"].as_str(), Some(REDACTED));
+        assert_eq!(
+            proxies[2]["grpc-opts"]["grpc-service-name"].as_str(),
+            Some(REDACTED)
+        );
+
+        let provider = &value["proxy-providers"]["sub"];
+        assert_eq!(provider["url"].as_str(), Some("https://provider1.example/"));
+        assert_eq!(provider["path"].as_str(), Some("./providers/sub.yaml"));
+        assert_eq!(
+            provider["header"]["Authorization"][0].as_str(),
+            Some(REDACTED)
+        );
+        assert_eq!(provider["header"]["User-Agent"][0].as_str(), Some(REDACTED));
+        assert_eq!(
+            provider["health-check"]["url"].as_str(),
+            Some("https://www.gstatic.com/generate_204")
+        );
+        assert_eq!(value["rules"][0].as_str(), Some("MATCH,HK"));
+    }
+
+    #[test]
+    fn rejects_invalid_yaml() {
+        assert!(anonymize_config("proxies: [").is_err());
+    }
+}
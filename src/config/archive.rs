@@ -0,0 +1,182 @@
+use crate::core::{MihomoError, Result};
+use std::io::{Cursor, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+const PROFILE_PREFIX: &str = "profile/";
+const FILES_PREFIX: &str = "files/";
+
+pub(super) struct ProfileArchive {
+    pub name: String,
+    pub yaml: String,
+    pub files: Vec<(PathBuf, Vec<u8>)>,
+}
+
+fn archive_error(err: impl std::fmt::Display) -> MihomoError {
+    MihomoError::config(format!("Invalid profile archive: {}", err))
+}
+
+/// Relative path that stays inside the directory it is joined to.
+fn is_contained(path: &Path) -> bool {
+    !path.as_os_str().is_empty()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
+
+/// Cache paths of `proxy-providers` and `rule-providers` that live under the config dir.
+pub(super) fn provider_paths(config: &serde_yaml::Value) -> Vec<PathBuf> {
+    let mut paths = vec![];
+    for section in ["proxy-providers", "rule-providers"] {
+        let Some(providers) = config.get(section).and_then(|v| v.as_mapping()) else {
+            continue;
+        };
+        for provider in providers.values() {
+            if let Some(path) = provider.get("path").and_then(|p| p.as_str()) {
+                let path = PathBuf::from(path.trim_start_matches("./"));
+                if is_contained(&path) && !paths.contains(&path) {
+                    paths.push(path);
+                }
+            }
+        }
+    }
+    paths
+}
+
+pub(super) fn write_archive(
+    profile: &str,
+    yaml: &str,
+    files: &[(PathBuf, Vec<u8>)],
+) -> Result<Vec<u8>> {
+    let mut buffer = Cursor::new(Vec::new());
+    {
+        let mut zip = ZipWriter::new(&mut buffer);
+        let options = SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated)
+            .unix_permissions(0o600);
+
+        zip.start_file(format!("{}{}.yaml", PROFILE_PREFIX, profile), options)
+            .map_err(archive_error)?;
+        zip.write_all(yaml.as_bytes())?;
+
+        for (path, data) in files {
+            let name = path
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            zip.start_file(format!("{}{}", FILES_PREFIX, name), options)
+                .map_err(archive_error)?;
+            zip.write_all(data)?;
+        }
+
+        zip.finish().map_err(archive_error)?;
+    }
+    Ok(buffer.into_inner())
+}
+
+pub(super) fn read_archive(bytes: &[u8]) -> Result<ProfileArchive> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).map_err(archive_error)?;
+    let mut profile = None;
+    let mut files = vec![];
+
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index).map_err(archive_error)?;
+        if entry.is_dir() {
+            continue;
+        }
+        let entry_name = entry.name().to_string();
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data)?;
+
+        if let Some(file_name) = entry_name.strip_prefix(PROFILE_PREFIX) {
+            let name = file_name
+                .strip_suffix(".yaml")
+                .filter(|name| !name.contains('/'))
+                .ok_or_else(|| archive_error(format!("unexpected entry '{}'", entry_name)))?;
+            if profile.is_some() {
+                return Err(archive_error("more than one profile"));
+            }
+            let yaml = String::from_utf8(data).map_err(archive_error)?;
+            profile = Some((name.to_string(), yaml));
+        } else if let Some(relative) = entry_name.strip_prefix(FILES_PREFIX) {
+            let path = PathBuf::from(relative);
+            if !is_contained(&path) {
+                return Err(archive_error(format!("unsafe path '{}'", entry_name)));
+            }
+            files.push((path, data));
+        } else {
+            return Err(archive_error(format!("unexpected entry '{}'", entry_name)));
+        }
+    }
+
+    let (name, yaml) = profile.ok_or_else(|| archive_error("no profile found"))?;
+    Ok(ProfileArchive { name, yaml, files })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn provider_paths_skip_absolute_and_parent_paths() {
+        let config: serde_yaml::Value = serde_yaml::from_str(
+            r#"
+proxy-providers:
+  sub:
+    type: http
+    path: ./providers/sub.yaml
+  escape:
+    type: file
+    path: ../outside.yaml
+rule-providers:
+  ads:
+    type: http
+    path: /etc/ads.yaml
+  direct:
+    type: http
+    path: rules/direct.yaml
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            provider_paths(&config),
+            vec![
+                PathBuf::from("providers/sub.yaml"),
+                PathBuf::from("rules/direct.yaml")
+            ]
+        );
+    }
+
+    #[test]
+    fn read_archive_rejects_entries_escaping_config_dir() {
+        let mut buffer = Cursor::new(Vec::new());
+        {
+            let mut zip = ZipWriter::new(&mut buffer);
+            let options = SimpleFileOptions::default();
+            zip.start_file("profile/work.yaml", options).unwrap();
+            zip.write_all(b"port: 7890\n").unwrap();
+            zip.start_file("files/../../evil.sh", options).unwrap();
+            zip.write_all(b"boom").unwrap();
+            zip.finish().unwrap();
+        }
+
+        let err = read_archive(&buffer.into_inner())
+            .err()
+            .expect("unsafe entry should be rejected");
+        assert!(err.to_string().contains("unsafe path"));
+    }
+
+    #[test]
+    fn write_and_read_archive_roundtrip() {
+        let files = vec![(PathBuf::from("providers/sub.yaml"), b"proxies: []".to_vec())];
+        let bytes = write_archive("work", "port: 7890\n", &files).unwrap();
+
+        let archive = read_archive(&bytes).unwrap();
+        assert_eq!(archive.name, "work");
+        assert_eq!(archive.yaml, "port: 7890\n");
+        assert_eq!(archive.files, files);
+    }
+}
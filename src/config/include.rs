@@ -0,0 +1,213 @@
+use crate::core::{MihomoError, Result};
+use serde_yaml::Value;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use tokio::fs;
+
+const INCLUDE_KEY: &str = "include";
+
+/// Merges `overlay` into `base`: mappings merge key by key, sequences are
+/// concatenated with `base` first, and any other overlay value replaces the base one.
+pub(super) fn merge_yaml(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Mapping(base), Value::Mapping(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_yaml(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (Value::Sequence(base), Value::Sequence(overlay)) => base.extend(overlay),
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Parses `content` and merges in every file listed under its top-level `include:`.
+///
+/// Included files are resolved relative to `dir`, merged in order, and the including
+/// document is applied last so its settings win and its list entries come after.
+pub(super) async fn resolve_includes(content: &str, dir: &Path) -> Result<Value> {
+    resolve(content.to_string(), dir.to_path_buf(), Vec::new()).await
+}
+
+/// Reads the profile at `path` and resolves its includes relative to its directory.
+pub(crate) async fn resolve_file(path: &Path) -> Result<Value> {
+    let content = fs::read_to_string(path).await?;
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    resolve_includes(&content, dir).await
+}
+
+/// Path to hand to mihomo for the profile at `path`.
+///
+/// mihomo doesn't understand `include:`, so a profile that uses it is resolved and
+/// written to a hidden `.<file name>.resolved` file next to it, and that path is
+/// returned. Other profiles, and files that can't be read or parsed, are returned
+/// unchanged so mihomo reports the problem itself.
+pub(crate) async fn launch_path(path: &Path) -> Result<PathBuf> {
+    let Ok(content) = fs::read_to_string(path).await else {
+        return Ok(path.to_path_buf());
+    };
+    let has_includes = serde_yaml::from_str::<Value>(&content)
+        .ok()
+        .is_some_and(|config| config.get(INCLUDE_KEY).is_some());
+    if !has_includes {
+        return Ok(path.to_path_buf());
+    }
+
+    let resolved = resolve_file(path).await?;
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "config.yaml".to_string());
+    let out = path.with_file_name(format!(".{}.resolved", file_name));
+    fs::write(&out, serde_yaml::to_string(&resolved)?).await?;
+    log::debug!(
+        "Resolved includes of {} into {}",
+        path.display(),
+        out.display()
+    );
+    Ok(out)
+}
+
+fn resolve(
+    content: String,
+    dir: PathBuf,
+    stack: Vec<PathBuf>,
+) -> Pin<Box<dyn Future<Output = Result<Value>> + Send>> {
+    Box::pin(async move {
+        let mut config: Value = serde_yaml::from_str(&content)?;
+        let includes = match config.as_mapping_mut().and_then(|m| m.remove(INCLUDE_KEY)) {
+            None => return Ok(config),
+            Some(Value::String(path)) => vec![path],
+            Some(Value::Sequence(items)) => items
+                .into_iter()
+                .map(|item| match item {
+                    Value::String(path) => Ok(path),
+                    _ => Err(MihomoError::config("include entries must be paths")),
+                })
+                .collect::<Result<Vec<_>>>()?,
+            Some(_) => {
+                return Err(MihomoError::config(
+                    "include must be a path or list of paths",
+                ))
+            }
+        };
+
+        let mut merged = Value::Mapping(Default::default());
+        for include in includes {
+            let path = fs::canonicalize(dir.join(&include)).await.map_err(|e| {
+                MihomoError::config(format!("Cannot read included file '{}': {}", include, e))
+            })?;
+            if stack.contains(&path) {
+                return Err(MihomoError::config(format!(
+                    "Include cycle detected at '{}'",
+                    path.display()
+                )));
+            }
+
+            let included = fs::read_to_string(&path).await?;
+            let parent = path.parent().unwrap_or(&dir).to_path_buf();
+            let mut nested = stack.clone();
+            nested.push(path);
+            merge_yaml(&mut merged, resolve(included, parent, nested).await?);
+        }
+
+        merge_yaml(&mut merged, config);
+        Ok(merged)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn merge_yaml_concatenates_lists_and_overrides_scalars() {
+        let mut base: Value =
+            serde_yaml::from_str("port: 1\nrules: [a]\ndns: {enable: false, ipv6: true}").unwrap();
+        let overlay: Value =
+            serde_yaml::from_str("port: 2\nrules: [b]\ndns: {enable: true}").unwrap();
+
+        merge_yaml(&mut base, overlay);
+
+        let expected: Value =
+            serde_yaml::from_str("port: 2\nrules: [a, b]\ndns: {enable: true, ipv6: true}")
+                .unwrap();
+        assert_eq!(base, expected);
+    }
+
+    #[tokio::test]
+    async fn resolve_includes_merges_nested_files_relative_to_includer() {
+        let temp = tempdir().unwrap();
+        fs::create_dir_all(temp.path().join("rules")).await.unwrap();
+        fs::write(
+            temp.path().join("rules/ads.yaml"),
+            "include: common.yaml\nrules: ['DOMAIN-SUFFIX,ads.com,REJECT']\n",
+        )
+        .await
+        .unwrap();
+        fs::write(
+            temp.path().join("rules/common.yaml"),
+            "rules: ['DOMAIN,example.com,DIRECT']\n",
+        )
+        .await
+        .unwrap();
+
+        let config = resolve_includes(
+            "include: [rules/ads.yaml]\nport: 7890\nrules: ['MATCH,DIRECT']\n",
+            temp.path(),
+        )
+        .await
+        .unwrap();
+
+        let expected: Value = serde_yaml::from_str(
+            "rules: ['DOMAIN,example.com,DIRECT', 'DOMAIN-SUFFIX,ads.com,REJECT', 'MATCH,DIRECT']\nport: 7890\n",
+        )
+        .unwrap();
+        assert_eq!(config, expected);
+    }
+
+    #[tokio::test]
+    async fn launch_path_writes_resolved_copy_only_for_profiles_with_includes() {
+        let temp = tempdir().unwrap();
+        fs::write(temp.path().join("common.yaml"), "rules: ['MATCH,DIRECT']\n")
+            .await
+            .unwrap();
+        let plain = temp.path().join("plain.yaml");
+        fs::write(&plain, "port: 7890\n").await.unwrap();
+        let profile = temp.path().join("main.yaml");
+        fs::write(&profile, "include: common.yaml\nport: 7890\n")
+            .await
+            .unwrap();
+
+        assert_eq!(launch_path(&plain).await.unwrap(), plain);
+
+        let launched = launch_path(&profile).await.unwrap();
+        assert_eq!(launched, temp.path().join(".main.yaml.resolved"));
+        let written: Value =
+            serde_yaml::from_str(&fs::read_to_string(&launched).await.unwrap()).unwrap();
+        let expected: Value = serde_yaml::from_str("rules: ['MATCH,DIRECT']\nport: 7890").unwrap();
+        assert_eq!(written, expected);
+    }
+
+    #[tokio::test]
+    async fn resolve_includes_rejects_cycles() {
+        let temp = tempdir().unwrap();
+        fs::write(temp.path().join("a.yaml"), "include: b.yaml\n")
+            .await
+            .unwrap();
+        fs::write(temp.path().join("b.yaml"), "include: a.yaml\n")
+            .await
+            .unwrap();
+
+        let err = resolve_includes("include: a.yaml\n", temp.path())
+            .await
+            .expect_err("cycle should fail");
+        assert!(err.to_string().contains("cycle"));
+    }
+}
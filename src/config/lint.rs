@@ -0,0 +1,260 @@
+use super::rule::RuleExpr;
+use crate::core::{MihomoError, Result};
+
+/// A problem found in a profile's `rules:` list. Indices are positions in that list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LintWarning {
+    /// Same type and payload as an earlier rule, so it never fires.
+    Duplicate { index: usize, first: usize },
+    /// Every request it matches is already caught by the broader rule at `by`.
+    Shadowed { index: usize, by: usize },
+    /// `MATCH` catches everything, so the rules after it are unreachable.
+    MatchNotLast { index: usize, unreachable: usize },
+    /// The rule doesn't parse; see [`validate_rule`].
+    Invalid { index: usize, reason: String },
+}
+
+/// Checks the shape of one rule as written in a profile, according to its type:
+/// `MATCH,TARGET`, logical rules such as `AND,((DOMAIN,a.com),(NETWORK,UDP)),TARGET`
+/// whose payload contains commas, and `TYPE,PAYLOAD,TARGET[,options]` otherwise.
+/// Rule types and targets are not checked against what mihomo knows.
+pub fn validate_rule(rule: &str) -> Result<()> {
+    let invalid =
+        |reason: &str| MihomoError::config(format!("Invalid rule '{}': {}", rule, reason));
+    let (kind, rest) = rule
+        .trim()
+        .split_once(',')
+        .ok_or_else(|| invalid("expected TYPE,...,TARGET"))?;
+    let kind = kind.trim().to_ascii_uppercase();
+    if kind.is_empty() {
+        return Err(invalid("missing rule type"));
+    }
+
+    let target = match kind.as_str() {
+        "MATCH" => rest,
+        "AND" | "OR" | "NOT" | "SUB-RULE" => {
+            let rest = rest.trim_start();
+            if !rest.starts_with('(') {
+                return Err(invalid("payload must be wrapped in parentheses"));
+            }
+            let mut depth = 0usize;
+            let close = rest.char_indices().find_map(|(i, c)| {
+                match c {
+                    '(' => depth += 1,
+                    ')' => depth -= 1,
+                    _ => {}
+                }
+                (depth == 0).then_some(i)
+            });
+            let close = close.ok_or_else(|| invalid("unbalanced parentheses"))?;
+            if kind != "SUB-RULE" {
+                RuleExpr::parse(&kind, &rest[..=close])?;
+            }
+            rest[close + 1..]
+                .trim_start()
+                .strip_prefix(',')
+                .ok_or_else(|| invalid("missing target"))?
+        }
+        _ => {
+            let (payload, target) = rest
+                .split_once(',')
+                .ok_or_else(|| invalid("missing target"))?;
+            if payload.trim().is_empty() {
+                return Err(invalid("missing payload"));
+            }
+            target
+        }
+    };
+
+    // Options such as `no-resolve` may follow the target.
+    let target = target.split(',').next().unwrap_or_default().trim();
+    if target.is_empty() {
+        return Err(invalid("missing target"));
+    }
+    Ok(())
+}
+
+/// Validates every rule in a profile's `rules:` list with [`validate_rule`] without
+/// stopping at the first failure. Each result is paired with the rule's index.
+pub fn validate_rules(rules: &[String]) -> Vec<(usize, Result<()>)> {
+    rules
+        .iter()
+        .enumerate()
+        .map(|(index, rule)| (index, validate_rule(rule)))
+        .collect()
+}
+
+struct ParsedRule {
+    kind: String,
+    payload: String,
+}
+
+fn parse_rule(rule: &str) -> ParsedRule {
+    let mut parts = rule.splitn(3, ',').map(str::trim);
+    let kind = parts.next().unwrap_or_default().to_ascii_uppercase();
+    // Logical rules nest commas inside parentheses; compare them verbatim.
+    let payload = if matches!(kind.as_str(), "AND" | "OR" | "NOT" | "SUB-RULE") {
+        rule.trim().to_string()
+    } else if kind.starts_with("DOMAIN") {
+        parts.next().unwrap_or_default().to_ascii_lowercase()
+    } else {
+        parts.next().unwrap_or_default().to_string()
+    };
+    ParsedRule { kind, payload }
+}
+
+fn is_subdomain_or_same(domain: &str, suffix: &str) -> bool {
+    domain == suffix
+        || domain
+            .strip_suffix(suffix)
+            .is_some_and(|rest| rest.ends_with('.'))
+}
+
+/// Whether every request matched by `later` is also matched by `earlier`.
+fn covers(earlier: &ParsedRule, later: &ParsedRule) -> bool {
+    match (earlier.kind.as_str(), later.kind.as_str()) {
+        ("DOMAIN-SUFFIX", "DOMAIN" | "DOMAIN-SUFFIX") => {
+            is_subdomain_or_same(&later.payload, &earlier.payload)
+        }
+        ("DOMAIN-KEYWORD", "DOMAIN" | "DOMAIN-SUFFIX" | "DOMAIN-KEYWORD") => {
+            later.payload.contains(&earlier.payload)
+        }
+        _ => false,
+    }
+}
+
+/// Flags malformed rules, duplicate rules, rules shadowed by an earlier broader domain
+/// rule, and a `MATCH` that is not the final rule. Rules are strings as written in the config.
+pub fn lint_rules(rules: &[String]) -> Vec<LintWarning> {
+    let parsed: Vec<ParsedRule> = rules.iter().map(|rule| parse_rule(rule)).collect();
+    let mut warnings = vec![];
+
+    for (index, rule) in parsed.iter().enumerate() {
+        if let Err(err) = validate_rule(&rules[index]) {
+            warnings.push(LintWarning::Invalid {
+                index,
+                reason: err.to_string(),
+            });
+            continue;
+        }
+        if rule.kind == "MATCH" {
+            let unreachable = parsed.len() - index - 1;
+            if unreachable > 0 {
+                warnings.push(LintWarning::MatchNotLast { index, unreachable });
+            }
+            break;
+        }
+
+        let earlier = &parsed[..index];
+        if let Some(first) = earlier
+            .iter()
+            .position(|e| e.kind == rule.kind && e.payload == rule.payload)
+        {
+            warnings.push(LintWarning::Duplicate { index, first });
+        } else if let Some(by) = earlier.iter().position(|e| covers(e, rule)) {
+            warnings.push(LintWarning::Shadowed { index, by });
+        }
+    }
+
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(list: &[&str]) -> Vec<String> {
+        list.iter().map(|rule| rule.to_string()).collect()
+    }
+
+    #[test]
+    fn lint_rules_flags_shadowed_duplicate_and_early_match() {
+        let warnings = lint_rules(&rules(&[
+            "DOMAIN-SUFFIX,example.com,Proxy",
+            "DOMAIN,a.example.com,DIRECT",
+            "DOMAIN-SUFFIX,notexample.com,DIRECT",
+            "DOMAIN-KEYWORD,google,Proxy",
+            "DOMAIN-SUFFIX,google.com,DIRECT",
+            "IP-CIDR,10.0.0.0/8,DIRECT,no-resolve",
+            "ip-cidr,10.0.0.0/8,REJECT",
+            "MATCH,Proxy",
+            "DOMAIN,late.com,DIRECT",
+        ]));
+
+        assert_eq!(
+            warnings,
+            vec![
+                LintWarning::Shadowed { index: 1, by: 0 },
+                LintWarning::Shadowed { index: 4, by: 3 },
+                LintWarning::Duplicate { index: 6, first: 5 },
+                LintWarning::MatchNotLast {
+                    index: 7,
+                    unreachable: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_rule_is_type_aware() {
+        for rule in [
+            "MATCH,DIRECT",
+            "DOMAIN-SUFFIX,example.com,Proxy",
+            "IP-CIDR,10.0.0.0/8,DIRECT,no-resolve",
+            "AND,((DOMAIN,a.com),(NETWORK,UDP)),REJECT",
+            "NOT,((DST-PORT,443)),DIRECT",
+            "SUB-RULE,(NETWORK,tcp),sub-rule",
+        ] {
+            assert!(validate_rule(rule).is_ok(), "{}", rule);
+        }
+        for rule in [
+            "MATCH",
+            "MATCH,",
+            "DOMAIN,example.com",
+            "DOMAIN,,Proxy",
+            "AND,((DOMAIN,a.com),(NETWORK,UDP),REJECT",
+            "OR,DOMAIN,a.com,REJECT",
+            "NOT,((DST-PORT,443),(NETWORK,UDP)),DIRECT",
+            "AND,((DOMAIN,a.com))",
+        ] {
+            assert!(validate_rule(rule).is_err(), "{}", rule);
+        }
+    }
+
+    #[test]
+    fn validate_rules_reports_every_failure_with_its_index() {
+        let results = validate_rules(&rules(&[
+            "DOMAIN,example.com",
+            "MATCH,DIRECT",
+            "OR,DOMAIN,a.com,REJECT",
+        ]));
+        let failed: Vec<usize> = results
+            .iter()
+            .filter(|(_, result)| result.is_err())
+            .map(|(index, _)| *index)
+            .collect();
+        assert_eq!(results.len(), 3);
+        assert_eq!(failed, vec![0, 2]);
+    }
+
+    #[test]
+    fn lint_rules_reports_invalid_rules() {
+        let warnings = lint_rules(&rules(&["DOMAIN,example.com", "MATCH,DIRECT"]));
+        assert!(matches!(
+            warnings.as_slice(),
+            [LintWarning::Invalid { index: 0, reason }] if reason.contains("missing target")
+        ));
+    }
+
+    #[test]
+    fn lint_rules_accepts_clean_list() {
+        assert!(lint_rules(&rules(&[
+            "DOMAIN,a.example.com,DIRECT",
+            "DOMAIN-SUFFIX,example.com,Proxy",
+            "AND,((DOMAIN,a.com),(NETWORK,UDP)),REJECT",
+            "GEOIP,CN,DIRECT",
+            "MATCH,Proxy",
+        ]))
+        .is_empty());
+    }
+}
@@ -1,13 +1,23 @@
+use super::archive;
+use super::include;
+use super::lint::{lint_rules, LintWarning};
 use super::profile::Profile;
+use super::sections::{SnifferConfig, TunConfig};
+use super::template;
 use crate::core::{
     find_available_port, get_home_dir, is_port_available, validate_profile_name, ErrorCode,
-    MihomoError, Result,
+    MihomoClient, MihomoError, Result,
 };
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tokio::fs;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 use url::Url;
 
+#[derive(Clone)]
 pub struct ConfigManager {
     config_dir: PathBuf,
     settings_file: PathBuf,
@@ -36,6 +46,35 @@ pub struct ConfigDirInfo {
     pub source: ConfigDirSource,
 }
 
+/// Settings for [`ConfigManager::watch`].
+#[derive(Clone)]
+pub struct WatchOptions {
+    pub interval: Duration,
+    /// Controller to reload each change into; `None` only reports changes.
+    pub reload: Option<MihomoClient>,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(1),
+            reload: None,
+        }
+    }
+}
+
+impl WatchOptions {
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval.max(Duration::from_millis(1));
+        self
+    }
+
+    pub fn with_reload(mut self, client: MihomoClient) -> Self {
+        self.reload = Some(client);
+        self
+    }
+}
+
 #[derive(Debug, Default, Deserialize)]
 struct ConfigSettings {
     #[serde(default)]
@@ -246,6 +285,43 @@ impl ConfigManager {
         Ok(content)
     }
 
+    /// Loads `profile` with its top-level `include:` files merged in.
+    ///
+    /// Include paths are relative to the file that lists them. Included lists come
+    /// before the includer's entries, and the includer's scalar values win.
+    ///
+    /// mihomo itself ignores `include:`. [`crate::ServiceManager::start`] and
+    /// [`Self::watch`] hand it a resolved copy instead, but a profile path passed
+    /// directly to [`crate::MihomoClient::reload_config`] is loaded without its
+    /// includes.
+    pub async fn load_resolved(&self, profile: &str) -> Result<serde_yaml::Value> {
+        let content = self.load(profile).await?;
+        let config_dir = self.resolve_config_dir()?;
+        include::resolve_includes(&content, &config_dir).await
+    }
+
+    /// Lints the `rules:` list of `profile`, including rules pulled in through `include:`.
+    pub async fn lint_profile(&self, profile: &str) -> Result<Vec<LintWarning>> {
+        let config = self.load_resolved(profile).await?;
+        let rules: Vec<String> = config
+            .get("rules")
+            .and_then(|rules| rules.as_sequence())
+            .map(|rules| {
+                rules
+                    .iter()
+                    .filter_map(|rule| rule.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(lint_rules(&rules))
+    }
+
+    /// Substitutes `${NAME}` placeholders in a templated profile; see
+    /// [`render_template`](super::render_template). Pass the result to [`Self::save`].
+    pub fn render_template(template: &str, vars: &HashMap<String, String>) -> Result<String> {
+        template::render_template(template, vars)
+    }
+
     pub async fn save(&self, profile: &str, content: &str) -> Result<()> {
         validate_profile_name(profile)?;
         let config_dir = self.resolve_config_dir()?;
@@ -259,6 +335,91 @@ impl ConfigManager {
         Ok(())
     }
 
+    /// Sets the health-check `url` (and optionally `interval`, in seconds) of a proxy
+    /// group in `profile`. mihomo has no API for this, so the profile is rewritten;
+    /// reload it with [`crate::MihomoClient::reload_config`] to apply the change live.
+    pub async fn set_group_test_url(
+        &self,
+        profile: &str,
+        group: &str,
+        url: &str,
+        interval: Option<u32>,
+    ) -> Result<()> {
+        let content = self.load(profile).await?;
+        let mut config: serde_yaml::Value = serde_yaml::from_str(&content)?;
+
+        let entry = config
+            .get_mut("proxy-groups")
+            .and_then(|groups| groups.as_sequence_mut())
+            .and_then(|groups| {
+                groups
+                    .iter_mut()
+                    .find(|entry| entry.get("name").and_then(|name| name.as_str()) == Some(group))
+            })
+            .and_then(|entry| entry.as_mapping_mut())
+            .ok_or_else(|| {
+                MihomoError::NotFound(format!(
+                    "Proxy group '{}' not found in profile '{}'",
+                    group, profile
+                ))
+            })?;
+
+        entry.insert("url".into(), url.into());
+        if let Some(interval) = interval {
+            entry.insert("interval".into(), interval.into());
+        }
+
+        self.save(profile, &serde_yaml::to_string(&config)?).await
+    }
+
+    pub async fn get_tun(&self, profile: &str) -> Result<Option<TunConfig>> {
+        self.get_section(profile, "tun").await
+    }
+
+    pub async fn set_tun(&self, profile: &str, tun: &TunConfig) -> Result<()> {
+        tun.validate()?;
+        self.set_section(profile, "tun", tun).await
+    }
+
+    pub async fn get_sniffer(&self, profile: &str) -> Result<Option<SnifferConfig>> {
+        self.get_section(profile, "sniffer").await
+    }
+
+    pub async fn set_sniffer(&self, profile: &str, sniffer: &SnifferConfig) -> Result<()> {
+        sniffer.validate()?;
+        self.set_section(profile, "sniffer", sniffer).await
+    }
+
+    async fn get_section<T: serde::de::DeserializeOwned>(
+        &self,
+        profile: &str,
+        key: &str,
+    ) -> Result<Option<T>> {
+        let content = self.load(profile).await?;
+        let config: serde_yaml::Value = serde_yaml::from_str(&content)?;
+        match config.get(key) {
+            Some(section) if !section.is_null() => {
+                Ok(Some(serde_yaml::from_value(section.clone())?))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    async fn set_section<T: serde::Serialize>(
+        &self,
+        profile: &str,
+        key: &str,
+        section: &T,
+    ) -> Result<()> {
+        let content = self.load(profile).await?;
+        let mut config: serde_yaml::Value = serde_yaml::from_str(&content)?;
+        let root = config.as_mapping_mut().ok_or_else(|| {
+            MihomoError::config(format!("Profile '{}' is not a YAML mapping", profile))
+        })?;
+        root.insert(key.into(), serde_yaml::to_value(section)?);
+        self.save(profile, &serde_yaml::to_string(&config)?).await
+    }
+
     pub async fn list_profiles(&self) -> Result<Vec<Profile>> {
         let config_dir = self.resolve_config_dir()?;
         if !config_dir.exists() {
@@ -286,6 +447,82 @@ impl ConfigManager {
         Ok(profiles)
     }
 
+    /// Writes `profile` and the provider caches it references into a zip archive at `out`.
+    pub async fn export_profile(&self, profile: &str, out: &Path) -> Result<()> {
+        let yaml = self.load(profile).await?;
+        let config: serde_yaml::Value = serde_yaml::from_str(&yaml)?;
+        let config_dir = self.resolve_config_dir()?;
+
+        let mut files = vec![];
+        for relative in archive::provider_paths(&config) {
+            match fs::read(config_dir.join(&relative)).await {
+                Ok(data) => files.push((relative, data)),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        let bytes = archive::write_archive(profile, &yaml, &files)?;
+        fs::write(out, bytes).await?;
+        Ok(())
+    }
+
+    /// Restores a profile written by [`Self::export_profile`], returning its name.
+    ///
+    /// Fails without writing anything if the profile or any of its provider cache
+    /// files already exists, so other profiles' caches are never overwritten.
+    pub async fn import_profile(&self, archive_path: &Path) -> Result<String> {
+        let bytes = fs::read(archive_path).await?;
+        let imported = archive::read_archive(&bytes)?;
+        validate_profile_name(&imported.name)?;
+        serde_yaml::from_str::<serde_yaml::Value>(&imported.yaml)?;
+
+        let config_dir = self.resolve_config_dir()?;
+        let profile_path = config_dir.join(format!("{}.yaml", imported.name));
+        if profile_path.exists() {
+            return Err(MihomoError::config(format!(
+                "Profile '{}' already exists",
+                imported.name
+            )));
+        }
+        let existing: Vec<String> = imported
+            .files
+            .iter()
+            .filter(|(relative, _)| config_dir.join(relative).exists())
+            .map(|(relative, _)| relative.display().to_string())
+            .collect();
+        if !existing.is_empty() {
+            return Err(MihomoError::config(format!(
+                "Provider files already exist: {}",
+                existing.join(", ")
+            )));
+        }
+
+        self.save(&imported.name, &imported.yaml).await?;
+
+        let mut written = vec![];
+        for (relative, data) in &imported.files {
+            let path = config_dir.join(relative);
+            let result = async {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent).await?;
+                }
+                fs::write(&path, data).await
+            }
+            .await;
+            if let Err(err) = result {
+                // Leave the config dir as it was before the import.
+                for path in written.iter().chain([&profile_path]) {
+                    let _ = fs::remove_file(path).await;
+                }
+                return Err(err.into());
+            }
+            written.push(path);
+        }
+
+        Ok(imported.name)
+    }
+
     pub async fn delete_profile(&self, profile: &str) -> Result<()> {
         validate_profile_name(profile)?;
         let config_dir = self.resolve_config_dir()?;
@@ -360,6 +597,98 @@ impl ConfigManager {
         Ok(self.resolve_config_dir()?.join(format!("{}.yaml", profile)))
     }
 
+    /// Polls the active profile every [`WatchOptions::interval`] and calls `on_change`
+    /// with its parsed YAML whenever the content changes and has stayed the same for
+    /// one more poll. The active profile is looked up on every poll, so switching
+    /// profiles with [`Self::set_current`] is reported as a change too. Edits that fail
+    /// to parse are skipped, so a half-saved file is never reported or reloaded.
+    ///
+    /// With [`WatchOptions::with_reload`], each change is also loaded into the running
+    /// core. Watching stops when `cancel` fires.
+    pub async fn watch<F>(
+        &self,
+        options: WatchOptions,
+        cancel: CancellationToken,
+        mut on_change: F,
+    ) -> Result<JoinHandle<()>>
+    where
+        F: FnMut(&serde_yaml::Value) + Send + 'static,
+    {
+        let mut path = self.get_current_path().await?;
+        let mut last = fs::read(&path).await.ok();
+        let mut pending: Option<Vec<u8>> = None;
+        let manager = self.clone();
+
+        Ok(tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = tokio::time::sleep(options.interval) => {}
+                    _ = cancel.cancelled() => return,
+                }
+
+                match manager.get_current_path().await {
+                    Ok(current) if current != path => {
+                        log::debug!("Active profile changed to {}", current.display());
+                        path = current;
+                        last = None;
+                        pending = None;
+                    }
+                    Ok(_) => {}
+                    Err(err) => {
+                        log::warn!("Failed to resolve active profile: {}", err);
+                        continue;
+                    }
+                }
+
+                let Ok(content) = fs::read(&path).await else {
+                    continue;
+                };
+                if last.as_ref() == Some(&content) {
+                    pending = None;
+                    continue;
+                }
+                // Wait for two identical reads so a save still in progress is not picked up.
+                if pending.as_ref() != Some(&content) {
+                    pending = Some(content);
+                    continue;
+                }
+                pending = None;
+                last = Some(content.clone());
+
+                let config = match serde_yaml::from_slice::<serde_yaml::Value>(&content) {
+                    Ok(config) if config.is_mapping() => config,
+                    Ok(_) => {
+                        log::warn!("Ignoring non-mapping config in {}", path.display());
+                        continue;
+                    }
+                    Err(err) => {
+                        log::warn!(
+                            "Ignoring invalid config change in {}: {}",
+                            path.display(),
+                            err
+                        );
+                        continue;
+                    }
+                };
+
+                on_change(&config);
+                if let Some(client) = &options.reload {
+                    let launch = match include::launch_path(&path).await {
+                        Ok(launch) => launch,
+                        Err(err) => {
+                            log::warn!("Failed to resolve {}: {}", path.display(), err);
+                            continue;
+                        }
+                    };
+                    match client.reload_config(launch.to_str()).await {
+                        Ok(()) => log::info!("Reloaded {}", path.display()),
+                        Err(err) => log::warn!("Failed to reload {}: {}", path.display(), err),
+                    }
+                }
+            }
+        }))
+    }
+
     /// Ensure a default config file exists, create one if it doesn't
     pub async fn ensure_default_config(&self) -> Result<()> {
         let profile = self.get_current().await?;
@@ -492,7 +821,7 @@ external-controller: 127.0.0.1:{}
         matches!(host, "127.0.0.1" | "localhost" | "0.0.0.0" | "::1")
     }
 
-    fn normalize_external_controller(controller: &str) -> Result<String> {
+    pub(crate) fn normalize_external_controller(controller: &str) -> Result<String> {
         let controller = controller.trim();
         if controller.is_empty() {
             return Err(Self::invalid_external_controller_error("<empty>"));
@@ -1,5 +1,17 @@
+mod anonymize;
+mod archive;
+pub(crate) mod include;
+pub mod lint;
 pub mod manager;
 pub mod profile;
+pub mod rule;
+pub mod sections;
+pub mod template;
 
-pub use manager::{ConfigDirInfo, ConfigDirSource, ConfigManager};
+pub use anonymize::anonymize_config;
+pub use lint::{lint_rules, validate_rule, validate_rules, LintWarning};
+pub use manager::{ConfigDirInfo, ConfigDirSource, ConfigManager, WatchOptions};
 pub use profile::Profile;
+pub use rule::RuleExpr;
+pub use sections::{SniffProtocol, SnifferConfig, TunConfig};
+pub use template::render_template;
@@ -1,5 +1,6 @@
-use crate::core::Result;
-use std::path::PathBuf;
+use crate::core::{MihomoError, Result};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone)]
 pub struct Profile {
@@ -15,7 +16,7 @@ impl Profile {
 
     pub async fn validate(&self) -> Result<()> {
         if !self.path.exists() {
-            return Err(crate::core::MihomoError::config(format!(
+            return Err(MihomoError::config(format!(
                 "Profile file does not exist: {}",
                 self.path.display()
             )));
@@ -32,6 +33,53 @@ impl Profile {
         tokio::fs::copy(&self.path, &backup_path).await?;
         Ok(backup_path)
     }
+
+    /// Like [`Self::backup`], but gzips the copy into a timestamped
+    /// `<file>.backup.<ts>.gz` next to the profile, so earlier backups are kept.
+    pub async fn backup_compressed(&self) -> Result<PathBuf> {
+        let content = tokio::fs::read(&self.path).await?;
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&content)?;
+        let compressed = encoder.finish()?;
+
+        let file_name = self
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| format!("{}.yaml", self.name));
+        let timestamp = chrono::Utc::now().format("%Y%m%d%H%M%S%3f");
+        let backup_path = self
+            .path
+            .with_file_name(format!("{}.backup.{}.gz", file_name, timestamp));
+        tokio::fs::write(&backup_path, compressed).await?;
+        Ok(backup_path)
+    }
+
+    /// Replaces the profile with `backup`, which may be a plain copy or a `.gz`
+    /// written by [`Self::backup_compressed`]. The backup must be valid YAML.
+    pub async fn restore(&self, backup: &Path) -> Result<()> {
+        let bytes = tokio::fs::read(backup).await?;
+        let content = if backup.extension().is_some_and(|ext| ext == "gz") {
+            let mut decompressed = String::new();
+            flate2::read::GzDecoder::new(bytes.as_slice())
+                .read_to_string(&mut decompressed)
+                .map_err(|e| {
+                    MihomoError::config(format!(
+                        "Failed to decompress backup {}: {}",
+                        backup.display(),
+                        e
+                    ))
+                })?;
+            decompressed
+        } else {
+            String::from_utf8(bytes).map_err(|_| {
+                MihomoError::config(format!("Backup {} is not UTF-8", backup.display()))
+            })?
+        };
+        serde_yaml::from_str::<serde_yaml::Value>(&content)?;
+        tokio::fs::write(&self.path, content).await?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -61,6 +109,47 @@ mod tests {
         assert!(content.contains("external-controller"));
     }
 
+    #[tokio::test]
+    async fn compressed_backup_restores_transparently() {
+        let temp = tempdir().expect("create temp dir");
+        let path = temp.path().join("work.yaml");
+        fs::write(&path, "mixed-port: 7890\n")
+            .await
+            .expect("write config");
+        let profile = Profile::new("work".to_string(), path.clone(), false);
+
+        let gz = profile
+            .backup_compressed()
+            .await
+            .expect("compressed backup");
+        let name = gz.file_name().unwrap().to_string_lossy().into_owned();
+        assert!(name.starts_with("work.yaml.backup.") && name.ends_with(".gz"));
+        assert_ne!(fs::read(&gz).await.unwrap(), b"mixed-port: 7890\n");
+        let plain = profile.backup().await.expect("plain backup");
+
+        fs::write(&path, "mixed-port: 1\n").await.unwrap();
+        profile.restore(&gz).await.expect("restore gz");
+        assert_eq!(
+            fs::read_to_string(&path).await.unwrap(),
+            "mixed-port: 7890\n"
+        );
+
+        fs::write(&path, "mixed-port: 1\n").await.unwrap();
+        profile.restore(&plain).await.expect("restore plain");
+        assert_eq!(
+            fs::read_to_string(&path).await.unwrap(),
+            "mixed-port: 7890\n"
+        );
+
+        let corrupt = temp.path().join("work.yaml.backup.0.gz");
+        fs::write(&corrupt, "not gzip").await.unwrap();
+        assert!(profile.restore(&corrupt).await.is_err());
+        assert_eq!(
+            fs::read_to_string(&path).await.unwrap(),
+            "mixed-port: 7890\n"
+        );
+    }
+
     #[tokio::test]
     async fn validate_errors_for_missing_and_invalid_yaml() {
         let temp = tempdir().expect("create temp dir");
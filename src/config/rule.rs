@@ -0,0 +1,158 @@
+use crate::core::{MihomoError, Result};
+
+/// The condition of a profile rule, with logical rules (`AND`, `OR`, `NOT`) parsed
+/// into their nested conditions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleExpr {
+    /// A plain condition such as `DOMAIN,example.com`; `kind` is upper-cased.
+    Condition {
+        kind: String,
+        payload: String,
+    },
+    And(Vec<RuleExpr>),
+    Or(Vec<RuleExpr>),
+    Not(Box<RuleExpr>),
+}
+
+impl RuleExpr {
+    /// Parses a rule's type and payload, without the target. Logical payloads are a
+    /// parenthesised list of parenthesised conditions, e.g.
+    /// `((DOMAIN,a.com),(OR,((DST-PORT,443),(NETWORK,UDP))))`.
+    pub fn parse(kind: &str, payload: &str) -> Result<Self> {
+        let kind = kind.trim().to_ascii_uppercase();
+        let payload = payload.trim();
+        match kind.as_str() {
+            "AND" | "OR" | "NOT" => {
+                let items = strip_parens(payload)
+                    .map(split_top_level)
+                    .ok_or_else(|| invalid(&kind, payload, "expected ((...),(...))"))?;
+                let mut operands = items
+                    .into_iter()
+                    .map(|item| {
+                        let inner = strip_parens(item).ok_or_else(|| {
+                            invalid(&kind, payload, "operands must be parenthesised")
+                        })?;
+                        let (kind, payload) = inner
+                            .split_once(',')
+                            .ok_or_else(|| invalid(&kind, payload, "operand needs TYPE,PAYLOAD"))?;
+                        Self::parse(kind, payload)
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                match kind.as_str() {
+                    "NOT" if operands.len() == 1 => Ok(Self::Not(Box::new(operands.remove(0)))),
+                    "NOT" => Err(invalid(&kind, payload, "NOT takes exactly one operand")),
+                    _ if operands.is_empty() => Err(invalid(&kind, payload, "no operands")),
+                    "AND" => Ok(Self::And(operands)),
+                    _ => Ok(Self::Or(operands)),
+                }
+            }
+            _ if kind.is_empty() || payload.is_empty() => {
+                Err(invalid(&kind, payload, "expected TYPE,PAYLOAD"))
+            }
+            _ => Ok(Self::Condition {
+                kind,
+                payload: payload.to_string(),
+            }),
+        }
+    }
+
+    pub fn is_logical(&self) -> bool {
+        !matches!(self, Self::Condition { .. })
+    }
+}
+
+fn invalid(kind: &str, payload: &str, reason: &str) -> MihomoError {
+    MihomoError::config(format!(
+        "Invalid {} condition '{}': {}",
+        kind, payload, reason
+    ))
+}
+
+/// `"(a,(b))"` -> `"a,(b)"`, only when the outer parentheses enclose the whole string.
+fn strip_parens(s: &str) -> Option<&str> {
+    let inner = s.trim().strip_prefix('(')?.strip_suffix(')')?;
+    let mut depth = 0i32;
+    for c in inner.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => {}
+        }
+        if depth < 0 {
+            return None;
+        }
+    }
+    (depth == 0).then_some(inner)
+}
+
+/// Splits on commas that are not inside parentheses.
+fn split_top_level(s: &str) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut depth = 0i32;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(s[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(s[start..].trim());
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn condition(kind: &str, payload: &str) -> RuleExpr {
+        RuleExpr::Condition {
+            kind: kind.into(),
+            payload: payload.into(),
+        }
+    }
+
+    #[test]
+    fn parses_nested_logical_rules() {
+        let expr = RuleExpr::parse(
+            "and",
+            "((DOMAIN,x.com),(OR,((DST-PORT,443),(NOT,((NETWORK,UDP))))))",
+        )
+        .unwrap();
+        assert_eq!(
+            expr,
+            RuleExpr::And(vec![
+                condition("DOMAIN", "x.com"),
+                RuleExpr::Or(vec![
+                    condition("DST-PORT", "443"),
+                    RuleExpr::Not(Box::new(condition("NETWORK", "UDP"))),
+                ]),
+            ])
+        );
+        assert!(expr.is_logical());
+        assert!(!RuleExpr::parse("DOMAIN", "x.com").unwrap().is_logical());
+    }
+
+    #[test]
+    fn rejects_malformed_logical_rules() {
+        for (kind, payload) in [
+            ("AND", "(DOMAIN,x.com)"),
+            ("AND", "((DOMAIN,x.com),(DST-PORT,443)"),
+            ("OR", "()"),
+            ("NOT", "((DOMAIN,a.com),(DOMAIN,b.com))"),
+            ("AND", "((DOMAIN))"),
+            ("DOMAIN", ""),
+        ] {
+            assert!(
+                RuleExpr::parse(kind, payload).is_err(),
+                "{} {}",
+                kind,
+                payload
+            );
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use crate::core::{MihomoError, Result};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+
+const TUN_STACKS: [&str; 3] = ["system", "gvisor", "mixed"];
+const SNIFF_PROTOCOLS: [&str; 3] = ["HTTP", "TLS", "QUIC"];
+
+/// The `tun:` section of a profile. Keys not modelled here are kept in `extra`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TunConfig {
+    #[serde(default)]
+    pub enable: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stack: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dns_hijack: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_route: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_detect_interface: Option<bool>,
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_yaml::Value>,
+}
+
+impl TunConfig {
+    pub fn validate(&self) -> Result<()> {
+        if let Some(stack) = &self.stack {
+            if !TUN_STACKS.iter().any(|s| s.eq_ignore_ascii_case(stack)) {
+                return Err(MihomoError::config(format!(
+                    "Invalid tun stack '{}', expected one of: {}",
+                    stack,
+                    TUN_STACKS.join(", ")
+                )));
+            }
+        }
+        for target in &self.dns_hijack {
+            // Accepts "any:53", "198.18.0.2:53" and "tcp://any:53".
+            let addr = target.split_once("://").map_or(target.as_str(), |(_, a)| a);
+            let valid = addr
+                .rsplit_once(':')
+                .is_some_and(|(host, port)| !host.is_empty() && parse_port(port).is_some());
+            if !valid {
+                return Err(MihomoError::config(format!(
+                    "Invalid tun dns-hijack target '{}', expected host:port",
+                    target
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The `sniffer:` section of a profile. Keys not modelled here are kept in `extra`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SnifferConfig {
+    #[serde(default)]
+    pub enable: bool,
+    /// Keyed by protocol: `HTTP`, `TLS` or `QUIC`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub sniff: BTreeMap<String, SniffProtocol>,
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_yaml::Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SniffProtocol {
+    /// Single ports (`"443"`) or inclusive ranges (`"8080-8880"`).
+    #[serde(default, deserialize_with = "deserialize_ports")]
+    pub ports: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub override_destination: Option<bool>,
+}
+
+impl SnifferConfig {
+    pub fn validate(&self) -> Result<()> {
+        for (protocol, settings) in &self.sniff {
+            if !SNIFF_PROTOCOLS
+                .iter()
+                .any(|p| p.eq_ignore_ascii_case(protocol))
+            {
+                return Err(MihomoError::config(format!(
+                    "Unknown sniff protocol '{}', expected one of: {}",
+                    protocol,
+                    SNIFF_PROTOCOLS.join(", ")
+                )));
+            }
+            for ports in &settings.ports {
+                let valid = match ports.split_once('-') {
+                    Some((start, end)) => {
+                        matches!((parse_port(start), parse_port(end)), (Some(s), Some(e)) if s <= e)
+                    }
+                    None => parse_port(ports).is_some(),
+                };
+                if !valid {
+                    return Err(MihomoError::config(format!(
+                        "Invalid {} sniff port '{}'",
+                        protocol, ports
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_port(port: &str) -> Option<u16> {
+    port.trim().parse::<u16>().ok().filter(|port| *port != 0)
+}
+
+/// mihomo accepts both `443` and `"8080-8880"` in the same list.
+fn deserialize_ports<'de, D>(deserializer: D) -> std::result::Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Port {
+        Number(u64),
+        Text(String),
+    }
+
+    let ports = Option::<Vec<Port>>::deserialize(deserializer)?.unwrap_or_default();
+    Ok(ports
+        .into_iter()
+        .map(|port| match port {
+            Port::Number(n) => n.to_string(),
+            Port::Text(s) => s,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tun_round_trips_unknown_keys_and_validates() {
+        let tun: TunConfig = serde_yaml::from_str(
+            "enable: true\nstack: gVisor\ndns-hijack: [any:53, 'tcp://any:53']\nauto-route: true\nmtu: 9000\n",
+        )
+        .unwrap();
+        assert_eq!(tun.stack.as_deref(), Some("gVisor"));
+        assert_eq!(tun.auto_route, Some(true));
+        assert!(tun.validate().is_ok());
+
+        let yaml = serde_yaml::to_value(&tun).unwrap();
+        assert_eq!(yaml["mtu"].as_u64(), Some(9000));
+        assert!(yaml.get("auto-detect-interface").is_none());
+
+        let bad_stack = TunConfig {
+            stack: Some("lwip".into()),
+            ..tun.clone()
+        };
+        assert!(bad_stack.validate().is_err());
+        let bad_hijack = TunConfig {
+            dns_hijack: vec!["any".into()],
+            ..tun
+        };
+        assert!(bad_hijack.validate().is_err());
+    }
+
+    #[test]
+    fn sniffer_accepts_mixed_port_lists() {
+        let sniffer: SnifferConfig = serde_yaml::from_str(
+            "enable: true\nsniff:\n  HTTP:\n    ports: [80, 8080-8880]\n    override-destination: true\n  TLS:\n    ports: [443]\nskip-domain: [example.com]\n",
+        )
+        .unwrap();
+        assert_eq!(sniffer.sniff["HTTP"].ports, vec!["80", "8080-8880"]);
+        assert!(sniffer.extra.contains_key("skip-domain"));
+        assert!(sniffer.validate().is_ok());
+
+        let mut bad = sniffer.clone();
+        bad.sniff.get_mut("TLS").unwrap().ports = vec!["9000-80".into()];
+        assert!(bad.validate().is_err());
+
+        let mut unknown = sniffer;
+        unknown
+            .sniff
+            .insert("SMTP".into(), SniffProtocol::default());
+        assert!(unknown.validate().is_err());
+    }
+}
@@ -0,0 +1,85 @@
+use crate::core::{MihomoError, Result};
+use std::collections::{BTreeSet, HashMap};
+
+/// Replaces every `${NAME}` in `template` with `vars[NAME]`; `$${NAME}` is kept as a
+/// literal `${NAME}`. All unresolved names are reported in one error.
+pub fn render_template(template: &str, vars: &HashMap<String, String>) -> Result<String> {
+    let mut out = String::with_capacity(template.len());
+    let mut missing = BTreeSet::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find("${") {
+        let escaped = rest[..start].ends_with('$');
+        out.push_str(&rest[..start - usize::from(escaped)]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            return Err(MihomoError::config(format!(
+                "Unterminated template variable at '{}'",
+                rest[start..].lines().next().unwrap_or_default()
+            )));
+        };
+        let name = &after[..end];
+        if escaped {
+            out.push_str(&rest[start..start + 2 + end + 1]);
+        } else {
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return Err(MihomoError::config(format!(
+                    "Invalid template variable name '{}'",
+                    name
+                )));
+            }
+            match vars.get(name) {
+                Some(value) => out.push_str(value),
+                None => {
+                    missing.insert(name);
+                }
+            }
+        }
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+
+    if !missing.is_empty() {
+        return Err(MihomoError::config(format!(
+            "Unresolved template variables: {}",
+            missing.into_iter().collect::<Vec<_>>().join(", ")
+        )));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(entries: &[(&str, &str)]) -> HashMap<String, String> {
+        entries
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn substitutes_variables_and_keeps_escapes() {
+        let rendered = render_template(
+            "mixed-port: ${PORT}\nsecret: \"${SECRET}\"\nnote: $${PORT} ${PORT}\n",
+            &vars(&[("PORT", "7890"), ("SECRET", "s3cr3t")]),
+        )
+        .unwrap();
+        assert_eq!(
+            rendered,
+            "mixed-port: 7890\nsecret: \"s3cr3t\"\nnote: ${PORT} 7890\n"
+        );
+    }
+
+    #[test]
+    fn reports_unresolved_and_malformed_variables() {
+        let err = render_template("a: ${B}\nc: ${A}\nd: ${B}", &HashMap::new())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("A, B"), "{}", err);
+
+        assert!(render_template("a: ${A", &vars(&[("A", "1")])).is_err());
+        assert!(render_template("a: ${A-B}", &vars(&[("A", "1")])).is_err());
+    }
+}
@@ -0,0 +1,101 @@
+use crate::core::{Connection, ConnectionsResponse, Rule};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// A point-in-time dump of the active connections, written by
+/// [`ConnectionManager::export_json`](super::ConnectionManager::export_json).
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionExport {
+    /// RFC 3339 timestamp.
+    pub exported_at: String,
+    pub download_total: u64,
+    pub upload_total: u64,
+    pub connections: Vec<ConnectionRecord>,
+}
+
+/// A connection as reported by mihomo, plus the values derived from it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionRecord {
+    #[serde(flatten)]
+    pub connection: Connection,
+    pub duration_secs: Option<u64>,
+    pub final_proxy: Option<String>,
+    /// Position of the routing rule in the controller's rule list.
+    pub rule_index: Option<usize>,
+    /// The routing rule's target as configured, e.g. a group name.
+    pub rule_target: Option<String>,
+}
+
+impl ConnectionExport {
+    pub fn build(response: ConnectionsResponse, rules: &[Rule], now: DateTime<Utc>) -> Self {
+        let connections = response
+            .connections
+            .into_iter()
+            .map(|connection| {
+                let rule = connection.matching_rule(rules);
+                ConnectionRecord {
+                    duration_secs: connection.duration_at(now).map(|d| d.as_secs()),
+                    final_proxy: connection.final_proxy().map(str::to_string),
+                    rule_index: rule.map(|(index, _)| index),
+                    rule_target: rule.map(|(_, rule)| rule.proxy.clone()),
+                    connection,
+                }
+            })
+            .collect();
+
+        Self {
+            exported_at: now.to_rfc3339(),
+            download_total: response.download_total,
+            upload_total: response.upload_total,
+            connections,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_adds_duration_and_resolved_rule() {
+        let response: ConnectionsResponse = serde_json::from_str(
+            r#"{
+                "downloadTotal": 300,
+                "uploadTotal": 100,
+                "connections": [
+                    {"id":"c1","metadata":{"host":"example.com"},"upload":10,"download":20,
+                     "start":"2024-01-01T00:00:00Z","chains":["HK-01","Proxy"],
+                     "rule":"DomainSuffix","rulePayload":"example.com"},
+                    {"id":"c2","start":"bogus","chains":["DIRECT"],"rule":"Match"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let rules: Vec<Rule> = serde_json::from_str(
+            r#"[{"type":"DomainSuffix","payload":"example.com","proxy":"Proxy"}]"#,
+        )
+        .unwrap();
+        let now = DateTime::parse_from_rfc3339("2024-01-01T00:01:30Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let export = ConnectionExport::build(response, &rules, now);
+        let json = serde_json::to_value(&export).unwrap();
+
+        assert_eq!(json["exportedAt"], "2024-01-01T00:01:30+00:00");
+        assert_eq!(json["downloadTotal"], 300);
+        let first = &json["connections"][0];
+        assert_eq!(first["id"], "c1");
+        assert_eq!(first["metadata"]["host"], "example.com");
+        assert_eq!(first["durationSecs"], 90);
+        assert_eq!(first["finalProxy"], "HK-01");
+        assert_eq!(first["ruleIndex"], 0);
+        assert_eq!(first["ruleTarget"], "Proxy");
+
+        let second = &json["connections"][1];
+        assert!(second["durationSecs"].is_null());
+        assert!(second["ruleIndex"].is_null());
+    }
+}
@@ -0,0 +1,92 @@
+use crate::core::ConnectionSnapshot;
+use std::collections::HashMap;
+
+/// Flags connections whose byte counters stop moving across consecutive snapshots.
+///
+/// Feed it every snapshot from `ConnectionManager::stream` (or successive `get_all`
+/// calls); connections that disappear from a snapshot are forgotten.
+#[derive(Debug, Default)]
+pub struct IdleTracker {
+    connections: HashMap<String, Activity>,
+}
+
+#[derive(Debug)]
+struct Activity {
+    bytes: (u64, u64),
+    unchanged: usize,
+}
+
+impl IdleTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn observe(&mut self, snapshot: &ConnectionSnapshot) {
+        let mut next = HashMap::with_capacity(snapshot.connections.len());
+        for conn in &snapshot.connections {
+            let bytes = (conn.upload, conn.download);
+            let unchanged = match self.connections.get(&conn.id) {
+                Some(prev) if prev.bytes == bytes => prev.unchanged + 1,
+                _ => 0,
+            };
+            next.insert(conn.id.clone(), Activity { bytes, unchanged });
+        }
+        self.connections = next;
+    }
+
+    /// IDs of connections that moved no bytes over the last `samples` snapshots, sorted.
+    pub fn idle_connections(&self, samples: usize) -> Vec<String> {
+        let mut idle: Vec<String> = self
+            .connections
+            .iter()
+            .filter(|(_, activity)| activity.unchanged >= samples.max(1))
+            .map(|(id, _)| id.clone())
+            .collect();
+        idle.sort();
+        idle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_connections_without_byte_changes() {
+        let mut tracker = IdleTracker::new();
+        tracker.observe(&ConnectionSnapshot::fixture(&[
+            ("a", 10, 10),
+            ("b", 10, 10),
+        ]));
+        assert!(tracker.idle_connections(1).is_empty());
+
+        tracker.observe(&ConnectionSnapshot::fixture(&[
+            ("a", 10, 10),
+            ("b", 10, 20),
+        ]));
+        assert_eq!(tracker.idle_connections(1), vec!["a".to_string()]);
+        assert!(tracker.idle_connections(2).is_empty());
+
+        tracker.observe(&ConnectionSnapshot::fixture(&[
+            ("a", 10, 10),
+            ("b", 10, 20),
+        ]));
+        assert_eq!(tracker.idle_connections(2), vec!["a".to_string()]);
+        assert_eq!(
+            tracker.idle_connections(1),
+            vec!["a".to_string(), "b".to_string()]
+        );
+    }
+
+    #[test]
+    fn forgets_closed_connections() {
+        let mut tracker = IdleTracker::new();
+        tracker.observe(&ConnectionSnapshot::fixture(&[("a", 1, 1)]));
+        tracker.observe(&ConnectionSnapshot::fixture(&[("a", 1, 1)]));
+        tracker.observe(&ConnectionSnapshot::fixture(&[]));
+        assert!(tracker.idle_connections(1).is_empty());
+
+        tracker.observe(&ConnectionSnapshot::fixture(&[("a", 1, 1)]));
+        assert!(tracker.idle_connections(1).is_empty());
+    }
+}
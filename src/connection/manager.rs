@@ -1,4 +1,9 @@
-use crate::core::{Connection, ConnectionSnapshot, ConnectionsResponse, MihomoClient, Result};
+use super::export::ConnectionExport;
+use crate::core::{
+    Connection, ConnectionSnapshot, ConnectionsResponse, HostBandwidth, MihomoClient,
+    ProxyBandwidth, Result, Rule,
+};
+use std::path::Path;
 
 pub struct ConnectionManager {
     client: MihomoClient,
@@ -78,10 +83,46 @@ impl ConnectionManager {
         ))
     }
 
+    /// The `n` proxies carrying the most bytes across active connections.
+    pub async fn top_proxies_by_bandwidth(&self, n: usize) -> Result<Vec<ProxyBandwidth>> {
+        let response = self.client.get_connections().await?;
+        let mut usage = response.bandwidth_by_proxy();
+        usage.truncate(n);
+        Ok(usage)
+    }
+
+    /// Active connections grouped by destination host, busiest first.
+    pub async fn connections_by_host(&self) -> Result<Vec<HostBandwidth>> {
+        Ok(self.client.get_connections().await?.bandwidth_by_host())
+    }
+
     pub async fn stream(&self) -> Result<tokio::sync::mpsc::UnboundedReceiver<ConnectionSnapshot>> {
         self.client.stream_connections().await
     }
 
+    /// Looks up the configured rule that routed `connection`, with its index in the rule list.
+    pub async fn matching_rule(&self, connection: &Connection) -> Result<Option<(usize, Rule)>> {
+        let rules = self.client.get_rules().await?;
+        Ok(connection
+            .matching_rule(&rules)
+            .map(|(index, rule)| (index, rule.clone())))
+    }
+
+    /// Writes the active connections, with durations and resolved rules, to `path` as
+    /// pretty-printed JSON. Returns the number of connections written.
+    pub async fn export_json(&self, path: &Path) -> Result<usize> {
+        let response = self.client.get_connections().await?;
+        let rules = self.client.get_rules().await?;
+        let export = ConnectionExport::build(response, &rules, chrono::Utc::now());
+        tokio::fs::write(path, serde_json::to_vec_pretty(&export)?).await?;
+        log::debug!(
+            "Exported {} connections to {}",
+            export.connections.len(),
+            path.display()
+        );
+        Ok(export.connections.len())
+    }
+
     pub async fn close_by_host(&self, host: &str) -> Result<usize> {
         let connections = self.filter_by_host(host).await?;
         let count = connections.len();
@@ -117,7 +158,6 @@ mod tests {
     // Helper function to create test connection
     fn create_test_connection(id: &str, host: &str, process: &str, rule: &str) -> Connection {
         Connection {
-            id: id.to_string(),
             metadata: ConnectionMetadata {
                 network: "tcp".to_string(),
                 connection_type: "HTTP".to_string(),
@@ -130,12 +170,10 @@ mod tests {
                 process_path: process.to_string(),
                 special_proxy: String::new(),
             },
-            upload: 1024,
-            download: 2048,
             start: "2024-01-01T00:00:00Z".to_string(),
             chains: vec!["DIRECT".to_string()],
             rule: rule.to_string(),
-            rule_payload: String::new(),
+            ..Connection::fixture(id, 1024, 2048)
         }
     }
 
@@ -1,3 +1,9 @@
+pub mod export;
+pub mod idle;
 pub mod manager;
+pub mod tracker;
 
+pub use export::{ConnectionExport, ConnectionRecord};
+pub use idle::IdleTracker;
 pub use manager::ConnectionManager;
+pub use tracker::{ConnectionEvent, ConnectionStats, ConnectionTracker};
@@ -0,0 +1,222 @@
+use crate::core::{Connection, ConnectionSnapshot};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// A change between two consecutive connection snapshots.
+#[derive(Debug, Clone)]
+pub enum ConnectionEvent {
+    Opened(Connection),
+    /// Byte counters or chain changed since the previous snapshot.
+    Updated(Connection),
+    /// Last state seen before the connection disappeared.
+    Closed(Connection),
+}
+
+impl ConnectionEvent {
+    pub fn connection(&self) -> &Connection {
+        match self {
+            Self::Opened(conn) | Self::Updated(conn) | Self::Closed(conn) => conn,
+        }
+    }
+}
+
+/// Live transfer rate of one connection, measured between the last two snapshots
+/// it appeared in. Speeds are zero for a connection seen only once.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConnectionStats {
+    pub id: String,
+    pub upload: u64,
+    pub download: u64,
+    /// Bytes per second.
+    pub upload_speed: f64,
+    /// Bytes per second.
+    pub download_speed: f64,
+}
+
+impl ConnectionStats {
+    pub fn total_speed(&self) -> f64 {
+        self.upload_speed + self.download_speed
+    }
+}
+
+#[derive(Debug)]
+struct Tracked {
+    connection: Connection,
+    upload_speed: f64,
+    download_speed: f64,
+}
+
+/// Turns the full snapshots from `ConnectionManager::stream` into per-connection
+/// events by diffing on connection id.
+#[derive(Debug, Default)]
+pub struct ConnectionTracker {
+    connections: HashMap<String, Tracked>,
+    observed_at: Option<Instant>,
+}
+
+fn per_second(bytes: u64, elapsed: Duration) -> f64 {
+    if elapsed.is_zero() {
+        0.0
+    } else {
+        bytes as f64 / elapsed.as_secs_f64()
+    }
+}
+
+impl ConnectionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records `snapshot` and returns what changed since the previous one. Opened and
+    /// updated events follow snapshot order; closed events come last, sorted by id.
+    pub fn observe(&mut self, snapshot: &ConnectionSnapshot) -> Vec<ConnectionEvent> {
+        self.observe_at(snapshot, Instant::now())
+    }
+
+    /// Like [`observe`](Self::observe), with an explicit capture time for speed
+    /// calculations.
+    pub fn observe_at(
+        &mut self,
+        snapshot: &ConnectionSnapshot,
+        now: Instant,
+    ) -> Vec<ConnectionEvent> {
+        let elapsed = self
+            .observed_at
+            .map(|prev| now.saturating_duration_since(prev))
+            .unwrap_or_default();
+        let mut events = vec![];
+        let mut next = HashMap::with_capacity(snapshot.connections.len());
+
+        for conn in &snapshot.connections {
+            let (upload_speed, download_speed) = match self.connections.remove(&conn.id) {
+                None => {
+                    events.push(ConnectionEvent::Opened(conn.clone()));
+                    (0.0, 0.0)
+                }
+                Some(Tracked {
+                    connection: prev, ..
+                }) => {
+                    if prev.upload != conn.upload
+                        || prev.download != conn.download
+                        || prev.chains != conn.chains
+                    {
+                        events.push(ConnectionEvent::Updated(conn.clone()));
+                    }
+                    (
+                        per_second(conn.upload.saturating_sub(prev.upload), elapsed),
+                        per_second(conn.download.saturating_sub(prev.download), elapsed),
+                    )
+                }
+            };
+            next.insert(
+                conn.id.clone(),
+                Tracked {
+                    connection: conn.clone(),
+                    upload_speed,
+                    download_speed,
+                },
+            );
+        }
+
+        self.observed_at = Some(now);
+        let mut closed: Vec<Connection> = std::mem::replace(&mut self.connections, next)
+            .into_values()
+            .map(|tracked| tracked.connection)
+            .collect();
+        closed.sort_by(|a, b| a.id.cmp(&b.id));
+        events.extend(closed.into_iter().map(ConnectionEvent::Closed));
+        events
+    }
+
+    /// Per-connection speeds from the last snapshot, fastest (upload + download) first.
+    pub fn stats(&self) -> Vec<ConnectionStats> {
+        let mut stats: Vec<ConnectionStats> = self
+            .connections
+            .values()
+            .map(|tracked| ConnectionStats {
+                id: tracked.connection.id.clone(),
+                upload: tracked.connection.upload,
+                download: tracked.connection.download,
+                upload_speed: tracked.upload_speed,
+                download_speed: tracked.download_speed,
+            })
+            .collect();
+        stats.sort_by(|a, b| {
+            b.total_speed()
+                .total_cmp(&a.total_speed())
+                .then_with(|| a.id.cmp(&b.id))
+        });
+        stats
+    }
+
+    /// Connections from the last observed snapshot.
+    pub fn len(&self) -> usize {
+        self.connections.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.connections.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(events: &[ConnectionEvent]) -> Vec<String> {
+        events
+            .iter()
+            .map(|event| match event {
+                ConnectionEvent::Opened(c) => format!("+{}", c.id),
+                ConnectionEvent::Updated(c) => format!("~{}", c.id),
+                ConnectionEvent::Closed(c) => format!("-{}", c.id),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn diffs_successive_snapshots_by_id() {
+        let mut tracker = ConnectionTracker::new();
+        let events = tracker.observe(&ConnectionSnapshot::fixture(&[("a", 1, 1), ("b", 1, 1)]));
+        assert_eq!(summary(&events), vec!["+a", "+b"]);
+
+        let events = tracker.observe(&ConnectionSnapshot::fixture(&[
+            ("b", 1, 5),
+            ("c", 0, 0),
+            ("a", 1, 1),
+        ]));
+        assert_eq!(summary(&events), vec!["~b", "+c"]);
+
+        let events = tracker.observe(&ConnectionSnapshot::fixture(&[("c", 0, 0)]));
+        assert_eq!(summary(&events), vec!["-a", "-b"]);
+        assert_eq!(events[1].connection().download, 5);
+        assert_eq!(tracker.len(), 1);
+
+        assert!(tracker
+            .observe(&ConnectionSnapshot::fixture(&[("c", 0, 0)]))
+            .is_empty());
+    }
+
+    #[test]
+    fn stats_report_speed_between_snapshots() {
+        let mut tracker = ConnectionTracker::new();
+        let start = Instant::now();
+        tracker.observe_at(
+            &ConnectionSnapshot::fixture(&[("a", 0, 0), ("b", 100, 100)]),
+            start,
+        );
+        assert!(tracker.stats().iter().all(|s| s.total_speed() == 0.0));
+
+        tracker.observe_at(
+            &ConnectionSnapshot::fixture(&[("a", 1000, 4000), ("b", 100, 300), ("c", 50, 50)]),
+            start + Duration::from_secs(2),
+        );
+        let stats = tracker.stats();
+        let ids: Vec<&str> = stats.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b", "c"]);
+        assert_eq!(stats[0].upload_speed, 500.0);
+        assert_eq!(stats[0].download_speed, 2000.0);
+        assert_eq!(stats[1].download_speed, 100.0);
+        assert_eq!(stats[2].total_speed(), 0.0);
+    }
+}
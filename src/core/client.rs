@@ -1,10 +1,14 @@
-use super::error::Result;
+use super::error::{MihomoError, Result};
+use super::rate_limit::RateLimiter;
+use super::reconnect::ReconnectPolicy;
 use super::types::*;
 use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use reqwest::header::{HeaderName, HeaderValue};
 use reqwest::Client;
 use serde_json::json;
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::Duration;
 use url::Url;
 
@@ -24,17 +28,78 @@ const PATH_SEGMENT_ENCODE_SET: &AsciiSet = &CONTROLS
     .add(b'|')
     .add(b'}');
 
+async fn health_probe<T>(
+    name: &str,
+    probe: impl std::future::Future<Output = Result<T>>,
+) -> Option<T> {
+    match tokio::time::timeout(HEALTH_PROBE_TIMEOUT, probe).await {
+        Ok(Ok(value)) => Some(value),
+        Ok(Err(err)) => {
+            log::debug!("Health probe '{}' failed: {}", name, err);
+            None
+        }
+        Err(_) => {
+            log::debug!("Health probe '{}' timed out", name);
+            None
+        }
+    }
+}
+
 #[derive(Clone)]
 enum Transport {
     Tcp { client: Client, base_url: Url },
     Unix { socket_path: PathBuf },
 }
 
+/// Summary of one controller HTTP request, passed to a request observer.
+#[derive(Debug, Clone)]
+pub struct RequestTrace {
+    pub method: String,
+    pub path: String,
+    /// Response status, or `None` if no response was received.
+    pub status: Option<u16>,
+    pub duration: Duration,
+    pub error: Option<String>,
+}
+
+pub type RequestObserver = Arc<dyn Fn(RequestTrace) + Send + Sync>;
+
 #[derive(Clone)]
 pub struct MihomoClient {
     transport: Transport,
     secret: Option<String>,
     ws_connect_timeout: Duration,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    request_observer: Option<RequestObserver>,
+    headers: Vec<(String, String)>,
+    debug: bool,
+}
+
+const DEFAULT_USER_AGENT: &str = "mihomo-rs";
+const SECRET_VERIFY_TIMEOUT: Duration = Duration::from_secs(5);
+const HEALTH_PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Checks an `expected` status list such as `"200"` or `"200-299/301"`.
+fn validate_expected_status(expected: &str) -> Result<()> {
+    let is_status = |code: &str| {
+        code.len() == 3
+            && code
+                .parse::<u16>()
+                .is_ok_and(|code| (100..600).contains(&code))
+    };
+    let valid = !expected.is_empty()
+        && expected.split('/').all(|part| match part.split_once('-') {
+            Some((start, end)) => is_status(start) && is_status(end) && start <= end,
+            None => is_status(part),
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(MihomoError::config(format!(
+            "Invalid expected status '{}', use codes or ranges like 200-299/301",
+            expected
+        )))
+    }
 }
 
 impl MihomoClient {
@@ -59,9 +124,58 @@ impl MihomoClient {
             transport,
             secret,
             ws_connect_timeout: Duration::from_secs(10),
+            rate_limiter: None,
+            request_observer: None,
+            headers: vec![("User-Agent".to_string(), DEFAULT_USER_AGENT.to_string())],
+            debug: false,
         })
     }
 
+    /// Throttles HTTP requests to at most `requests_per_second`, with bursts of the
+    /// same size. The limit is shared by every clone of this client.
+    pub fn with_rate_limit(mut self, requests_per_second: u32) -> Self {
+        self.rate_limiter = Some(Arc::new(RateLimiter::new(requests_per_second)));
+        self
+    }
+
+    /// Calls `observer` after every HTTP request with its method, path, status and timing.
+    /// Rate-limit waits are not included in the duration.
+    pub fn with_request_observer(mut self, observer: RequestObserver) -> Self {
+        self.request_observer = Some(observer);
+        self
+    }
+
+    /// Logs every HTTP request and response (method, path, query, body, status) at
+    /// debug level, with the secret redacted. Bodies are cut off after 4 KiB.
+    pub fn with_debug(mut self, debug: bool) -> Self {
+        self.debug = debug;
+        self
+    }
+
+    /// Replaces the default `mihomo-rs` User-Agent sent with every HTTP and WebSocket request.
+    pub fn with_user_agent(self, user_agent: impl Into<String>) -> Self {
+        self.with_header("User-Agent", user_agent)
+    }
+
+    /// Adds a header to every HTTP and WebSocket request, replacing any earlier value
+    /// for the same name. Invalid names or values are logged and ignored.
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        let (name, value) = (name.into(), value.into());
+        let valid = HeaderName::from_bytes(name.as_bytes()).is_ok()
+            && HeaderValue::from_str(&value)
+                .map(|v| v.to_str().is_ok())
+                .unwrap_or(false);
+        if !valid {
+            log::warn!("Ignoring invalid request header '{}'", name);
+            return self;
+        }
+
+        self.headers
+            .retain(|(existing, _)| !existing.eq_ignore_ascii_case(&name));
+        self.headers.push((name, value));
+        self
+    }
+
     fn encode_path_segment(input: &str) -> String {
         utf8_percent_encode(input, PATH_SEGMENT_ENCODE_SET).to_string()
     }
@@ -71,12 +185,92 @@ impl MihomoClient {
         Ok(serde_json::from_slice(&response)?)
     }
 
+    /// Polls `/version` with backoff until the controller answers or `timeout` runs
+    /// out, for use right after the core is started.
+    pub async fn wait_until_ready(&self, timeout: Duration) -> Result<Version> {
+        let policy = ReconnectPolicy::default()
+            .with_initial_delay(Duration::from_millis(100))
+            .with_max_delay(Duration::from_secs(1));
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut attempt = 0;
+
+        loop {
+            let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+            let err = match tokio::time::timeout(remaining, self.get_version()).await {
+                Ok(Ok(version)) => return Ok(version),
+                Ok(Err(err)) => err.to_string(),
+                Err(_) => "request timed out".to_string(),
+            };
+            let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+            if remaining.is_zero() {
+                return Err(MihomoError::Service(format!(
+                    "Controller not ready after {:.1}s: {}",
+                    timeout.as_secs_f64(),
+                    err
+                )));
+            }
+            attempt += 1;
+            log::debug!("Controller not ready (attempt {}): {}", attempt, err);
+            tokio::time::sleep(policy.delay_for(attempt).min(remaining)).await;
+        }
+    }
+
     pub async fn get_proxies(&self) -> Result<HashMap<String, ProxyInfo>> {
+        let parsed = self.get_proxies_lenient().await?;
+        match parsed.skipped.into_iter().next() {
+            Some((name, err)) => Err(MihomoError::Proxy(format!(
+                "Invalid entry for proxy '{}': {}",
+                name, err
+            ))),
+            None => Ok(parsed.proxies),
+        }
+    }
+
+    /// Like [`Self::get_proxies`], but entries that fail to parse are reported in
+    /// `skipped` instead of failing the whole call.
+    pub async fn get_proxies_lenient(&self) -> Result<ParsedProxies> {
         log::debug!("Fetching proxies");
         let response = self.http_request("GET", "/proxies", None, None).await?;
-        let data: ProxiesResponse = serde_json::from_slice(&response)?;
-        log::debug!("Received {} proxies", data.proxies.len());
-        Ok(data.proxies)
+        let entries = Self::proxies_object(&response)?;
+
+        let mut parsed = ParsedProxies::default();
+        for (name, value) in entries {
+            match serde_json::from_value::<ProxyInfo>(value) {
+                Ok(info) => {
+                    parsed.proxies.insert(name, info);
+                }
+                Err(err) => {
+                    log::warn!("Skipping proxy '{}': {}", name, err);
+                    parsed.skipped.push((name, err.to_string()));
+                }
+            }
+        }
+        parsed.skipped.sort();
+        log::debug!(
+            "Received {} proxies, skipped {}",
+            parsed.proxies.len(),
+            parsed.skipped.len()
+        );
+        Ok(parsed)
+    }
+
+    /// Extracts the `proxies` object, treating `null` as empty. Valid JSON without a
+    /// `proxies` object usually means the URL doesn't point at a mihomo controller.
+    fn proxies_object(response: &[u8]) -> Result<serde_json::Map<String, serde_json::Value>> {
+        let unexpected = |detail: &str| {
+            MihomoError::Service(format!(
+                "Unexpected /proxies response ({}); is the controller URL correct?",
+                detail
+            ))
+        };
+
+        let body: serde_json::Value = serde_json::from_slice(response)?;
+        match body.get("proxies") {
+            Some(serde_json::Value::Object(entries)) => Ok(entries.clone()),
+            Some(serde_json::Value::Null) => Ok(serde_json::Map::new()),
+            Some(_) => Err(unexpected("'proxies' is not an object")),
+            None => Err(unexpected("missing 'proxies'")),
+        }
     }
 
     pub async fn get_proxy(&self, name: &str) -> Result<ProxyInfo> {
@@ -87,6 +281,61 @@ impl MihomoClient {
         Ok(serde_json::from_slice(&response)?)
     }
 
+    pub async fn get_providers(&self) -> Result<HashMap<String, Provider>> {
+        let response = self
+            .http_request("GET", "/providers/proxies", None, None)
+            .await?;
+        let data: ProvidersResponse = serde_json::from_slice(&response)?;
+        Ok(data.providers)
+    }
+
+    pub async fn get_provider(&self, name: &str) -> Result<Provider> {
+        let encoded_name = Self::encode_path_segment(name);
+        let response = self
+            .http_request(
+                "GET",
+                &format!("/providers/proxies/{}", encoded_name),
+                None,
+                None,
+            )
+            .await?;
+        Ok(serde_json::from_slice(&response)?)
+    }
+
+    pub async fn update_provider(&self, name: &str) -> Result<()> {
+        let encoded_name = Self::encode_path_segment(name);
+        log::debug!("Updating proxy provider '{}'", name);
+        self.http_request(
+            "PUT",
+            &format!("/providers/proxies/{}", encoded_name),
+            None,
+            None,
+        )
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_rule_providers(&self) -> Result<HashMap<String, RuleProvider>> {
+        let response = self
+            .http_request("GET", "/providers/rules", None, None)
+            .await?;
+        let data: RuleProvidersResponse = serde_json::from_slice(&response)?;
+        Ok(data.providers)
+    }
+
+    pub async fn update_rule_provider(&self, name: &str) -> Result<()> {
+        let encoded_name = Self::encode_path_segment(name);
+        log::debug!("Updating rule provider '{}'", name);
+        self.http_request(
+            "PUT",
+            &format!("/providers/rules/{}", encoded_name),
+            None,
+            None,
+        )
+        .await?;
+        Ok(())
+    }
+
     pub async fn switch_proxy(&self, group: &str, proxy: &str) -> Result<()> {
         let encoded_group = Self::encode_path_segment(group);
         log::debug!("Switching group '{}' to proxy '{}'", group, proxy);
@@ -102,15 +351,45 @@ impl MihomoClient {
     }
 
     pub async fn test_delay(&self, proxy: &str, test_url: &str, timeout: u32) -> Result<u32> {
+        self.proxy_delay(proxy, test_url, timeout, None).await
+    }
+
+    /// Like [`Self::test_delay`], but counts the test as passed only when `test_url`
+    /// answers with a status in `expected`, instead of mihomo's default of 204.
+    /// `expected` uses mihomo's syntax: codes and ranges joined by `/`, e.g.
+    /// `"200"` or `"200-299/301"`.
+    pub async fn test_delay_expecting(
+        &self,
+        proxy: &str,
+        test_url: &str,
+        timeout: u32,
+        expected: &str,
+    ) -> Result<u32> {
+        validate_expected_status(expected)?;
+        self.proxy_delay(proxy, test_url, timeout, Some(expected))
+            .await
+    }
+
+    async fn proxy_delay(
+        &self,
+        proxy: &str,
+        test_url: &str,
+        timeout: u32,
+        expected: Option<&str>,
+    ) -> Result<u32> {
         let encoded_proxy = Self::encode_path_segment(proxy);
+        let mut query = vec![
+            ("timeout", timeout.to_string()),
+            ("url", test_url.to_string()),
+        ];
+        if let Some(expected) = expected {
+            query.push(("expected", expected.to_string()));
+        }
         let response = self
             .http_request(
                 "GET",
                 &format!("/proxies/{}/delay", encoded_proxy),
-                Some(&[
-                    ("timeout", timeout.to_string()),
-                    ("url", test_url.to_string()),
-                ]),
+                Some(&query),
                 None,
             )
             .await?;
@@ -118,6 +397,111 @@ impl MihomoClient {
         Ok(data.delay)
     }
 
+    /// Like [`Self::test_delay`], but classifies failures so a timed-out or unreachable
+    /// node can be told apart from a failed API call.
+    pub async fn test_delay_outcome(
+        &self,
+        proxy: &str,
+        test_url: &str,
+        timeout: u32,
+    ) -> DelayOutcome {
+        self.delay_outcome(proxy, test_url, timeout, None).await
+    }
+
+    /// [`Self::test_delay_outcome`] with an optional expected status, for callers that
+    /// carry one as configuration.
+    pub(crate) async fn delay_outcome(
+        &self,
+        proxy: &str,
+        test_url: &str,
+        timeout: u32,
+        expected: Option<&str>,
+    ) -> DelayOutcome {
+        let result = match expected {
+            Some(expected) => {
+                self.test_delay_expecting(proxy, test_url, timeout, expected)
+                    .await
+            }
+            None => self.test_delay(proxy, test_url, timeout).await,
+        };
+        DelayOutcome::from_result(result)
+    }
+
+    pub async fn test_group_delay(
+        &self,
+        group: &str,
+        test_url: &str,
+        timeout: u32,
+    ) -> Result<HashMap<String, u32>> {
+        self.group_delay(group, test_url, timeout, None).await
+    }
+
+    /// Like [`Self::test_group_delay`], but members only count as answering when
+    /// `test_url` responds with a status in `expected`. See
+    /// [`Self::test_delay_expecting`] for the syntax.
+    pub async fn test_group_delay_expecting(
+        &self,
+        group: &str,
+        test_url: &str,
+        timeout: u32,
+        expected: &str,
+    ) -> Result<HashMap<String, u32>> {
+        self.group_delay(group, test_url, timeout, Some(expected))
+            .await
+    }
+
+    pub(crate) async fn group_delay(
+        &self,
+        group: &str,
+        test_url: &str,
+        timeout: u32,
+        expected: Option<&str>,
+    ) -> Result<HashMap<String, u32>> {
+        let encoded_group = Self::encode_path_segment(group);
+        let mut query = vec![
+            ("timeout", timeout.to_string()),
+            ("url", test_url.to_string()),
+        ];
+        if let Some(expected) = expected {
+            validate_expected_status(expected)?;
+            query.push(("expected", expected.to_string()));
+        }
+        log::debug!("Testing delay for all members of group '{}'", group);
+        let response = self
+            .http_request(
+                "GET",
+                &format!("/group/{}/delay", encoded_group),
+                Some(&query),
+                None,
+            )
+            .await?;
+        Ok(serde_json::from_slice(&response)?)
+    }
+
+    pub async fn get_service_config(&self) -> Result<ServiceConfigInfo> {
+        let response = self.http_request("GET", "/configs", None, None).await?;
+        Ok(serde_json::from_slice(&response)?)
+    }
+
+    /// Applies only the fields set in `patch` to the running core and returns the
+    /// resulting settings. Unset fields are not sent, so they keep their current values.
+    pub async fn patch_service_config(
+        &self,
+        patch: &ServiceConfigPatch,
+    ) -> Result<ServiceConfigInfo> {
+        if !patch.is_empty() {
+            log::debug!("Patching runtime config: {:?}", patch);
+            self.http_request(
+                "PATCH",
+                "/configs",
+                None,
+                Some(serde_json::to_value(patch)?),
+            )
+            .await?;
+        }
+        self.get_service_config().await
+    }
+
     pub async fn reload_config(&self, path: Option<&str>) -> Result<()> {
         let (query, body) = if let Some(p) = path {
             (
@@ -133,11 +517,153 @@ impl MihomoClient {
         Ok(())
     }
 
+    /// Makes the running core apply `yaml` as its whole config, without touching disk.
+    pub async fn reload_config_inline(&self, yaml: &str) -> Result<()> {
+        self.http_request(
+            "PUT",
+            "/configs",
+            Some(&[("force", "true".to_string())]),
+            Some(json!({ "payload": yaml })),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Rotates the controller secret of the core running from `config_path` and returns
+    /// a client that uses `new_secret`.
+    ///
+    /// mihomo can't patch `secret` at runtime, so the new value is written to the config
+    /// file and the core reloads it. Success is only reported once the controller
+    /// answers with the new secret; otherwise the file is restored and reloaded.
+    pub async fn rotate_secret(&self, config_path: &str, new_secret: &str) -> Result<Self> {
+        if new_secret.is_empty() {
+            return Err(MihomoError::config("New secret must not be empty"));
+        }
+        let original = tokio::fs::read_to_string(config_path).await?;
+        let mut config: serde_yaml::Value = serde_yaml::from_str(&original)?;
+        let root = config.as_mapping_mut().ok_or_else(|| {
+            MihomoError::config(format!("Config '{}' is not a YAML mapping", config_path))
+        })?;
+        root.insert("secret".into(), new_secret.into());
+        tokio::fs::write(config_path, serde_yaml::to_string(&config)?).await?;
+
+        let mut rotated = self.clone();
+        rotated.secret = Some(new_secret.to_string());
+
+        let applied = match self.reload_config(Some(config_path)).await {
+            Ok(()) => rotated
+                .wait_until_ready(SECRET_VERIFY_TIMEOUT)
+                .await
+                .map(|_| ()),
+            Err(err) => Err(err),
+        };
+        if let Err(err) = applied {
+            tokio::fs::write(config_path, &original).await?;
+            // The core may or may not have picked up the new secret before failing.
+            if rotated.reload_config(Some(config_path)).await.is_err() {
+                let _ = self.reload_config(Some(config_path)).await;
+            }
+            return Err(MihomoError::Service(format!(
+                "Secret rotation failed, previous secret restored: {}",
+                err
+            )));
+        }
+
+        log::info!("Rotated controller secret");
+        Ok(rotated)
+    }
+
+    /// Asks the running core to download and switch to the latest release of itself.
+    ///
+    /// The core restarts on success, so in-flight streams will drop.
+    pub async fn upgrade_core(&self) -> Result<()> {
+        log::debug!("Requesting core self-upgrade");
+        self.http_request("POST", "/upgrade", None, None).await?;
+        Ok(())
+    }
+
+    /// Asks the core to refresh the external dashboard configured by `external-ui`.
+    pub async fn upgrade_ui(&self) -> Result<()> {
+        log::debug!("Requesting dashboard upgrade");
+        self.http_request("POST", "/upgrade/ui", None, None).await?;
+        Ok(())
+    }
+
+    pub async fn get_rules(&self) -> Result<Vec<Rule>> {
+        let response = self.http_request("GET", "/rules", None, None).await?;
+        let data: RulesResponse = serde_json::from_slice(&response)?;
+        Ok(data.rules)
+    }
+
+    pub async fn get_rule_stats(&self) -> Result<RuleStats> {
+        Ok(RuleStats::from_rules(&self.get_rules().await?))
+    }
+
     pub async fn get_memory(&self) -> Result<MemoryData> {
         let response = self.http_request("GET", "/memory", None, None).await?;
         Ok(serde_json::from_slice(&response)?)
     }
 
+    /// Gathers a [`HealthReport`] for external monitoring. This never fails: every
+    /// probe is bounded by a three second timeout, and the rest of the report is
+    /// left empty when `/version` does not answer.
+    pub async fn healthcheck(&self) -> HealthReport {
+        let Some(version) = health_probe("version", self.get_version()).await else {
+            return HealthReport::default();
+        };
+        let (memory, connections, providers, goroutines) = tokio::join!(
+            health_probe("memory", self.memory_sample()),
+            health_probe("connections", self.get_connections()),
+            health_probe("providers", self.get_providers()),
+            health_probe("goroutines", self.goroutine_count()),
+        );
+
+        let mut failing_providers: Vec<String> = providers
+            .into_iter()
+            .flatten()
+            .filter(|(_, p)| p.vehicle_type != "Compatible" && p.proxies.is_empty())
+            .map(|(name, _)| name)
+            .collect();
+        failing_providers.sort();
+
+        HealthReport {
+            api_reachable: true,
+            version: Some(version.version),
+            goroutines,
+            memory_bytes: memory.as_ref().map(|m| m.in_use),
+            memory_pct: memory
+                .filter(|m| m.os_limit > 0)
+                .map(|m| m.in_use as f64 * 100.0 / m.os_limit as f64),
+            connection_count: connections.map(|c| c.connections.len()),
+            failing_providers,
+        }
+    }
+
+    /// `/memory` streams one sample per second, and the first is always zero.
+    async fn memory_sample(&self) -> Result<MemoryData> {
+        let mut samples = self.stream_memory().await?;
+        while let Some(sample) = samples.recv().await {
+            if sample.in_use > 0 {
+                return Ok(sample);
+            }
+        }
+        Err(MihomoError::Service("Memory stream closed".to_string()))
+    }
+
+    async fn goroutine_count(&self) -> Result<u64> {
+        let query = [("debug", "1".to_string())];
+        let response = self
+            .http_request("GET", "/debug/pprof/goroutine", Some(&query), None)
+            .await?;
+        // The text profile starts with "goroutine profile: total N".
+        String::from_utf8_lossy(&response)
+            .lines()
+            .next()
+            .and_then(|line| line.strip_prefix("goroutine profile: total "))
+            .and_then(|total| total.trim().parse().ok())
+            .ok_or_else(|| MihomoError::Service("Unrecognised goroutine profile".to_string()))
+    }
+
     pub async fn get_connections(&self) -> Result<ConnectionsResponse> {
         log::debug!("Fetching connections");
         let response = self.http_request("GET", "/connections", None, None).await?;
@@ -146,6 +672,14 @@ impl MihomoClient {
         Ok(data)
     }
 
+    /// Sets the routing mode (`rule`, `global` or `direct`) of the running core.
+    pub async fn set_mode(&self, mode: &str) -> Result<()> {
+        log::debug!("Setting mode to '{}'", mode);
+        self.http_request("PATCH", "/configs", None, Some(json!({ "mode": mode })))
+            .await?;
+        Ok(())
+    }
+
     pub async fn close_all_connections(&self) -> Result<()> {
         log::debug!("Closing all connections");
         self.http_request("DELETE", "/connections", None, None)
@@ -173,14 +707,15 @@ mod http {
     use super::Result;
     use crate::core::MihomoError;
     use std::path::PathBuf;
-    use std::time::Duration;
+    use std::time::{Duration, Instant};
     use tokio::io::AsyncRead;
 
     const HTTP_READ_TIMEOUT: Duration = Duration::from_secs(10);
+    const DEBUG_BODY_LIMIT: usize = 4096;
     const MAX_HTTP_HEADER_BYTES: usize = 64 * 1024;
 
     impl super::MihomoClient {
-        async fn read_http_response<R>(reader: &mut R) -> Result<Vec<u8>>
+        async fn read_http_response<R>(reader: &mut R) -> Result<(Option<u16>, Vec<u8>)>
         where
             R: AsyncRead + Unpin,
         {
@@ -262,7 +797,7 @@ mod http {
                 )));
             }
 
-            Ok(body)
+            Ok((status_code, body))
         }
 
         pub(super) async fn http_request(
@@ -272,13 +807,85 @@ mod http {
             query: Option<&[(&str, String)]>,
             body: Option<serde_json::Value>,
         ) -> Result<Vec<u8>> {
+            if let Some(limiter) = &self.rate_limiter {
+                limiter.acquire().await;
+            }
+
+            if self.debug {
+                let mut line = format!("--> {} {}", method, path);
+                if let Some(query) = query {
+                    line.push_str(&format!(" query={:?}", query));
+                }
+                if let Some(body) = &body {
+                    line.push_str(&format!(" body={}", body));
+                }
+                log::debug!("{}", self.redact(&line));
+            }
+
+            let started = Instant::now();
+            let result = self.send_http_request(method, path, query, body).await;
+            if self.debug {
+                match &result {
+                    Ok((status, bytes)) => {
+                        let shown = &bytes[..bytes.len().min(DEBUG_BODY_LIMIT)];
+                        log::debug!(
+                            "<-- {} {} {} ({:?}, {} bytes) {}",
+                            status.map_or_else(|| "-".to_string(), |s| s.to_string()),
+                            method,
+                            path,
+                            started.elapsed(),
+                            bytes.len(),
+                            self.redact(&String::from_utf8_lossy(shown))
+                        )
+                    }
+                    Err(err) => log::debug!(
+                        "<-- error {} {} ({:?}) {}",
+                        method,
+                        path,
+                        started.elapsed(),
+                        self.redact(&err.to_string())
+                    ),
+                }
+            }
+            if let Some(observer) = &self.request_observer {
+                let (status, error) = match &result {
+                    Ok((status, _)) => (*status, None),
+                    Err(err) => (err.http_status(), Some(err.to_string())),
+                };
+                observer(super::RequestTrace {
+                    method: method.to_string(),
+                    path: path.to_string(),
+                    status,
+                    duration: started.elapsed(),
+                    error,
+                });
+            }
+            result.map(|(_, body)| body)
+        }
+
+        pub(super) fn redact(&self, text: &str) -> String {
+            match self.secret.as_deref() {
+                Some(secret) if !secret.is_empty() => text.replace(secret, "<redacted>"),
+                _ => text.to_string(),
+            }
+        }
+
+        async fn send_http_request(
+            &self,
+            method: &str,
+            path: &str,
+            query: Option<&[(&str, String)]>,
+            body: Option<serde_json::Value>,
+        ) -> Result<(Option<u16>, Vec<u8>)> {
             match &self.transport {
                 super::Transport::Tcp { client, base_url } => {
                     let url = base_url.join(path)?;
                     let mut req = match method {
                         "GET" => client.get(url),
+                        "POST" => client.post(url),
                         "PUT" => client.put(url),
                         "DELETE" => client.delete(url),
+                        "PATCH" => client.patch(url),
                         _ => return Err(MihomoError::config("Unsupported method")),
                     };
 
@@ -291,7 +898,8 @@ mod http {
                     req = self.add_auth(req);
 
                     let resp = req.send().await?.error_for_status()?;
-                    Ok(resp.bytes().await?.to_vec())
+                    let status = resp.status().as_u16();
+                    Ok((Some(status), resp.bytes().await?.to_vec()))
                 }
                 super::Transport::Unix { socket_path } => {
                     self.unix_http_request(method, path, query, body, socket_path)
@@ -307,7 +915,7 @@ mod http {
             query: Option<&[(&str, String)]>,
             body: Option<serde_json::Value>,
             socket_path: &PathBuf,
-        ) -> Result<Vec<u8>> {
+        ) -> Result<(Option<u16>, Vec<u8>)> {
             use tokio::io::AsyncWriteExt;
 
             #[cfg(unix)]
@@ -334,7 +942,8 @@ mod http {
                     .secret
                     .as_ref()
                     .map(|s| format!("Authorization: Bearer {}\r\n", s))
-                    .unwrap_or_default();
+                    .unwrap_or_default()
+                    + &self.extra_header_lines();
 
                 let request = format!(
                     "{} {}{} HTTP/1.1\r\n\
@@ -387,7 +996,8 @@ mod http {
                     .secret
                     .as_ref()
                     .map(|s| format!("Authorization: Bearer {}\r\n", s))
-                    .unwrap_or_default();
+                    .unwrap_or_default()
+                    + &self.extra_header_lines();
 
                 let request = format!(
                     "{} {}{} HTTP/1.1\r\n\
@@ -421,20 +1031,34 @@ mod http {
             if let Some(secret) = &self.secret {
                 req = req.bearer_auth(secret);
             }
+            for (name, value) in &self.headers {
+                req = req.header(name, value);
+            }
             req
         }
+
+        fn extra_header_lines(&self) -> String {
+            self.headers
+                .iter()
+                .map(|(name, value)| format!("{}: {}\r\n", name, value))
+                .collect()
+        }
     }
 }
 
 mod ws {
     use super::Result;
-    use super::{ConnectionSnapshot, TrafficData};
-    use futures_util::StreamExt;
+    use super::{ConnectionSnapshot, LogEntry, MemoryData, TrafficData};
+    use crate::core::{ReconnectPolicy, StreamEvent};
+    use futures_util::{SinkExt, StreamExt};
     use std::time::Duration;
     use tokio_tungstenite::tungstenite::client::IntoClientRequest;
     use tokio_tungstenite::{connect_async, tungstenite::Message};
     use url::Url;
 
+    /// How long a stream waits for the close handshake once its receiver is dropped.
+    const WS_CLOSE_TIMEOUT: Duration = Duration::from_secs(1);
+
     impl super::MihomoClient {
         pub fn with_ws_connect_timeout(mut self, timeout: Duration) -> Self {
             self.ws_connect_timeout = timeout.max(Duration::from_millis(1));
@@ -444,6 +1068,7 @@ mod ws {
         pub(super) fn ws_request_with_auth(
             url: &str,
             secret: Option<&str>,
+            headers: &[(String, String)],
         ) -> std::result::Result<
             tokio_tungstenite::tungstenite::handshake::client::Request,
             tokio_tungstenite::tungstenite::Error,
@@ -455,6 +1080,14 @@ mod ws {
                     request.headers_mut().insert("Authorization", value);
                 }
             }
+            for (name, value) in headers {
+                if let (Ok(name), Ok(value)) = (
+                    name.parse::<tokio_tungstenite::tungstenite::http::HeaderName>(),
+                    value.parse(),
+                ) {
+                    request.headers_mut().insert(name, value);
+                }
+            }
             Ok(request)
         }
 
@@ -472,19 +1105,30 @@ mod ws {
             F: FnMut(String) -> Option<T> + Send + 'static,
         {
             tokio::spawn(async move {
-                let (_, mut read) = ws_stream.split();
-                while let Some(msg) = read.next().await {
+                let (mut write, mut read) = ws_stream.split();
+                loop {
+                    let msg = tokio::select! {
+                        // Quiet streams (logs, idle connections) may not produce another
+                        // message for a long time, so don't wait for a failed send to
+                        // notice that the receiver is gone.
+                        _ = tx.closed() => {
+                            let _ = tokio::time::timeout(WS_CLOSE_TIMEOUT, write.close()).await;
+                            break;
+                        }
+                        msg = read.next() => msg,
+                    };
                     match msg {
-                        Ok(Message::Text(text)) => {
+                        Some(Ok(Message::Text(text))) => {
                             if let Some(item) = parse_text(text.to_string()) {
                                 if tx.send(item).is_err() {
+                                    let _ =
+                                        tokio::time::timeout(WS_CLOSE_TIMEOUT, write.close()).await;
                                     break;
                                 }
                             }
                         }
-                        Ok(Message::Close(_)) => break,
-                        Err(_) => break,
-                        _ => {}
+                        Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                        Some(Ok(_)) => {}
                     }
                 }
             });
@@ -543,7 +1187,8 @@ mod ws {
             match &self.transport {
                 super::Transport::Tcp { base_url, .. } => {
                     let ws_url = Self::build_tcp_ws_url(base_url, endpoint, query.as_ref());
-                    let request = Self::ws_request_with_auth(&ws_url, self.secret.as_deref())?;
+                    let request =
+                        Self::ws_request_with_auth(&ws_url, self.secret.as_deref(), &self.headers)?;
                     let (ws_stream, _) =
                         tokio::time::timeout(self.ws_connect_timeout, connect_async(request))
                             .await
@@ -568,7 +1213,8 @@ mod ws {
 
                         let path = Self::build_ws_path(endpoint, query.as_ref());
                         let ws_url = format!("ws://localhost{}", path);
-                        let request = Self::ws_request_with_auth(&ws_url, secret.as_deref())?;
+                        let request =
+                            Self::ws_request_with_auth(&ws_url, secret.as_deref(), &self.headers)?;
 
                         let (ws_stream, _) = tokio::time::timeout(
                             self.ws_connect_timeout,
@@ -608,7 +1254,8 @@ mod ws {
 
                         let path = Self::build_ws_path(endpoint, query.as_ref());
                         let ws_url = format!("ws://localhost{}", path);
-                        let request = Self::ws_request_with_auth(&ws_url, secret.as_deref())?;
+                        let request =
+                            Self::ws_request_with_auth(&ws_url, secret.as_deref(), &self.headers)?;
 
                         let (ws_stream, _) = tokio::time::timeout(
                             self.ws_connect_timeout,
@@ -631,6 +1278,83 @@ mod ws {
             Ok(rx)
         }
 
+        /// Like [`Self::stream_with_parser`], but re-establishes the stream with backoff
+        /// whenever it drops. Only the initial connection error is returned.
+        async fn reconnecting_stream<T, F>(
+            &self,
+            endpoint: &'static str,
+            query: Option<Vec<(String, String)>>,
+            parser: F,
+            policy: ReconnectPolicy,
+        ) -> Result<tokio::sync::mpsc::UnboundedReceiver<StreamEvent<T>>>
+        where
+            T: Send + 'static,
+            F: FnMut(String) -> Option<T> + Send + Clone + 'static,
+        {
+            let mut inner = self
+                .stream_with_parser(endpoint, query.clone(), parser.clone())
+                .await?;
+            let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+            let client = self.clone();
+
+            tokio::spawn(async move {
+                let mut attempt = 0;
+                loop {
+                    loop {
+                        tokio::select! {
+                            item = inner.recv() => match item {
+                                Some(item) => {
+                                    if tx.send(StreamEvent::Item(item)).is_err() {
+                                        return;
+                                    }
+                                }
+                                None => break,
+                            },
+                            _ = tx.closed() => return,
+                        }
+                    }
+
+                    loop {
+                        attempt += 1;
+                        if policy.exhausted(attempt) {
+                            log::debug!("Giving up reconnecting to {}", endpoint);
+                            return;
+                        }
+                        let delay = policy.delay_for(attempt);
+                        if tx
+                            .send(StreamEvent::Reconnecting { attempt, delay })
+                            .is_err()
+                        {
+                            return;
+                        }
+                        tokio::select! {
+                            _ = tokio::time::sleep(delay) => {}
+                            _ = tx.closed() => return,
+                        }
+
+                        match client
+                            .stream_with_parser(endpoint, query.clone(), parser.clone())
+                            .await
+                        {
+                            Ok(stream) => {
+                                inner = stream;
+                                attempt = 0;
+                                if tx.send(StreamEvent::Reconnected).is_err() {
+                                    return;
+                                }
+                                break;
+                            }
+                            Err(err) => {
+                                log::debug!("Reconnect to {} failed: {}", endpoint, err);
+                            }
+                        }
+                    }
+                }
+            });
+
+            Ok(rx)
+        }
+
         pub async fn stream_logs(
             &self,
             level: Option<&str>,
@@ -639,6 +1363,21 @@ mod ws {
             self.stream_with_parser("/logs", query, Some).await
         }
 
+        /// Like [`Self::stream_logs`], but parsed, with entries lacking a `time`
+        /// stamped on receipt. Messages that aren't log JSON are skipped.
+        pub async fn stream_log_entries(
+            &self,
+            level: Option<&str>,
+        ) -> Result<tokio::sync::mpsc::UnboundedReceiver<LogEntry>> {
+            let query = level.map(|l| vec![("level".to_string(), l.to_string())]);
+            self.stream_with_parser("/logs", query, |text| {
+                serde_json::from_str::<LogEntry>(&text)
+                    .ok()
+                    .map(|entry| entry.stamped(chrono::Utc::now()))
+            })
+            .await
+        }
+
         pub async fn stream_traffic(
             &self,
         ) -> Result<tokio::sync::mpsc::UnboundedReceiver<TrafficData>> {
@@ -648,6 +1387,15 @@ mod ws {
             .await
         }
 
+        pub async fn stream_memory(
+            &self,
+        ) -> Result<tokio::sync::mpsc::UnboundedReceiver<MemoryData>> {
+            self.stream_with_parser("/memory", None, |text| {
+                serde_json::from_str::<MemoryData>(&text).ok()
+            })
+            .await
+        }
+
         pub async fn stream_connections(
             &self,
         ) -> Result<tokio::sync::mpsc::UnboundedReceiver<ConnectionSnapshot>> {
@@ -656,12 +1404,61 @@ mod ws {
             })
             .await
         }
+
+        pub async fn stream_logs_reconnecting(
+            &self,
+            level: Option<&str>,
+            policy: ReconnectPolicy,
+        ) -> Result<tokio::sync::mpsc::UnboundedReceiver<StreamEvent<String>>> {
+            let query = level.map(|l| vec![("level".to_string(), l.to_string())]);
+            self.reconnecting_stream("/logs", query, Some, policy).await
+        }
+
+        pub async fn stream_traffic_reconnecting(
+            &self,
+            policy: ReconnectPolicy,
+        ) -> Result<tokio::sync::mpsc::UnboundedReceiver<StreamEvent<TrafficData>>> {
+            self.reconnecting_stream(
+                "/traffic",
+                None,
+                |text| serde_json::from_str::<TrafficData>(&text).ok(),
+                policy,
+            )
+            .await
+        }
+
+        pub async fn stream_memory_reconnecting(
+            &self,
+            policy: ReconnectPolicy,
+        ) -> Result<tokio::sync::mpsc::UnboundedReceiver<StreamEvent<MemoryData>>> {
+            self.reconnecting_stream(
+                "/memory",
+                None,
+                |text| serde_json::from_str::<MemoryData>(&text).ok(),
+                policy,
+            )
+            .await
+        }
+
+        pub async fn stream_connections_reconnecting(
+            &self,
+            policy: ReconnectPolicy,
+        ) -> Result<tokio::sync::mpsc::UnboundedReceiver<StreamEvent<ConnectionSnapshot>>> {
+            self.reconnecting_stream(
+                "/connections",
+                None,
+                |text| serde_json::from_str::<ConnectionSnapshot>(&text).ok(),
+                policy,
+            )
+            .await
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::{ReconnectPolicy, StreamEvent};
     use futures_util::StreamExt;
     use mockito::{Matcher, Server};
     #[cfg(any(unix, windows))]
@@ -829,6 +1626,7 @@ mod tests {
             let request = String::from_utf8_lossy(&buf[..n]).to_string();
             assert!(request.starts_with("PUT /configs?force=true HTTP/1.1"));
             assert!(request.contains("Authorization: Bearer secret-token"));
+            assert!(request.contains("User-Agent: mihomo-rs\r\n"));
             assert!(request.contains("\"path\":\"/tmp/test-config.yaml\""));
 
             let response = "HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\r\n";
@@ -1170,11 +1968,37 @@ mod tests {
         assert_eq!(version.version, "v1.18.0");
     }
 
+    #[tokio::test]
+    async fn test_rate_limit_is_shared_across_clones() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("GET", "/version")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"version":"v1.18.0"}"#)
+            .expect(12)
+            .create_async()
+            .await;
+
+        let client = MihomoClient::new(&server.url(), None)
+            .unwrap()
+            .with_rate_limit(10);
+        let clone = client.clone();
+        let start = std::time::Instant::now();
+        for _ in 0..6 {
+            client.get_version().await.unwrap();
+            clone.get_version().await.unwrap();
+        }
+
+        mock.assert_async().await;
+        assert!(start.elapsed() >= std::time::Duration::from_millis(150));
+    }
+
     #[tokio::test]
     async fn test_http_request_rejects_unsupported_method() {
         let client = MihomoClient::new("http://127.0.0.1:9090", None).unwrap();
         let err = client
-            .http_request("POST", "/version", None, None)
+            .http_request("OPTIONS", "/version", None, None)
             .await
             .expect_err("unsupported method should fail");
         assert!(err.to_string().contains("Unsupported method"));
@@ -1428,10 +2252,64 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_debug_redacts_secret() {
+        let client = MihomoClient::new("http://127.0.0.1:9090", Some("s3cret".to_string()))
+            .unwrap()
+            .with_debug(true);
+        assert!(client.debug);
+        assert_eq!(
+            client.redact(r#"--> PUT /configs body={"secret":"s3cret"}"#),
+            r#"--> PUT /configs body={"secret":"<redacted>"}"#
+        );
+
+        let anonymous = MihomoClient::new("http://127.0.0.1:9090", None).unwrap();
+        assert_eq!(anonymous.redact("plain"), "plain");
+    }
+
+    #[tokio::test]
+    async fn test_debug_mode_does_not_change_responses() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("GET", "/version")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"version":"v1.19.0"}"#)
+            .create_async()
+            .await;
+
+        let client = MihomoClient::new(&server.url(), None)
+            .unwrap()
+            .with_debug(true);
+        let version = client.get_version().await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(version.version, "v1.19.0");
+    }
+
+    #[test]
+    fn test_proxies_object_distinguishes_empty_from_malformed() {
+        assert!(MihomoClient::proxies_object(br#"{"proxies":{}}"#)
+            .unwrap()
+            .is_empty());
+        assert!(MihomoClient::proxies_object(br#"{"proxies":null}"#)
+            .unwrap()
+            .is_empty());
+
+        assert!(matches!(
+            MihomoClient::proxies_object(b"<html>not mihomo</html>"),
+            Err(MihomoError::Json(_))
+        ));
+        for body in [&br#"{"message":"Unauthorized"}"#[..], br#"{"proxies":[]}"#] {
+            let err = MihomoClient::proxies_object(body).expect_err("malformed body");
+            assert!(err.to_string().contains("Unexpected /proxies response"));
+        }
+    }
+
     #[test]
     fn test_ws_request_with_auth_header() {
         let request =
-            MihomoClient::ws_request_with_auth("ws://127.0.0.1:9090/logs", Some("my-secret"))
+            MihomoClient::ws_request_with_auth("ws://127.0.0.1:9090/logs", Some("my-secret"), &[])
                 .expect("request should be built");
         let auth = request
             .headers()
@@ -1441,10 +2319,50 @@ mod tests {
     }
 
     #[test]
-    fn test_ws_request_with_invalid_header_value_is_ignored() {
+    fn test_with_header_replaces_and_rejects_invalid() {
+        let client = MihomoClient::new("http://127.0.0.1:9090", None)
+            .unwrap()
+            .with_user_agent("dashboard/1.0")
+            .with_header("X-Trace", "a")
+            .with_header("x-trace", "b")
+            .with_header("X-Bad", "line\r\nbreak")
+            .with_header("Bad Name", "v");
+
+        assert_eq!(
+            client.headers,
+            vec![
+                ("User-Agent".to_string(), "dashboard/1.0".to_string()),
+                ("x-trace".to_string(), "b".to_string()),
+            ]
+        );
+
         let request =
-            MihomoClient::ws_request_with_auth("ws://127.0.0.1:9090/logs", Some("bad\r\nsecret"))
-                .expect("request should still be built");
+            MihomoClient::ws_request_with_auth("ws://127.0.0.1:9090/logs", None, &client.headers)
+                .expect("request should be built");
+        assert_eq!(
+            request
+                .headers()
+                .get("User-Agent")
+                .and_then(|v| v.to_str().ok()),
+            Some("dashboard/1.0")
+        );
+        assert_eq!(
+            request
+                .headers()
+                .get("X-Trace")
+                .and_then(|v| v.to_str().ok()),
+            Some("b")
+        );
+    }
+
+    #[test]
+    fn test_ws_request_with_invalid_header_value_is_ignored() {
+        let request = MihomoClient::ws_request_with_auth(
+            "ws://127.0.0.1:9090/logs",
+            Some("bad\r\nsecret"),
+            &[],
+        )
+        .expect("request should still be built");
         assert!(request.headers().get("Authorization").is_none());
     }
 
@@ -1469,6 +2387,38 @@ mod tests {
             .ok();
     }
 
+    #[tokio::test]
+    async fn test_stream_log_entries_stamps_receive_time() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let ws = accept_async(stream).await.unwrap();
+            let (mut tx, _) = ws.split();
+            use futures_util::SinkExt;
+            tx.send(WsMessage::Text("not json".into())).await.ok();
+            tx.send(WsMessage::Text(
+                r#"{"type":"info","payload":"dial ok"}"#.into(),
+            ))
+            .await
+            .ok();
+        });
+
+        let client = MihomoClient::new(&format!("http://{}", addr), None).unwrap();
+        let before = chrono::Utc::now();
+        let mut rx = client.stream_log_entries(None).await.unwrap();
+
+        let entry = tokio::time::timeout(std::time::Duration::from_secs(1), rx.recv())
+            .await
+            .expect("log entry in time")
+            .expect("stream open");
+        assert_eq!(entry.level, "info");
+        assert_eq!(entry.payload, "dial ok");
+        assert!(entry.time.is_some());
+        assert!(entry.timestamp().expect("timestamp") >= before);
+    }
+
     #[tokio::test]
     async fn test_stream_traffic_message_handling() {
         let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
@@ -1517,6 +2467,62 @@ mod tests {
             .ok();
     }
 
+    #[tokio::test]
+    async fn test_stream_logs_reconnecting_resumes_after_server_drop() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            use futures_util::SinkExt;
+            for line in ["first", "second"] {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut ws = accept_async(stream).await.unwrap();
+                ws.send(WsMessage::Text(line.into())).await.ok();
+                ws.close(None).await.ok();
+            }
+        });
+
+        let client = MihomoClient::new(&format!("http://{}", addr), None).unwrap();
+        let policy = ReconnectPolicy::default()
+            .with_initial_delay(std::time::Duration::from_millis(10))
+            .with_max_attempts(3);
+        let mut rx = client.stream_logs_reconnecting(None, policy).await.unwrap();
+
+        let mut events = vec![];
+        while let Ok(Some(event)) =
+            tokio::time::timeout(std::time::Duration::from_secs(2), rx.recv()).await
+        {
+            let done = event == StreamEvent::Item("second".to_string());
+            events.push(event);
+            if done {
+                break;
+            }
+        }
+
+        assert_eq!(
+            events.first(),
+            Some(&StreamEvent::Item("first".to_string()))
+        );
+        assert!(events.contains(&StreamEvent::Reconnected));
+        assert_eq!(
+            events.last(),
+            Some(&StreamEvent::Item("second".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_reconnecting_returns_initial_connect_error() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let client = MihomoClient::new(&format!("http://{}", addr), None).unwrap();
+        let result = client
+            .stream_traffic_reconnecting(ReconnectPolicy::default())
+            .await;
+        assert!(result.is_err());
+    }
+
     #[tokio::test]
     #[cfg(unix)]
     async fn test_stream_logs_over_unix_socket() {
@@ -5,6 +5,7 @@ pub enum ErrorCode {
     InvalidExternalController,
     InvalidProfileName,
     InvalidVersion,
+    InvalidInstanceName,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -57,6 +58,7 @@ impl std::fmt::Display for ErrorCode {
             ErrorCode::InvalidExternalController => "E_CFG_INVALID_EXTERNAL_CONTROLLER",
             ErrorCode::InvalidProfileName => "E_CFG_INVALID_PROFILE_NAME",
             ErrorCode::InvalidVersion => "E_VER_INVALID_VERSION",
+            ErrorCode::InvalidInstanceName => "E_SVC_INVALID_INSTANCE_NAME",
         };
         f.write_str(code)
     }
@@ -70,6 +72,7 @@ impl std::str::FromStr for ErrorCode {
             "E_CFG_INVALID_EXTERNAL_CONTROLLER" => Ok(ErrorCode::InvalidExternalController),
             "E_CFG_INVALID_PROFILE_NAME" => Ok(ErrorCode::InvalidProfileName),
             "E_VER_INVALID_VERSION" => Ok(ErrorCode::InvalidVersion),
+            "E_SVC_INVALID_INSTANCE_NAME" => Ok(ErrorCode::InvalidInstanceName),
             _ => Err(()),
         }
     }
@@ -109,6 +112,13 @@ pub enum MihomoError {
 
     #[error("Not found: {0}")]
     NotFound(String),
+
+    #[error("Partial failure: {} succeeded, {} failed", succeeded.len(), failed.len())]
+    PartialFailure {
+        succeeded: Vec<String>,
+        /// Name of each failed item paired with its error message.
+        failed: Vec<(String, String)>,
+    },
 }
 
 impl MihomoError {
@@ -127,6 +137,19 @@ impl MihomoError {
     pub fn version_with_code(code: ErrorCode, message: impl Into<String>) -> Self {
         Self::Version(ErrorDetail::with_code(code, message))
     }
+
+    /// HTTP status code returned by the controller, if this error carries one.
+    pub fn http_status(&self) -> Option<u16> {
+        match self {
+            Self::Http(err) => err.status().map(|status| status.as_u16()),
+            // Unix socket transport reports status as "HTTP error <code>: <body>"
+            Self::Service(msg) => msg
+                .strip_prefix("HTTP error ")
+                .and_then(|rest| rest.split(':').next())
+                .and_then(|code| code.trim().parse().ok()),
+            _ => None,
+        }
+    }
 }
 
 // Manual From implementation for WebSocket error to box it
@@ -172,6 +195,15 @@ mod tests {
         assert_eq!(err.to_string(), "Not found: resource not found");
     }
 
+    #[test]
+    fn test_partial_failure_display() {
+        let err = MihomoError::PartialFailure {
+            succeeded: vec!["a".to_string(), "b".to_string()],
+            failed: vec![("c".to_string(), "timeout".to_string())],
+        };
+        assert_eq!(err.to_string(), "Partial failure: 2 succeeded, 1 failed");
+    }
+
     #[test]
     fn test_io_error_conversion() {
         let io_err = std::io::Error::new(std::io::ErrorKind::NotFound, "file not found");
@@ -214,6 +246,16 @@ mod tests {
         assert!(matches!(mihomo_err, MihomoError::WebSocket(_)));
     }
 
+    #[test]
+    fn test_http_status_from_service_error() {
+        let err = MihomoError::Service("HTTP error 504: {\"message\":\"timeout\"}".to_string());
+        assert_eq!(err.http_status(), Some(504));
+
+        let other = MihomoError::Service("Service failed to start".to_string());
+        assert_eq!(other.http_status(), None);
+        assert_eq!(MihomoError::config("bad").http_status(), None);
+    }
+
     #[test]
     fn test_error_code_display_and_from_str() {
         use std::str::FromStr;
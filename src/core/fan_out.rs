@@ -0,0 +1,67 @@
+use super::Result;
+use futures_util::StreamExt;
+use std::future::Future;
+
+/// Runs `f` on every item with at most `concurrency` calls in flight and returns each
+/// item paired with its result, in input order. A `concurrency` of 0 is treated as 1.
+pub async fn fan_out<I, T, F, Fut>(items: Vec<I>, concurrency: usize, f: F) -> Vec<(I, Result<T>)>
+where
+    I: Clone,
+    F: Fn(I) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let f = &f;
+    let mut results: Vec<(usize, I, Result<T>)> =
+        futures_util::stream::iter(items.into_iter().enumerate())
+            .map(|(index, item)| async move {
+                let result = f(item.clone()).await;
+                (index, item, result)
+            })
+            .buffer_unordered(concurrency.max(1))
+            .collect()
+            .await;
+
+    results.sort_by_key(|(index, _, _)| *index);
+    results
+        .into_iter()
+        .map(|(_, item, result)| (item, result))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::MihomoError;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_fan_out_caps_concurrency_and_keeps_order() {
+        let in_flight = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+
+        let results = fan_out((0..10u64).collect(), 3, |n| {
+            let (in_flight, peak) = (&in_flight, &peak);
+            async move {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(10 - n)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                if n % 4 == 0 {
+                    Err(MihomoError::Proxy(format!("item {}", n)))
+                } else {
+                    Ok(n * 2)
+                }
+            }
+        })
+        .await;
+
+        assert!(peak.load(Ordering::SeqCst) <= 3);
+        assert_eq!(
+            results.iter().map(|(n, _)| *n).collect::<Vec<_>>(),
+            (0..10).collect::<Vec<_>>()
+        );
+        assert!(results[0].1.is_err());
+        assert_eq!(results[3].1.as_ref().ok(), Some(&6));
+    }
+}
@@ -1,13 +1,22 @@
 pub mod client;
 pub mod error;
+pub mod fan_out;
 pub mod home;
 pub mod port;
+mod rate_limit;
+pub mod reconnect;
+pub mod sample;
+pub mod traffic;
 pub mod types;
 pub mod validate;
 
-pub use client::MihomoClient;
+pub use client::{MihomoClient, RequestObserver, RequestTrace};
 pub use error::{ErrorCode, MihomoError, Result};
+pub use fan_out::fan_out;
 pub use home::get_home_dir;
 pub use port::{find_available_port, is_port_available, parse_port_from_addr};
+pub use reconnect::{ReconnectPolicy, StreamEvent};
+pub use sample::sample_every;
+pub use traffic::{TrafficMeter, TrafficRate};
 pub use types::*;
-pub use validate::{validate_profile_name, validate_version_name};
+pub use validate::{validate_instance_name, validate_profile_name, validate_version_name};
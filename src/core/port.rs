@@ -1,4 +1,4 @@
-use std::net::{TcpListener, ToSocketAddrs};
+use std::net::TcpListener;
 
 /// Check if a port is available on localhost
 pub fn is_port_available(port: u16) -> bool {
@@ -11,11 +11,15 @@ pub fn find_available_port(start_port: u16) -> Option<u16> {
 }
 
 /// Parse port from address string (e.g., "127.0.0.1:9090" -> 9090)
+///
+/// Only the text is inspected, so host names are never resolved. IPv6 hosts must be
+/// bracketed (`[::1]:9090`), and an empty host (`:9090`) is accepted.
 pub fn parse_port_from_addr(addr: &str) -> Option<u16> {
-    addr.to_socket_addrs()
-        .ok()?
-        .next()
-        .map(|socket_addr| socket_addr.port())
+    let (host, port) = addr.rsplit_once(':')?;
+    if host.contains(':') && !(host.starts_with('[') && host.ends_with(']')) {
+        return None;
+    }
+    port.parse().ok()
 }
 
 #[cfg(test)]
@@ -49,5 +53,10 @@ mod tests {
         assert_eq!(parse_port_from_addr("127.0.0.1:9090"), Some(9090));
         assert_eq!(parse_port_from_addr("localhost:8080"), Some(8080));
         assert_eq!(parse_port_from_addr("invalid"), None);
+        assert_eq!(parse_port_from_addr("[::1]:9090"), Some(9090));
+        assert_eq!(parse_port_from_addr(":9090"), Some(9090));
+        assert_eq!(parse_port_from_addr("mihomo.invalid:7890"), Some(7890));
+        assert_eq!(parse_port_from_addr("::1"), None);
+        assert_eq!(parse_port_from_addr("127.0.0.1:http"), None);
     }
 }
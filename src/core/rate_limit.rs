@@ -0,0 +1,83 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Token bucket shared by all clones of a `MihomoClient`.
+///
+/// Holds up to one second's worth of requests as burst capacity.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    rate: f64,
+    state: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(requests_per_second: u32) -> Self {
+        let rate = f64::from(requests_per_second.max(1));
+        Self {
+            rate,
+            state: Mutex::new(Bucket {
+                tokens: rate,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Waits until a request may be sent.
+    pub(crate) async fn acquire(&self) {
+        while let Some(wait) = self.try_acquire(Instant::now()) {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Takes a token if one is available, otherwise returns how long until the next one.
+    fn try_acquire(&self, now: Instant) -> Option<Duration> {
+        let mut bucket = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let elapsed = now.saturating_duration_since(bucket.refilled_at);
+        bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() * self.rate).min(self.rate);
+        bucket.refilled_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64((1.0 - bucket.tokens) / self.rate))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_allows_burst_then_waits_for_refill() {
+        let limiter = RateLimiter::new(2);
+        let start = Instant::now();
+
+        assert!(limiter.try_acquire(start).is_none());
+        assert!(limiter.try_acquire(start).is_none());
+        let wait = limiter.try_acquire(start).expect("bucket should be empty");
+        assert!(wait <= Duration::from_millis(500));
+
+        assert!(limiter
+            .try_acquire(start + Duration::from_millis(500))
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_acquire_throttles_to_configured_rate() {
+        let limiter = RateLimiter::new(20);
+        let start = Instant::now();
+        for _ in 0..25 {
+            limiter.acquire().await;
+        }
+        // 20 burst tokens, then 5 more at 50ms each.
+        assert!(start.elapsed() >= Duration::from_millis(200));
+    }
+}
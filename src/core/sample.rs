@@ -0,0 +1,104 @@
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+/// Downsamples a stream by forwarding one item per `n` received. The skipped items
+/// are folded into the forwarded one with `merge(accumulated, next)`, e.g. summing
+/// [`TrafficData`](super::TrafficData) or keeping the latest
+/// [`MemoryData`](super::MemoryData). A partial batch is flushed when `rx` closes.
+///
+/// The forwarding task stops once either side is dropped.
+pub fn sample_every<T, F>(
+    mut rx: UnboundedReceiver<T>,
+    n: usize,
+    mut merge: F,
+) -> UnboundedReceiver<T>
+where
+    T: Send + 'static,
+    F: FnMut(T, T) -> T + Send + 'static,
+{
+    let n = n.max(1);
+    let (tx, out) = unbounded_channel();
+
+    tokio::spawn(async move {
+        let mut pending: Option<T> = None;
+        let mut count = 0;
+        loop {
+            let item = tokio::select! {
+                item = rx.recv() => item,
+                _ = tx.closed() => return,
+            };
+            let Some(item) = item else { break };
+
+            pending = Some(match pending.take() {
+                Some(acc) => merge(acc, item),
+                None => item,
+            });
+            count += 1;
+            if count == n {
+                count = 0;
+                if let Some(batch) = pending.take() {
+                    if tx.send(batch).is_err() {
+                        return;
+                    }
+                }
+            }
+        }
+        if let Some(batch) = pending {
+            let _ = tx.send(batch);
+        }
+    });
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{MemoryData, TrafficData};
+
+    async fn collect<T>(mut rx: UnboundedReceiver<T>) -> Vec<T> {
+        let mut items = vec![];
+        while let Some(item) = rx.recv().await {
+            items.push(item);
+        }
+        items
+    }
+
+    #[tokio::test]
+    async fn sums_traffic_per_batch_and_flushes_remainder() {
+        let (tx, rx) = unbounded_channel();
+        for i in 1..=5 {
+            tx.send(TrafficData {
+                up: i,
+                down: i * 10,
+            })
+            .unwrap();
+        }
+        drop(tx);
+
+        let sampled = collect(sample_every(rx, 2, |a: TrafficData, b| TrafficData {
+            up: a.up + b.up,
+            down: a.down + b.down,
+        }))
+        .await;
+
+        let totals: Vec<(u64, u64)> = sampled.iter().map(|t| (t.up, t.down)).collect();
+        assert_eq!(totals, vec![(3, 30), (7, 70), (5, 50)]);
+    }
+
+    #[tokio::test]
+    async fn keeps_latest_memory_sample() {
+        let (tx, rx) = unbounded_channel();
+        for in_use in [10, 20, 30] {
+            tx.send(MemoryData {
+                in_use,
+                os_limit: 0,
+            })
+            .unwrap();
+        }
+        drop(tx);
+
+        let sampled = collect(sample_every(rx, 3, |_, latest: MemoryData| latest)).await;
+        assert_eq!(sampled.len(), 1);
+        assert_eq!(sampled[0].in_use, 30);
+    }
+}
@@ -0,0 +1,190 @@
+use super::types::TrafficData;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+const DEFAULT_RETENTION: Duration = Duration::from_secs(60);
+const DEFAULT_EMA_ALPHA: f64 = 0.3;
+
+/// Throughput in bytes per second.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TrafficRate {
+    pub up: f64,
+    pub down: f64,
+}
+
+/// Accumulates `/traffic` samples into rolling averages, an EMA and running totals.
+///
+/// mihomo reports one sample per second, each holding the bytes moved in that second.
+#[derive(Debug, Clone)]
+pub struct TrafficMeter {
+    samples: VecDeque<(Instant, TrafficData)>,
+    retention: Duration,
+    ema_alpha: f64,
+    ema: Option<TrafficRate>,
+    total_up: u64,
+    total_down: u64,
+}
+
+impl Default for TrafficMeter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TrafficMeter {
+    pub fn new() -> Self {
+        Self {
+            samples: VecDeque::new(),
+            retention: DEFAULT_RETENTION,
+            ema_alpha: DEFAULT_EMA_ALPHA,
+            ema: None,
+            total_up: 0,
+            total_down: 0,
+        }
+    }
+
+    /// How far back samples are kept; bounds the largest useful `average` window.
+    pub fn with_retention(mut self, retention: Duration) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// Smoothing factor in `(0, 1]`; higher values react faster to changes.
+    pub fn with_ema_alpha(mut self, alpha: f64) -> Self {
+        self.ema_alpha = alpha.clamp(f64::EPSILON, 1.0);
+        self
+    }
+
+    pub fn record(&mut self, sample: &TrafficData) {
+        self.record_at(sample, Instant::now());
+    }
+
+    pub fn record_at(&mut self, sample: &TrafficData, at: Instant) {
+        self.total_up = self.total_up.saturating_add(sample.up);
+        self.total_down = self.total_down.saturating_add(sample.down);
+
+        let current = TrafficRate {
+            up: sample.up as f64,
+            down: sample.down as f64,
+        };
+        self.ema = Some(match self.ema {
+            Some(prev) => TrafficRate {
+                up: prev.up + self.ema_alpha * (current.up - prev.up),
+                down: prev.down + self.ema_alpha * (current.down - prev.down),
+            },
+            None => current,
+        });
+
+        self.samples.push_back((at, sample.clone()));
+        while let Some((ts, _)) = self.samples.front() {
+            if at.saturating_duration_since(*ts) >= self.retention {
+                self.samples.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Mean rate over samples within `window` of the most recent one.
+    pub fn average(&self, window: Duration) -> TrafficRate {
+        let Some((latest, _)) = self.samples.back() else {
+            return TrafficRate::default();
+        };
+
+        let (mut up, mut down, mut count) = (0u64, 0u64, 0u32);
+        for (_, sample) in self
+            .samples
+            .iter()
+            .rev()
+            .take_while(|(ts, _)| latest.saturating_duration_since(*ts) < window)
+        {
+            up = up.saturating_add(sample.up);
+            down = down.saturating_add(sample.down);
+            count += 1;
+        }
+
+        if count == 0 {
+            return TrafficRate::default();
+        }
+        TrafficRate {
+            up: up as f64 / count as f64,
+            down: down as f64 / count as f64,
+        }
+    }
+
+    pub fn average_1s(&self) -> TrafficRate {
+        self.average(Duration::from_secs(1))
+    }
+
+    pub fn average_10s(&self) -> TrafficRate {
+        self.average(Duration::from_secs(10))
+    }
+
+    pub fn average_60s(&self) -> TrafficRate {
+        self.average(Duration::from_secs(60))
+    }
+
+    pub fn ema(&self) -> Option<TrafficRate> {
+        self.ema
+    }
+
+    pub fn total_up(&self) -> u64 {
+        self.total_up
+    }
+
+    pub fn total_down(&self) -> u64 {
+        self.total_down
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(up: u64, down: u64) -> TrafficData {
+        TrafficData { up, down }
+    }
+
+    #[test]
+    fn test_average_uses_only_samples_inside_window() {
+        let start = Instant::now();
+        let mut meter = TrafficMeter::new();
+        for (i, up) in [100, 200, 300, 400].into_iter().enumerate() {
+            meter.record_at(&sample(up, up * 2), start + Duration::from_secs(i as u64));
+        }
+
+        assert_eq!(
+            meter.average_1s(),
+            TrafficRate {
+                up: 400.0,
+                down: 800.0
+            }
+        );
+        assert_eq!(meter.average(Duration::from_secs(2)).up, 350.0);
+        assert_eq!(meter.average_10s().up, 250.0);
+        assert_eq!(meter.total_up(), 1000);
+        assert_eq!(meter.total_down(), 2000);
+    }
+
+    #[test]
+    fn test_retention_drops_old_samples_but_keeps_totals() {
+        let start = Instant::now();
+        let mut meter = TrafficMeter::new().with_retention(Duration::from_secs(2));
+        meter.record_at(&sample(1000, 0), start);
+        meter.record_at(&sample(10, 0), start + Duration::from_secs(2));
+
+        assert_eq!(meter.average_60s().up, 10.0);
+        assert_eq!(meter.total_up(), 1010);
+    }
+
+    #[test]
+    fn test_ema_smooths_towards_new_samples() {
+        let mut meter = TrafficMeter::new().with_ema_alpha(0.5);
+        assert!(meter.ema().is_none());
+        assert_eq!(meter.average_1s(), TrafficRate::default());
+
+        meter.record(&sample(100, 0));
+        meter.record(&sample(200, 0));
+        assert_eq!(meter.ema().map(|rate| rate.up), Some(150.0));
+    }
+}
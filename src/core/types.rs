@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -14,8 +15,10 @@ where
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Version {
     pub version: String,
+    /// Only sent by the legacy Clash Premium core.
     #[serde(default)]
     pub premium: bool,
+    /// Sent as `true` by mihomo (Clash.Meta).
     #[serde(default)]
     pub meta: bool,
 }
@@ -29,6 +32,14 @@ pub struct ProxyNode {
     pub delay: Option<u32>,
     #[serde(default)]
     pub alive: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interface: Option<String>,
+    #[serde(rename = "routing-mark")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub routing_mark: Option<u32>,
+    #[serde(rename = "dialer-proxy")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dialer_proxy: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,11 +51,28 @@ pub struct ProxyGroup {
     pub all: Vec<String>,
 }
 
+/// A selector group with everything a picker UI needs to render it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupChoices {
+    pub name: String,
+    pub now: String,
+    /// Members ordered by last known delay, untested members last.
+    pub members: Vec<ProxyNode>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ProxiesResponse {
     pub proxies: HashMap<String, ProxyInfo>,
 }
 
+/// Result of a `/proxies` fetch that tolerates malformed entries.
+#[derive(Debug, Clone, Default)]
+pub struct ParsedProxies {
+    pub proxies: HashMap<String, ProxyInfo>,
+    /// Name and parse error of each entry that was left out, sorted by name.
+    pub skipped: Vec<(String, String)>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProxyInfo {
     #[serde(rename = "type")]
@@ -55,6 +83,293 @@ pub struct ProxyInfo {
     pub all: Option<Vec<String>>,
     #[serde(default)]
     pub history: Vec<DelayHistory>,
+    #[serde(default)]
+    pub interface: Option<String>,
+    #[serde(rename = "routing-mark")]
+    #[serde(default)]
+    pub routing_mark: Option<u32>,
+    #[serde(rename = "dialer-proxy")]
+    #[serde(default)]
+    pub dialer_proxy: Option<String>,
+}
+
+impl ProxyInfo {
+    /// Outbound interface the node is bound to; mihomo reports `""` when unset.
+    pub fn bound_interface(&self) -> Option<&str> {
+        self.interface.as_deref().filter(|name| !name.is_empty())
+    }
+
+    /// Routing mark applied to the node's sockets; `0` means unset.
+    pub fn bound_routing_mark(&self) -> Option<u32> {
+        self.routing_mark.filter(|mark| *mark != 0)
+    }
+
+    /// Proxy this node dials out through; mihomo reports `""` when unset.
+    pub fn dialer(&self) -> Option<&str> {
+        self.dialer_proxy.as_deref().filter(|name| !name.is_empty())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProvidersResponse {
+    pub providers: HashMap<String, Provider>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Provider {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub provider_type: String,
+    #[serde(rename = "vehicleType")]
+    #[serde(default)]
+    pub vehicle_type: String,
+    #[serde(default, deserialize_with = "deserialize_null_as_empty_vec")]
+    pub proxies: Vec<ProviderProxy>,
+    #[serde(rename = "updatedAt")]
+    #[serde(default)]
+    pub updated_at: Option<String>,
+    #[serde(rename = "subscriptionInfo")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscription_info: Option<SubscriptionInfo>,
+}
+
+impl Provider {
+    pub fn proxy_count(&self) -> usize {
+        self.proxies.len()
+    }
+}
+
+/// How a rule provider's entries are interpreted.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RuleBehavior {
+    /// Domain names and `+.`/`*.` wildcards.
+    Domain,
+    /// IP networks in CIDR notation.
+    IpCidr,
+    /// Full rules such as `DOMAIN-SUFFIX,example.com`.
+    Classical,
+    /// A behavior this crate doesn't know yet, as reported by the controller.
+    Other(String),
+}
+
+impl RuleBehavior {
+    /// The spelling used in profiles (`behavior: ipcidr`).
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Domain => "domain",
+            Self::IpCidr => "ipcidr",
+            Self::Classical => "classical",
+            Self::Other(behavior) => behavior,
+        }
+    }
+}
+
+impl std::fmt::Display for RuleBehavior {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for RuleBehavior {
+    type Err = crate::core::MihomoError;
+
+    /// Accepts both the profile spelling and the controller's (`Domain`, `IPCIDR`).
+    /// Unknown behaviors are rejected; only deserialization falls back to
+    /// [`RuleBehavior::Other`].
+    fn from_str(s: &str) -> crate::core::Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "domain" => Ok(Self::Domain),
+            "ipcidr" => Ok(Self::IpCidr),
+            "classical" => Ok(Self::Classical),
+            _ => Err(crate::core::MihomoError::config(format!(
+                "Unknown rule provider behavior '{}', expected domain, ipcidr or classical",
+                s
+            ))),
+        }
+    }
+}
+
+impl Serialize for RuleBehavior {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for RuleBehavior {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Ok(value.parse().unwrap_or(Self::Other(value)))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RuleProvidersResponse {
+    pub providers: HashMap<String, RuleProvider>,
+}
+
+/// A rule set as reported by the controller's `/providers/rules`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleProvider {
+    pub name: String,
+    pub behavior: RuleBehavior,
+    /// `yaml`, `text` or `mrs`.
+    #[serde(default)]
+    pub format: Option<String>,
+    #[serde(rename = "ruleCount")]
+    #[serde(default)]
+    pub rule_count: usize,
+    #[serde(rename = "vehicleType")]
+    #[serde(default)]
+    pub vehicle_type: String,
+    #[serde(rename = "updatedAt")]
+    #[serde(default)]
+    pub updated_at: Option<String>,
+}
+
+/// Traffic quota and expiry parsed from a provider's `subscription-userinfo` header.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubscriptionInfo {
+    #[serde(rename = "Upload")]
+    #[serde(default)]
+    pub upload: u64,
+    #[serde(rename = "Download")]
+    #[serde(default)]
+    pub download: u64,
+    /// Quota in bytes; `0` means unlimited.
+    #[serde(rename = "Total")]
+    #[serde(default)]
+    pub total: u64,
+    /// Unix timestamp in seconds; `0` means the subscription never expires.
+    #[serde(rename = "Expire")]
+    #[serde(default)]
+    pub expire: i64,
+}
+
+impl SubscriptionInfo {
+    /// Parses a `subscription-userinfo` header value such as
+    /// `upload=1024; download=2048; total=10737418240; expire=1700000000`.
+    ///
+    /// Keys are case-insensitive and may come in any order; unknown keys are ignored
+    /// and missing or empty ones stay `0`. Returns `None` when no known key is
+    /// present or a value is not a number.
+    pub fn parse_header(value: &str) -> Option<Self> {
+        let mut info = Self::default();
+        let mut found = false;
+
+        for pair in value.split(';') {
+            let Some((key, raw)) = pair.split_once('=') else {
+                continue;
+            };
+            let raw = raw.trim();
+            // Some panels send decimals ("1.5e9") or leave fields blank.
+            let number = || -> Option<f64> {
+                if raw.is_empty() {
+                    Some(0.0)
+                } else {
+                    raw.parse::<f64>()
+                        .ok()
+                        .filter(|n| n.is_finite() && *n >= 0.0)
+                }
+            };
+            match key.trim().to_ascii_lowercase().as_str() {
+                "upload" => info.upload = number()? as u64,
+                "download" => info.download = number()? as u64,
+                "total" => info.total = number()? as u64,
+                "expire" => info.expire = number()? as i64,
+                _ => continue,
+            }
+            found = true;
+        }
+
+        found.then_some(info)
+    }
+
+    pub fn used(&self) -> u64 {
+        self.upload.saturating_add(self.download)
+    }
+
+    /// Bytes left in the quota, or `None` for unlimited plans.
+    pub fn remaining(&self) -> Option<u64> {
+        (self.total > 0).then(|| self.total.saturating_sub(self.used()))
+    }
+
+    /// Fraction of the quota used, or `None` for unlimited plans.
+    pub fn usage_ratio(&self) -> Option<f64> {
+        (self.total > 0).then(|| self.used() as f64 / self.total as f64)
+    }
+
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        if self.expire <= 0 {
+            return None;
+        }
+        DateTime::from_timestamp(self.expire, 0)
+    }
+
+    /// Whole days until expiry, negative once expired.
+    pub fn days_until_expiry_at(&self, now: DateTime<Utc>) -> Option<i64> {
+        self.expires_at().map(|expires| (expires - now).num_days())
+    }
+
+    pub fn days_until_expiry(&self) -> Option<i64> {
+        self.days_until_expiry_at(Utc::now())
+    }
+
+    /// One-line summary such as `"12.50 GB left of 100.00 GB, expires in 5 days"`.
+    pub fn summary_at(&self, now: DateTime<Utc>) -> String {
+        const GB: f64 = 1024.0 * 1024.0 * 1024.0;
+        let traffic = match self.remaining() {
+            Some(left) => format!(
+                "{:.2} GB left of {:.2} GB",
+                left as f64 / GB,
+                self.total as f64 / GB
+            ),
+            None => format!("{:.2} GB used, no quota", self.used() as f64 / GB),
+        };
+        let expiry = match self.days_until_expiry_at(now) {
+            Some(days) if days < 0 => "expired".to_string(),
+            Some(1) => "expires in 1 day".to_string(),
+            Some(days) => format!("expires in {} days", days),
+            None => "never expires".to_string(),
+        };
+        format!("{}, {}", traffic, expiry)
+    }
+
+    pub fn summary(&self) -> String {
+        self.summary_at(Utc::now())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SubscriptionAlert {
+    Expired { provider: String },
+    ExpiringSoon { provider: String, days: i64 },
+    QuotaNearlyUsed { provider: String, usage_ratio: f64 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderProxy {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub proxy_type: String,
+    #[serde(default)]
+    pub history: Vec<DelayHistory>,
+    #[serde(default)]
+    pub interface: Option<String>,
+    #[serde(rename = "routing-mark")]
+    #[serde(default)]
+    pub routing_mark: Option<u32>,
+}
+
+impl ProviderProxy {
+    /// Outbound interface the node is bound to; mihomo reports `""` when unset.
+    pub fn bound_interface(&self) -> Option<&str> {
+        self.interface.as_deref().filter(|name| !name.is_empty())
+    }
+
+    /// Routing mark applied to the node's sockets; `0` means unset.
+    pub fn bound_routing_mark(&self) -> Option<u32> {
+        self.routing_mark.filter(|mark| *mark != 0)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -74,6 +389,81 @@ pub struct DelayTestResponse {
     pub delay: u32,
 }
 
+/// Result of a single-proxy delay test, separating dead nodes from API failures.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DelayOutcome {
+    Ok(u32),
+    /// The node did not answer within the timeout (HTTP 504 from the controller).
+    Timeout,
+    /// The test request through the node failed (HTTP 503 from the controller).
+    Unreachable,
+    /// The controller could not be queried or rejected the request.
+    ApiError(String),
+}
+
+impl DelayOutcome {
+    pub fn from_result(result: crate::core::Result<u32>) -> Self {
+        match result {
+            Ok(delay) => DelayOutcome::Ok(delay),
+            Err(err) => match err.http_status() {
+                Some(504) => DelayOutcome::Timeout,
+                Some(503) => DelayOutcome::Unreachable,
+                _ => DelayOutcome::ApiError(err.to_string()),
+            },
+        }
+    }
+
+    pub fn delay(&self) -> Option<u32> {
+        match self {
+            DelayOutcome::Ok(delay) => Some(*delay),
+            _ => None,
+        }
+    }
+
+    /// True when the node itself failed, as opposed to the API call.
+    pub fn is_dead(&self) -> bool {
+        matches!(self, DelayOutcome::Timeout | DelayOutcome::Unreachable)
+    }
+}
+
+/// One message from the `/logs` stream.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogEntry {
+    #[serde(rename = "type")]
+    #[serde(default)]
+    pub level: String,
+    #[serde(default)]
+    pub payload: String,
+    /// Sent by mihomo only in some log formats; stamped with the receive time
+    /// (RFC 3339) when missing from a streamed entry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time: Option<String>,
+    /// When this client received the entry. Not part of the wire format.
+    #[serde(skip)]
+    pub received_at: Option<DateTime<Utc>>,
+}
+
+impl LogEntry {
+    pub(crate) fn stamped(mut self, now: DateTime<Utc>) -> Self {
+        if self.time.is_none() {
+            self.time = Some(now.to_rfc3339());
+        }
+        self.received_at = Some(now);
+        self
+    }
+
+    /// `time` as a UTC timestamp, or the receive time when `time` isn't RFC 3339
+    /// (mihomo's structured logs only send a clock time). Sort streamed entries with
+    /// `entries.sort_by_key(LogEntry::timestamp)`.
+    pub fn timestamp(&self) -> Option<DateTime<Utc>> {
+        self.time
+            .as_deref()
+            .and_then(|time| DateTime::parse_from_rfc3339(time).ok())
+            .map(|time| time.with_timezone(&Utc))
+            .or(self.received_at)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrafficData {
     pub up: u64,
@@ -88,6 +478,110 @@ pub struct MemoryData {
     pub os_limit: u64,
 }
 
+/// Health summary from [`MihomoClient::healthcheck`](super::MihomoClient::healthcheck).
+/// Optional fields are `None` when the matching probe failed or timed out.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HealthReport {
+    pub api_reachable: bool,
+    pub version: Option<String>,
+    /// Only available when the core exposes `/debug/pprof`.
+    pub goroutines: Option<u64>,
+    pub memory_bytes: Option<u64>,
+    /// Share of the core's memory limit in use; `None` when mihomo reports no limit.
+    pub memory_pct: Option<f64>,
+    pub connection_count: Option<usize>,
+    /// Remote providers that currently supply no proxies, usually a failed fetch.
+    pub failing_providers: Vec<String>,
+}
+
+impl HealthReport {
+    pub fn is_healthy(&self) -> bool {
+        self.api_reachable && self.failing_providers.is_empty()
+    }
+}
+
+/// Runtime settings reported by `GET /configs`. Listener ports are `0` when disabled.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ServiceConfigInfo {
+    #[serde(default)]
+    pub port: u16,
+    #[serde(rename = "socks-port")]
+    #[serde(default)]
+    pub socks_port: u16,
+    #[serde(rename = "mixed-port")]
+    #[serde(default)]
+    pub mixed_port: u16,
+    #[serde(rename = "redir-port")]
+    #[serde(default)]
+    pub redir_port: u16,
+    #[serde(rename = "tproxy-port")]
+    #[serde(default)]
+    pub tproxy_port: u16,
+    #[serde(default)]
+    pub mode: String,
+    #[serde(rename = "log-level")]
+    #[serde(default)]
+    pub log_level: String,
+    #[serde(rename = "allow-lan")]
+    #[serde(default)]
+    pub allow_lan: bool,
+    #[serde(rename = "bind-address")]
+    #[serde(default)]
+    pub bind_address: String,
+    #[serde(default)]
+    pub ipv6: bool,
+}
+
+/// A partial update for `PATCH /configs`. Only the fields that are `Some` are sent,
+/// so mihomo leaves every other runtime setting as it is.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ServiceConfigPatch {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub socks_port: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mixed_port: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redir_port: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tproxy_port: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_level: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow_lan: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bind_address: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ipv6: Option<bool>,
+}
+
+impl ServiceConfigPatch {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl ServiceConfigInfo {
+    /// Enabled inbound listeners as `(kind, port)`, in http, socks, mixed, redir, tproxy order.
+    pub fn active_ports(&self) -> Vec<(String, u16)> {
+        [
+            ("http", self.port),
+            ("socks", self.socks_port),
+            ("mixed", self.mixed_port),
+            ("redir", self.redir_port),
+            ("tproxy", self.tproxy_port),
+        ]
+        .into_iter()
+        .filter(|(_, port)| *port != 0)
+        .map(|(kind, port)| (kind.to_string(), port))
+        .collect()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Connection {
     pub id: String,
@@ -108,6 +602,56 @@ pub struct Connection {
     pub rule_payload: String,
 }
 
+impl Connection {
+    /// Parses `start`, which mihomo reports as an RFC 3339 timestamp.
+    pub fn started_at(&self) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(&self.start)
+            .ok()
+            .map(|start| start.with_timezone(&Utc))
+    }
+
+    /// Time since the connection started, or `None` if `start` can't be parsed.
+    pub fn duration(&self) -> Option<std::time::Duration> {
+        self.duration_at(Utc::now())
+    }
+
+    pub fn duration_at(&self, now: DateTime<Utc>) -> Option<std::time::Duration> {
+        (now - self.started_at()?).to_std().ok()
+    }
+
+    /// Node that actually carried the traffic. mihomo lists `chains` innermost
+    /// first, so this is the first entry.
+    pub fn final_proxy(&self) -> Option<&str> {
+        self.chains.first().map(String::as_str)
+    }
+
+    /// Group the matching rule selected, i.e. the outermost chain entry.
+    /// `None` when the rule pointed straight at a node such as `DIRECT`.
+    pub fn entry_group(&self) -> Option<&str> {
+        match self.chains.as_slice() {
+            [_, .., last] => Some(last.as_str()),
+            _ => None,
+        }
+    }
+
+    /// The rule that routed this connection and its index in `rules`, matched on
+    /// rule type and payload. Pass the list from `GET /rules`, which is in config order.
+    pub fn matching_rule<'a>(&self, rules: &'a [Rule]) -> Option<(usize, &'a Rule)> {
+        rules.iter().enumerate().find(|(_, rule)| {
+            rule.rule_type.eq_ignore_ascii_case(&self.rule) && rule.payload == self.rule_payload
+        })
+    }
+
+    /// Groups between the entry group and the final node, outermost first.
+    pub fn intermediate_groups(&self) -> impl Iterator<Item = &str> {
+        let inner = match self.chains.len() {
+            0..=2 => &[][..],
+            len => &self.chains[1..len - 1],
+        };
+        inner.iter().rev().map(String::as_str)
+    }
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ConnectionMetadata {
     #[serde(default)]
@@ -164,6 +708,218 @@ pub struct ConnectionSnapshot {
     pub connections: Vec<Connection>,
 }
 
+#[cfg(test)]
+impl Connection {
+    /// Test fixture with only `id` and the byte counters set.
+    pub(crate) fn fixture(id: &str, upload: u64, download: u64) -> Self {
+        Self {
+            id: id.to_string(),
+            metadata: ConnectionMetadata::default(),
+            upload,
+            download,
+            start: String::new(),
+            chains: vec![],
+            rule: String::new(),
+            rule_payload: String::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+impl ConnectionSnapshot {
+    /// Test fixture built from `(id, upload, download)` entries.
+    pub(crate) fn fixture(connections: &[(&str, u64, u64)]) -> Self {
+        Self {
+            download_total: 0,
+            upload_total: 0,
+            connections: connections
+                .iter()
+                .map(|(id, upload, download)| Connection::fixture(id, *upload, *download))
+                .collect(),
+        }
+    }
+}
+
+/// Bytes carried by one outbound proxy across its active connections.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProxyBandwidth {
+    pub proxy: String,
+    pub upload: u64,
+    pub download: u64,
+    pub connections: usize,
+}
+
+impl ProxyBandwidth {
+    pub fn total(&self) -> u64 {
+        self.upload.saturating_add(self.download)
+    }
+}
+
+/// Bytes exchanged with one destination host across its active connections.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostBandwidth {
+    /// The sniffed or requested host name, or the destination IP when there is none.
+    pub host: String,
+    pub upload: u64,
+    pub download: u64,
+    pub connections: usize,
+}
+
+impl HostBandwidth {
+    pub fn total(&self) -> u64 {
+        self.upload.saturating_add(self.download)
+    }
+}
+
+impl ConnectionSnapshot {
+    /// Per-proxy byte totals, busiest first.
+    pub fn bandwidth_by_proxy(&self) -> Vec<ProxyBandwidth> {
+        bandwidth_by_proxy(&self.connections)
+    }
+
+    /// Per-destination byte totals, busiest first.
+    pub fn bandwidth_by_host(&self) -> Vec<HostBandwidth> {
+        bandwidth_by_host(&self.connections)
+    }
+}
+
+impl ConnectionsResponse {
+    /// Per-proxy byte totals, busiest first.
+    pub fn bandwidth_by_proxy(&self) -> Vec<ProxyBandwidth> {
+        bandwidth_by_proxy(&self.connections)
+    }
+
+    /// Per-destination byte totals, busiest first.
+    pub fn bandwidth_by_host(&self) -> Vec<HostBandwidth> {
+        bandwidth_by_host(&self.connections)
+    }
+}
+
+fn bandwidth_by_host(connections: &[Connection]) -> Vec<HostBandwidth> {
+    let mut by_host: HashMap<&str, HostBandwidth> = HashMap::new();
+    for conn in connections {
+        let host = if conn.metadata.host.is_empty() {
+            conn.metadata.destination_ip.as_str()
+        } else {
+            conn.metadata.host.as_str()
+        };
+        if host.is_empty() {
+            continue;
+        }
+        let entry = by_host.entry(host).or_insert_with(|| HostBandwidth {
+            host: host.to_string(),
+            upload: 0,
+            download: 0,
+            connections: 0,
+        });
+        entry.upload = entry.upload.saturating_add(conn.upload);
+        entry.download = entry.download.saturating_add(conn.download);
+        entry.connections += 1;
+    }
+
+    let mut usage: Vec<HostBandwidth> = by_host.into_values().collect();
+    usage.sort_by(|a, b| b.total().cmp(&a.total()).then_with(|| a.host.cmp(&b.host)));
+    usage
+}
+
+fn bandwidth_by_proxy(connections: &[Connection]) -> Vec<ProxyBandwidth> {
+    let mut by_proxy: HashMap<&str, ProxyBandwidth> = HashMap::new();
+    for conn in connections {
+        let Some(proxy) = conn.final_proxy() else {
+            continue;
+        };
+        let entry = by_proxy.entry(proxy).or_insert_with(|| ProxyBandwidth {
+            proxy: proxy.to_string(),
+            upload: 0,
+            download: 0,
+            connections: 0,
+        });
+        entry.upload = entry.upload.saturating_add(conn.upload);
+        entry.download = entry.download.saturating_add(conn.download);
+        entry.connections += 1;
+    }
+
+    let mut usage: Vec<ProxyBandwidth> = by_proxy.into_values().collect();
+    usage.sort_by(|a, b| {
+        b.total()
+            .cmp(&a.total())
+            .then_with(|| a.proxy.cmp(&b.proxy))
+    });
+    usage
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RulesResponse {
+    #[serde(default, deserialize_with = "deserialize_null_as_empty_vec")]
+    pub rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rule {
+    #[serde(rename = "type")]
+    pub rule_type: String,
+    #[serde(default)]
+    pub payload: String,
+    #[serde(default)]
+    pub proxy: String,
+    /// Entry count for rule-set rules; mihomo reports `-1` for every other rule.
+    #[serde(default = "default_rule_size")]
+    pub size: i64,
+}
+
+fn default_rule_size() -> i64 {
+    -1
+}
+
+impl Rule {
+    pub fn is_ruleset(&self) -> bool {
+        self.rule_type.eq_ignore_ascii_case("RuleSet")
+            || self.rule_type.eq_ignore_ascii_case("RULE-SET")
+    }
+
+    /// Number of entries in the referenced rule-set, if this is a rule-set rule.
+    pub fn ruleset_size(&self) -> Option<u64> {
+        if self.is_ruleset() {
+            u64::try_from(self.size).ok()
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleStats {
+    pub total: usize,
+    pub by_type: HashMap<String, usize>,
+    pub rulesets: usize,
+    /// Sum of entries across all rule-set rules.
+    pub ruleset_entries: u64,
+    /// Largest rule-sets first, as `(payload, entries)`.
+    pub largest_rulesets: Vec<(String, u64)>,
+}
+
+impl RuleStats {
+    pub fn from_rules(rules: &[Rule]) -> Self {
+        let mut stats = RuleStats {
+            total: rules.len(),
+            ..Default::default()
+        };
+        for rule in rules {
+            *stats.by_type.entry(rule.rule_type.clone()).or_insert(0) += 1;
+            if rule.is_ruleset() {
+                stats.rulesets += 1;
+                let size = rule.ruleset_size().unwrap_or(0);
+                stats.ruleset_entries = stats.ruleset_entries.saturating_add(size);
+                stats.largest_rulesets.push((rule.payload.clone(), size));
+            }
+        }
+        stats
+            .largest_rulesets
+            .sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        stats
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -194,6 +950,16 @@ mod tests {
         assert!(!version.meta);
     }
 
+    #[test]
+    fn test_version_mihomo_response() {
+        let json = r#"{"meta":true,"version":"v1.19.2"}"#;
+        let version: Version = serde_json::from_str(json).unwrap();
+
+        assert_eq!(version.version, "v1.19.2");
+        assert!(version.meta);
+        assert!(!version.premium);
+    }
+
     #[test]
     fn test_proxy_node_serialization() {
         let node = ProxyNode {
@@ -201,6 +967,9 @@ mod tests {
             proxy_type: "ss".to_string(),
             delay: Some(100),
             alive: true,
+            interface: None,
+            routing_mark: None,
+            dialer_proxy: None,
         };
 
         let json = serde_json::to_string(&node).unwrap();
@@ -492,4 +1261,535 @@ mod tests {
         assert_eq!(snapshot.upload_total, 200);
         assert_eq!(snapshot.connections.len(), 0);
     }
+
+    #[test]
+    fn test_connection_duration_from_rfc3339_start() {
+        let conn: Connection =
+            serde_json::from_str(r#"{"id":"1","start":"2024-01-01T08:00:00.5+08:00"}"#).unwrap();
+        let now = DateTime::parse_from_rfc3339("2024-01-01T00:01:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        assert_eq!(
+            conn.duration_at(now),
+            Some(std::time::Duration::from_millis(59_500))
+        );
+
+        let unparsable: Connection = serde_json::from_str(r#"{"id":"2","start":"soon"}"#).unwrap();
+        assert!(unparsable.started_at().is_none());
+        assert!(unparsable.duration().is_none());
+    }
+
+    #[test]
+    fn test_bandwidth_by_proxy_groups_on_first_chain_entry() {
+        let json = r#"{
+            "downloadTotal": 0,
+            "uploadTotal": 0,
+            "connections": [
+                {"id":"1","upload":10,"download":100,"chains":["HK-01","GLOBAL"]},
+                {"id":"2","upload":5,"download":50,"chains":["HK-01","Streaming"]},
+                {"id":"3","upload":1000,"download":0,"chains":["DIRECT"]},
+                {"id":"4","upload":7,"download":7,"chains":[]}
+            ]
+        }"#;
+        let snapshot: ConnectionSnapshot = serde_json::from_str(json).unwrap();
+
+        let usage = snapshot.bandwidth_by_proxy();
+        assert_eq!(usage.len(), 2);
+        assert_eq!(usage[0].proxy, "DIRECT");
+        assert_eq!(usage[0].total(), 1000);
+        assert_eq!(
+            usage[1],
+            ProxyBandwidth {
+                proxy: "HK-01".to_string(),
+                upload: 15,
+                download: 150,
+                connections: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_service_config_patch_omits_unset_fields() {
+        let patch = ServiceConfigPatch {
+            allow_lan: Some(true),
+            mixed_port: Some(7890),
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_value(&patch).unwrap(),
+            serde_json::json!({ "allow-lan": true, "mixed-port": 7890 })
+        );
+        assert!(!patch.is_empty());
+        assert!(ServiceConfigPatch::default().is_empty());
+    }
+
+    #[test]
+    fn test_bandwidth_by_host_falls_back_to_destination_ip() {
+        let json = r#"{
+            "connections": [
+                {"id":"1","upload":10,"download":100,"metadata":{"host":"youtube.com"}},
+                {"id":"2","upload":5,"download":50,"metadata":{"host":"youtube.com"}},
+                {"id":"3","upload":1,"download":1,"metadata":{"destinationIP":"1.1.1.1"}},
+                {"id":"4","upload":7,"download":7}
+            ]
+        }"#;
+        let response: ConnectionsResponse = serde_json::from_str(json).unwrap();
+
+        let usage = response.bandwidth_by_host();
+        assert_eq!(
+            usage[0],
+            HostBandwidth {
+                host: "youtube.com".to_string(),
+                upload: 15,
+                download: 150,
+                connections: 2,
+            }
+        );
+        assert_eq!(usage[1].host, "1.1.1.1");
+        assert_eq!(usage.len(), 2);
+    }
+
+    #[test]
+    fn test_proxy_info_binding_fields_treat_defaults_as_unset() {
+        let bound: ProxyInfo =
+            serde_json::from_str(r#"{"type":"Shadowsocks","interface":"eth1","routing-mark":255}"#)
+                .unwrap();
+        assert_eq!(bound.bound_interface(), Some("eth1"));
+        assert_eq!(bound.bound_routing_mark(), Some(255));
+
+        let unbound: ProxyInfo =
+            serde_json::from_str(r#"{"type":"Shadowsocks","interface":"","routing-mark":0}"#)
+                .unwrap();
+        assert_eq!(unbound.bound_interface(), None);
+        assert_eq!(unbound.bound_routing_mark(), None);
+    }
+
+    #[test]
+    fn test_service_config_active_ports_skip_disabled() {
+        let json = r#"{
+            "port": 7890,
+            "socks-port": 0,
+            "mixed-port": 7893,
+            "redir-port": 0,
+            "tproxy-port": 7895,
+            "mode": "rule",
+            "log-level": "info",
+            "allow-lan": true
+        }"#;
+
+        let config: ServiceConfigInfo = serde_json::from_str(json).unwrap();
+        assert_eq!(config.mode, "rule");
+        assert!(config.allow_lan);
+        assert_eq!(
+            config.active_ports(),
+            vec![
+                ("http".to_string(), 7890),
+                ("mixed".to_string(), 7893),
+                ("tproxy".to_string(), 7895),
+            ]
+        );
+        assert!(ServiceConfigInfo::default().active_ports().is_empty());
+    }
+
+    #[test]
+    fn log_entries_are_stamped_and_sort_by_timestamp() {
+        let now = DateTime::parse_from_rfc3339("2026-10-18T08:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let plain: LogEntry = serde_json::from_str(r#"{"type":"info","payload":"later"}"#).unwrap();
+        let plain = plain.stamped(now);
+        assert_eq!(plain.time.as_deref(), Some("2026-10-18T08:00:00+00:00"));
+
+        let dated: LogEntry = serde_json::from_str(
+            r#"{"type":"warning","payload":"earlier","time":"2026-10-18T07:59:59Z"}"#,
+        )
+        .unwrap();
+        let dated = dated.stamped(now);
+        assert_eq!(dated.time.as_deref(), Some("2026-10-18T07:59:59Z"));
+
+        let clock: LogEntry =
+            serde_json::from_str(r#"{"type":"debug","payload":"clock","time":"08:00:01"}"#)
+                .unwrap();
+        let clock = clock.stamped(now + chrono::Duration::seconds(1));
+        assert_eq!(clock.timestamp(), Some(now + chrono::Duration::seconds(1)));
+
+        let mut entries = [clock, plain, dated];
+        entries.sort_by_key(LogEntry::timestamp);
+        let payloads: Vec<&str> = entries.iter().map(|e| e.payload.as_str()).collect();
+        assert_eq!(payloads, vec!["earlier", "later", "clock"]);
+    }
+
+    #[test]
+    fn test_connection_matching_rule_finds_first_match() {
+        let rules: Vec<Rule> = serde_json::from_str(
+            r#"[
+                {"type": "DomainSuffix", "payload": "google.com", "proxy": "Proxy"},
+                {"type": "RuleSet", "payload": "ads", "proxy": "REJECT", "size": 10},
+                {"type": "Match", "payload": "", "proxy": "DIRECT"}
+            ]"#,
+        )
+        .unwrap();
+        let conn: Connection =
+            serde_json::from_str(r#"{"id": "c1", "rule": "RuleSet", "rulePayload": "ads"}"#)
+                .unwrap();
+        let fallback: Connection =
+            serde_json::from_str(r#"{"id": "c2", "rule": "Match", "rulePayload": ""}"#).unwrap();
+        let stale: Connection = serde_json::from_str(
+            r#"{"id": "c3", "rule": "DomainSuffix", "rulePayload": "removed.com"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            conn.matching_rule(&rules)
+                .map(|(i, r)| (i, r.proxy.as_str())),
+            Some((1, "REJECT"))
+        );
+        assert_eq!(fallback.matching_rule(&rules).map(|(i, _)| i), Some(2));
+        assert!(stale.matching_rule(&rules).is_none());
+    }
+
+    #[test]
+    fn test_subscription_info_remaining_and_expiry() {
+        let json = r#"{
+            "name": "airport",
+            "type": "Proxy",
+            "vehicleType": "HTTP",
+            "proxies": [],
+            "subscriptionInfo": {
+                "Upload": 1073741824,
+                "Download": 9663676416,
+                "Total": 107374182400,
+                "Expire": 1700432000
+            }
+        }"#;
+        let provider: Provider = serde_json::from_str(json).unwrap();
+        let info = provider.subscription_info.expect("subscription info");
+        let now = DateTime::from_timestamp(1700000000, 0).unwrap();
+
+        assert_eq!(info.remaining(), Some(96636764160));
+        assert_eq!(info.usage_ratio(), Some(0.1));
+        assert_eq!(info.days_until_expiry_at(now), Some(5));
+        assert_eq!(
+            info.summary_at(now),
+            "90.00 GB left of 100.00 GB, expires in 5 days"
+        );
+
+        let unlimited = SubscriptionInfo::default();
+        assert_eq!(unlimited.remaining(), None);
+        assert_eq!(
+            unlimited.summary_at(now),
+            "0.00 GB used, no quota, never expires"
+        );
+    }
+
+    #[test]
+    fn test_rule_provider_behavior_parses_both_spellings() {
+        let json = r#"{
+            "providers": {
+                "ads": {"name":"ads","behavior":"Domain","format":"MrsRule","ruleCount":120,
+                        "type":"Rule","vehicleType":"HTTP","updatedAt":"2024-01-01T00:00:00Z"},
+                "lan": {"name":"lan","behavior":"IPCIDR","type":"Rule","vehicleType":"File"},
+                "geo": {"name":"geo","behavior":"GeoSite","type":"Rule","vehicleType":"File"}
+            }
+        }"#;
+        let response: RuleProvidersResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(response.providers["ads"].behavior, RuleBehavior::Domain);
+        assert_eq!(response.providers["ads"].rule_count, 120);
+        assert_eq!(response.providers["lan"].behavior, RuleBehavior::IpCidr);
+        assert_eq!(
+            response.providers["geo"].behavior,
+            RuleBehavior::Other("GeoSite".to_string())
+        );
+        assert_eq!(response.providers["geo"].behavior.to_string(), "GeoSite");
+
+        assert_eq!(
+            "classical".parse::<RuleBehavior>().unwrap(),
+            RuleBehavior::Classical
+        );
+        assert!("geosite".parse::<RuleBehavior>().is_err());
+        assert_eq!(
+            serde_yaml::to_string(&RuleBehavior::IpCidr).unwrap().trim(),
+            "ipcidr"
+        );
+    }
+
+    #[test]
+    fn test_subscription_info_parse_header() {
+        let info = SubscriptionInfo::parse_header(
+            "upload=1024; download=2048;total=10737418240; expire=1700000000",
+        )
+        .expect("parse header");
+        assert_eq!(
+            info,
+            SubscriptionInfo {
+                upload: 1024,
+                download: 2048,
+                total: 10737418240,
+                expire: 1700000000,
+            }
+        );
+
+        let partial = SubscriptionInfo::parse_header("Download=1.5e3; Total=; expire=; x=y")
+            .expect("parse partial header");
+        assert_eq!(partial.download, 1500);
+        assert_eq!(partial.total, 0);
+        assert_eq!(partial.expires_at(), None);
+
+        assert_eq!(SubscriptionInfo::parse_header(""), None);
+        assert_eq!(SubscriptionInfo::parse_header("plan=pro"), None);
+        assert_eq!(SubscriptionInfo::parse_header("upload=-1; total=10"), None);
+        assert_eq!(SubscriptionInfo::parse_header("total=lots"), None);
+    }
+
+    #[test]
+    fn test_connection_chain_ends() {
+        let json = r#"{"id": "c1", "chains": ["HK-01", "Auto", "Proxy"]}"#;
+        let conn: Connection = serde_json::from_str(json).unwrap();
+        assert_eq!(conn.final_proxy(), Some("HK-01"));
+        assert_eq!(conn.entry_group(), Some("Proxy"));
+        assert_eq!(conn.intermediate_groups().collect::<Vec<_>>(), vec!["Auto"]);
+
+        let direct: Connection =
+            serde_json::from_str(r#"{"id": "c2", "chains": ["DIRECT"]}"#).unwrap();
+        assert_eq!(direct.final_proxy(), Some("DIRECT"));
+        assert_eq!(direct.entry_group(), None);
+        assert_eq!(direct.intermediate_groups().count(), 0);
+    }
+
+    #[test]
+    fn test_rule_stats_sum_ruleset_entries() {
+        let json = r#"{
+            "rules": [
+                {"type": "RuleSet", "payload": "ads", "proxy": "REJECT", "size": 1200},
+                {"type": "RuleSet", "payload": "cn", "proxy": "DIRECT", "size": 8000},
+                {"type": "DomainSuffix", "payload": "example.com", "proxy": "Proxy", "size": -1},
+                {"type": "Match", "payload": "", "proxy": "Proxy"}
+            ]
+        }"#;
+
+        let response: RulesResponse = serde_json::from_str(json).unwrap();
+        assert!(response.rules[0].is_ruleset());
+        assert_eq!(response.rules[0].ruleset_size(), Some(1200));
+        assert_eq!(response.rules[2].ruleset_size(), None);
+        assert_eq!(response.rules[3].size, -1);
+
+        let stats = RuleStats::from_rules(&response.rules);
+        assert_eq!(stats.total, 4);
+        assert_eq!(stats.rulesets, 2);
+        assert_eq!(stats.ruleset_entries, 9200);
+        assert_eq!(stats.by_type["RuleSet"], 2);
+        assert_eq!(
+            stats.largest_rulesets,
+            vec![("cn".to_string(), 8000), ("ads".to_string(), 1200)]
+        );
+    }
+}
+
+#[cfg(test)]
+mod roundtrip_tests {
+    use super::*;
+    use proptest::collection::vec;
+    use proptest::option;
+    use proptest::prelude::*;
+    use serde::de::DeserializeOwned;
+
+    // Serialize, parse back, and serialize again: any renamed or defaulted field
+    // that does not survive the trip shows up as a JSON difference.
+    fn assert_roundtrip<T: Serialize + DeserializeOwned>(value: &T) {
+        let json = serde_json::to_value(value).unwrap();
+        let parsed: T = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), json);
+    }
+
+    fn delay_history() -> impl Strategy<Value = DelayHistory> {
+        (any::<String>(), any::<u32>()).prop_map(|(time, delay)| DelayHistory { time, delay })
+    }
+
+    fn proxy_info() -> impl Strategy<Value = ProxyInfo> {
+        (
+            any::<String>(),
+            option::of(any::<String>()),
+            option::of(vec(any::<String>(), 0..4)),
+            vec(delay_history(), 0..3),
+            option::of(any::<String>()),
+            option::of(any::<u32>()),
+            option::of(any::<String>()),
+        )
+            .prop_map(
+                |(proxy_type, now, all, history, interface, routing_mark, dialer_proxy)| {
+                    ProxyInfo {
+                        proxy_type,
+                        now,
+                        all,
+                        history,
+                        interface,
+                        routing_mark,
+                        dialer_proxy,
+                    }
+                },
+            )
+    }
+
+    fn connection_metadata() -> impl Strategy<Value = ConnectionMetadata> {
+        vec(any::<String>(), 10).prop_map(|fields| {
+            let mut fields = fields.into_iter();
+            let mut next = || fields.next().unwrap_or_default();
+            ConnectionMetadata {
+                network: next(),
+                connection_type: next(),
+                source_ip: next(),
+                destination_ip: next(),
+                source_port: next(),
+                destination_port: next(),
+                host: next(),
+                dns_mode: next(),
+                process_path: next(),
+                special_proxy: next(),
+            }
+        })
+    }
+
+    fn connection() -> impl Strategy<Value = Connection> {
+        (
+            any::<String>(),
+            connection_metadata(),
+            any::<u64>(),
+            any::<u64>(),
+            any::<String>(),
+            vec(any::<String>(), 0..4),
+            any::<String>(),
+            any::<String>(),
+        )
+            .prop_map(
+                |(id, metadata, upload, download, start, chains, rule, rule_payload)| Connection {
+                    id,
+                    metadata,
+                    upload,
+                    download,
+                    start,
+                    chains,
+                    rule,
+                    rule_payload,
+                },
+            )
+    }
+
+    fn provider() -> impl Strategy<Value = Provider> {
+        let proxy = (
+            any::<String>(),
+            any::<String>(),
+            vec(delay_history(), 0..3),
+            option::of(any::<String>()),
+            option::of(any::<u32>()),
+        )
+            .prop_map(|(name, proxy_type, history, interface, routing_mark)| {
+                ProviderProxy {
+                    name,
+                    proxy_type,
+                    history,
+                    interface,
+                    routing_mark,
+                }
+            });
+        (
+            any::<String>(),
+            any::<String>(),
+            any::<String>(),
+            vec(proxy, 0..3),
+            option::of(any::<String>()),
+            option::of(
+                (any::<u64>(), any::<u64>(), any::<u64>(), any::<i64>()).prop_map(
+                    |(upload, download, total, expire)| SubscriptionInfo {
+                        upload,
+                        download,
+                        total,
+                        expire,
+                    },
+                ),
+            ),
+        )
+            .prop_map(
+                |(name, provider_type, vehicle_type, proxies, updated_at, subscription_info)| {
+                    Provider {
+                        name,
+                        provider_type,
+                        vehicle_type,
+                        proxies,
+                        updated_at,
+                        subscription_info,
+                    }
+                },
+            )
+    }
+
+    proptest! {
+        #[test]
+        fn version_roundtrip(version in any::<String>(), premium in any::<bool>(), meta in any::<bool>()) {
+            assert_roundtrip(&Version { version, premium, meta });
+        }
+
+        #[test]
+        fn proxy_node_roundtrip(
+            name in any::<String>(),
+            proxy_type in any::<String>(),
+            delay in option::of(any::<u32>()),
+            alive in any::<bool>(),
+            interface in option::of(any::<String>()),
+            routing_mark in option::of(any::<u32>()),
+            dialer_proxy in option::of(any::<String>()),
+        ) {
+            assert_roundtrip(&ProxyNode { name, proxy_type, delay, alive, interface, routing_mark, dialer_proxy });
+        }
+
+        #[test]
+        fn proxy_group_roundtrip(
+            name in any::<String>(),
+            group_type in any::<String>(),
+            now in any::<String>(),
+            all in vec(any::<String>(), 0..4),
+        ) {
+            assert_roundtrip(&ProxyGroup { name, group_type, now, all });
+        }
+
+        #[test]
+        fn proxy_info_roundtrip(info in proxy_info()) {
+            assert_roundtrip(&info);
+        }
+
+        #[test]
+        fn provider_roundtrip(provider in provider()) {
+            assert_roundtrip(&provider);
+        }
+
+        #[test]
+        fn traffic_and_memory_roundtrip(up in any::<u64>(), down in any::<u64>(), in_use in any::<u64>(), os_limit in any::<u64>()) {
+            assert_roundtrip(&TrafficData { up, down });
+            assert_roundtrip(&MemoryData { in_use, os_limit });
+        }
+
+        #[test]
+        fn connections_roundtrip(
+            connections in vec(connection(), 0..3),
+            download_total in any::<u64>(),
+            upload_total in any::<u64>(),
+        ) {
+            assert_roundtrip(&ConnectionsResponse {
+                download_total,
+                upload_total,
+                connections: connections.clone(),
+            });
+            assert_roundtrip(&ConnectionSnapshot { download_total, upload_total, connections });
+        }
+
+        #[test]
+        fn proxy_info_ignores_unknown_fields(info in proxy_info(), extra in any::<String>()) {
+            let mut json = serde_json::to_value(&info).unwrap();
+            json["udp"] = serde_json::Value::Bool(true);
+            json["extra"] = serde_json::Value::String(extra);
+            let parsed: ProxyInfo = serde_json::from_value(json).unwrap();
+            prop_assert_eq!(parsed.proxy_type, info.proxy_type);
+            prop_assert_eq!(parsed.history.len(), info.history.len());
+        }
+    }
 }
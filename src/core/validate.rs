@@ -42,9 +42,13 @@ pub fn validate_version_name(name: &str) -> Result<()> {
     validate_simple_name(name, "version", true, ErrorCode::InvalidVersion)
 }
 
+pub fn validate_instance_name(name: &str) -> Result<()> {
+    validate_simple_name(name, "instance name", false, ErrorCode::InvalidInstanceName)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{validate_profile_name, validate_version_name};
+    use super::{validate_instance_name, validate_profile_name, validate_version_name};
 
     #[test]
     fn profile_name_validation_rules() {
@@ -63,4 +67,12 @@ mod tests {
         assert!(validate_version_name("a\\b").is_err());
         assert!(validate_version_name("bad version").is_err());
     }
+
+    #[test]
+    fn instance_name_validation_rules() {
+        assert!(validate_instance_name("test-2").is_ok());
+        assert!(validate_instance_name("").is_err());
+        assert!(validate_instance_name("../prod").is_err());
+        assert!(validate_instance_name("a+b").is_err());
+    }
 }
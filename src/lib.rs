@@ -3,6 +3,8 @@ pub mod config;
 pub mod connection;
 pub mod core;
 pub mod doctor;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 pub mod proxy;
 pub mod service;
 pub mod version;
@@ -20,14 +22,72 @@ pub use version::{Channel, VersionManager};
 use std::path::Path;
 
 pub async fn install_mihomo(version: Option<&str>) -> Result<String> {
+    install_mihomo_with(version, false).await
+}
+
+/// Like [`install_mihomo`], but skips the download when the version is already
+/// installed unless `force` is set.
+pub async fn install_mihomo_with(version: Option<&str>, force: bool) -> Result<String> {
+    let vm = VersionManager::new()?;
+    let version = match version {
+        Some(v) => v.to_string(),
+        None => version::fetch_latest(Channel::Stable).await?.version,
+    };
+    vm.ensure_installed(&version, force).await?;
+    Ok(version)
+}
+
+/// First-run setup: installs the core if needed, makes sure a config exists and
+/// starts the service unless it is already running. Safe to call repeatedly.
+///
+/// Without `version`, the current default is used, or the latest stable release
+/// is installed and made default if none is set. Without `config`, the current
+/// profile is used, creating the default one if necessary.
+pub async fn bootstrap(version: Option<&str>, config: Option<&Path>) -> Result<()> {
     let vm = VersionManager::new()?;
-    if let Some(v) = version {
-        vm.install(v).await?;
-        Ok(v.to_string())
-    } else {
-        let version = vm.install_channel(Channel::Stable).await?;
-        Ok(version)
+    let binary = match version {
+        Some(version) => {
+            let binary = vm.ensure_installed(version, false).await?;
+            if vm.get_default().await.is_err() {
+                vm.set_default(version).await?;
+            }
+            binary
+        }
+        None => match vm.get_binary_path(None).await {
+            Ok(binary) => binary,
+            Err(_) => {
+                let version = version::fetch_latest(Channel::Stable).await?.version;
+                let binary = vm.ensure_installed(&version, false).await?;
+                vm.set_default(&version).await?;
+                binary
+            }
+        },
+    };
+
+    let config_path = match config {
+        Some(path) => {
+            if !path.exists() {
+                return Err(MihomoError::NotFound(format!(
+                    "Config file not found: {}",
+                    path.display()
+                )));
+            }
+            path.to_path_buf()
+        }
+        None => {
+            let cm = ConfigManager::new()?;
+            cm.ensure_default_config().await?;
+            cm.ensure_external_controller().await?;
+            cm.get_current_path().await?
+        }
+    };
+
+    let sm = ServiceManager::new(binary, config_path);
+    if sm.is_running().await {
+        log::debug!("Service already running, nothing to start");
+        return Ok(());
     }
+    sm.start().await
 }
 
 pub async fn start_service(config_path: &Path) -> Result<()> {
@@ -0,0 +1,518 @@
+//! Prometheus exporter for a running core. Enabled with the `metrics` feature.
+
+use crate::core::{
+    HealthReport, MemoryData, MihomoClient, ProxyInfo, ReconnectPolicy, Result, StreamEvent,
+    TrafficData,
+};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio_util::sync::CancellationToken;
+
+const MAX_REQUEST_HEAD_BYTES: usize = 8 * 1024;
+const REQUEST_READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Latest values pushed by the controller's streaming endpoints; `None` while a
+/// stream is down.
+#[derive(Debug, Clone, Default)]
+struct StreamedMetrics {
+    traffic: Option<TrafficData>,
+    memory: Option<MemoryData>,
+    connections: Option<ConnectionTotals>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ConnectionTotals {
+    active: usize,
+    download_total: u64,
+    upload_total: u64,
+}
+
+pub struct MetricsExporter {
+    client: MihomoClient,
+    reconnect: ReconnectPolicy,
+}
+
+impl MetricsExporter {
+    pub fn new(client: MihomoClient) -> Self {
+        Self {
+            client,
+            reconnect: ReconnectPolicy::default(),
+        }
+    }
+
+    /// Backoff used to open the streaming endpoints and to reopen them when they
+    /// drop. With a `max_attempts` limit, a stream that gives up is opened afresh
+    /// after the policy's longest delay.
+    pub fn with_reconnect_policy(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect = policy;
+        self
+    }
+
+    /// Serves `GET /metrics` on `listener` until `cancel` fires. Traffic, memory and
+    /// connection figures follow the controller's streaming endpoints; health and
+    /// proxy delays are read from the controller on each scrape.
+    pub async fn serve(self, listener: TcpListener, cancel: CancellationToken) -> Result<()> {
+        let state = Arc::new(Mutex::new(StreamedMetrics::default()));
+        self.spawn_followers(&state, &cancel);
+        log::info!("Serving metrics on {}", listener.local_addr()?);
+
+        loop {
+            let accepted = tokio::select! {
+                _ = cancel.cancelled() => return Ok(()),
+                accepted = listener.accept() => accepted,
+            };
+            let (stream, peer) = match accepted {
+                Ok(accepted) => accepted,
+                Err(err) => {
+                    log::warn!("Failed to accept metrics connection: {}", err);
+                    continue;
+                }
+            };
+            let client = self.client.clone();
+            let state = state.clone();
+            tokio::spawn(async move {
+                if let Err(err) = handle_request(stream, &client, &state).await {
+                    log::debug!("Metrics request from {} failed: {}", peer, err);
+                }
+            });
+        }
+    }
+
+    fn spawn_followers(&self, state: &Arc<Mutex<StreamedMetrics>>, cancel: &CancellationToken) {
+        let traffic = state.clone();
+        self.follow(
+            "traffic",
+            cancel,
+            |client, policy| async move { client.stream_traffic_reconnecting(policy).await },
+            move |item| lock(&traffic).traffic = item,
+        );
+        let memory = state.clone();
+        self.follow(
+            "memory",
+            cancel,
+            |client, policy| async move { client.stream_memory_reconnecting(policy).await },
+            move |item| lock(&memory).memory = item,
+        );
+        let connections = state.clone();
+        self.follow(
+            "connections",
+            cancel,
+            |client, policy| async move { client.stream_connections_reconnecting(policy).await },
+            move |item| {
+                lock(&connections).connections = item.map(|snapshot| ConnectionTotals {
+                    active: snapshot.connections.len(),
+                    download_total: snapshot.download_total,
+                    upload_total: snapshot.upload_total,
+                })
+            },
+        );
+    }
+
+    /// Keeps `open`'s reconnecting stream running until `cancel` fires, feeding each
+    /// item to `apply` and `None` whenever the stream is down. Reconnects are left to
+    /// the stream; this only retries opening it.
+    fn follow<T, F, Fut>(
+        &self,
+        name: &'static str,
+        cancel: &CancellationToken,
+        open: F,
+        mut apply: impl FnMut(Option<T>) + Send + 'static,
+    ) where
+        T: Send + 'static,
+        F: Fn(MihomoClient, ReconnectPolicy) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<UnboundedReceiver<StreamEvent<T>>>> + Send,
+    {
+        let client = self.client.clone();
+        let cancel = cancel.clone();
+        let policy = self.reconnect;
+
+        tokio::spawn(async move {
+            let mut attempt = 0;
+            loop {
+                let opened = tokio::select! {
+                    _ = cancel.cancelled() => return,
+                    opened = open(client.clone(), policy) => opened,
+                };
+                match opened {
+                    Ok(mut events) => {
+                        attempt = 0;
+                        loop {
+                            let event = tokio::select! {
+                                _ = cancel.cancelled() => return,
+                                event = events.recv() => event,
+                            };
+                            match event {
+                                Some(StreamEvent::Item(item)) => apply(Some(item)),
+                                Some(StreamEvent::Reconnecting { .. }) => apply(None),
+                                Some(StreamEvent::Reconnected) => {}
+                                None => break,
+                            }
+                        }
+                        log::debug!("Gave up reconnecting the {} stream", name);
+                    }
+                    Err(err) => log::debug!("Failed to open {} stream: {}", name, err),
+                }
+                apply(None);
+                attempt += 1;
+                let delay = if policy.exhausted(attempt) {
+                    policy.max_delay
+                } else {
+                    policy.delay_for(attempt)
+                };
+                tokio::select! {
+                    _ = cancel.cancelled() => return,
+                    _ = tokio::time::sleep(delay) => {}
+                }
+            }
+        });
+    }
+}
+
+/// Stream updates and scrapes only ever replace whole values, so a panic while the
+/// lock was held can't leave them half-written.
+fn lock(state: &Mutex<StreamedMetrics>) -> std::sync::MutexGuard<'_, StreamedMetrics> {
+    state.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+async fn handle_request(
+    mut stream: TcpStream,
+    client: &MihomoClient,
+    state: &Mutex<StreamedMetrics>,
+) -> std::io::Result<()> {
+    let head = tokio::time::timeout(REQUEST_READ_TIMEOUT, read_request_head(&mut stream))
+        .await
+        .map_err(|_| std::io::Error::new(std::io::ErrorKind::TimedOut, "request timed out"))??;
+    let mut request_line = head.lines().next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default();
+    let path = request_line.next().unwrap_or_default();
+
+    let (status, body) = if method == "GET" && path.split('?').next() == Some("/metrics") {
+        let (health, proxies) = tokio::join!(client.healthcheck(), client.get_proxies());
+        let streamed = lock(state).clone();
+        let body = render(&health, proxies.ok().as_ref(), &streamed);
+        ("200 OK", body)
+    } else {
+        ("404 Not Found", "Not Found\n".to_string())
+    };
+
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+async fn read_request_head(stream: &mut TcpStream) -> std::io::Result<String> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        if head.len() > MAX_REQUEST_HEAD_BYTES {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "request head too large",
+            ));
+        }
+        let read = stream.read(&mut buf).await?;
+        if read == 0 {
+            break;
+        }
+        head.extend_from_slice(&buf[..read]);
+    }
+    Ok(String::from_utf8_lossy(&head).into_owned())
+}
+
+fn render(
+    health: &HealthReport,
+    proxies: Option<&HashMap<String, ProxyInfo>>,
+    streamed: &StreamedMetrics,
+) -> String {
+    let mut out = String::new();
+
+    family(
+        &mut out,
+        "mihomo_up",
+        "gauge",
+        "Whether the controller answered.",
+    );
+    sample(&mut out, "mihomo_up", &[], u64::from(health.api_reachable));
+    if let Some(version) = &health.version {
+        family(
+            &mut out,
+            "mihomo_info",
+            "gauge",
+            "Version of the running core.",
+        );
+        sample(&mut out, "mihomo_info", &[("version", version)], 1);
+    }
+    if health.api_reachable {
+        family(
+            &mut out,
+            "mihomo_healthy",
+            "gauge",
+            "Whether the controller answered and every remote provider supplies proxies.",
+        );
+        sample(
+            &mut out,
+            "mihomo_healthy",
+            &[],
+            u64::from(health.is_healthy()),
+        );
+        family(
+            &mut out,
+            "mihomo_providers_failing",
+            "gauge",
+            "Remote providers that currently supply no proxies.",
+        );
+        sample(
+            &mut out,
+            "mihomo_providers_failing",
+            &[],
+            health.failing_providers.len() as u64,
+        );
+    }
+    if let Some(goroutines) = health.goroutines {
+        family(
+            &mut out,
+            "mihomo_goroutines",
+            "gauge",
+            "Goroutines in the core, when it exposes /debug/pprof.",
+        );
+        sample(&mut out, "mihomo_goroutines", &[], goroutines);
+    }
+
+    if let Some(traffic) = &streamed.traffic {
+        family(
+            &mut out,
+            "mihomo_upload_bytes_per_second",
+            "gauge",
+            "Current upload rate.",
+        );
+        sample(&mut out, "mihomo_upload_bytes_per_second", &[], traffic.up);
+        family(
+            &mut out,
+            "mihomo_download_bytes_per_second",
+            "gauge",
+            "Current download rate.",
+        );
+        sample(
+            &mut out,
+            "mihomo_download_bytes_per_second",
+            &[],
+            traffic.down,
+        );
+    }
+
+    if let Some(memory) = &streamed.memory {
+        family(
+            &mut out,
+            "mihomo_memory_inuse_bytes",
+            "gauge",
+            "Memory used by the core.",
+        );
+        sample(&mut out, "mihomo_memory_inuse_bytes", &[], memory.in_use);
+    }
+
+    if let Some(connections) = &streamed.connections {
+        family(
+            &mut out,
+            "mihomo_connections_active",
+            "gauge",
+            "Open connections.",
+        );
+        sample(
+            &mut out,
+            "mihomo_connections_active",
+            &[],
+            connections.active as u64,
+        );
+        family(
+            &mut out,
+            "mihomo_upload_bytes_total",
+            "counter",
+            "Bytes uploaded since the core started.",
+        );
+        sample(
+            &mut out,
+            "mihomo_upload_bytes_total",
+            &[],
+            connections.upload_total,
+        );
+        family(
+            &mut out,
+            "mihomo_download_bytes_total",
+            "counter",
+            "Bytes downloaded since the core started.",
+        );
+        sample(
+            &mut out,
+            "mihomo_download_bytes_total",
+            &[],
+            connections.download_total,
+        );
+    }
+
+    if let Some(proxies) = proxies {
+        let mut delays: Vec<(&String, &ProxyInfo, u32)> = proxies
+            .iter()
+            .filter(|(_, info)| info.all.is_none())
+            // History is oldest first; a delay of 0 is a failed test.
+            .filter_map(|(name, info)| Some((name, info, info.history.last()?.delay)))
+            .filter(|(_, _, delay)| *delay > 0)
+            .collect();
+        delays.sort_by(|a, b| a.0.cmp(b.0));
+        family(
+            &mut out,
+            "mihomo_proxy_delay_milliseconds",
+            "gauge",
+            "Last successful delay test of each proxy node.",
+        );
+        for (name, info, delay) in delays {
+            sample(
+                &mut out,
+                "mihomo_proxy_delay_milliseconds",
+                &[("proxy", name), ("type", &info.proxy_type)],
+                u64::from(delay),
+            );
+        }
+    }
+
+    out
+}
+
+fn family(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn sample(out: &mut String, name: &str, labels: &[(&str, &str)], value: u64) {
+    out.push_str(name);
+    if !labels.is_empty() {
+        let labels: Vec<String> = labels
+            .iter()
+            .map(|(key, value)| format!("{}=\"{}\"", key, escape_label(value)))
+            .collect();
+        let _ = write!(out, "{{{}}}", labels.join(","));
+    }
+    let _ = writeln!(out, " {}", value);
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::DelayHistory;
+
+    fn proxy(proxy_type: &str, all: Option<Vec<String>>, delays: &[u32]) -> ProxyInfo {
+        ProxyInfo {
+            proxy_type: proxy_type.to_string(),
+            now: None,
+            all,
+            history: delays
+                .iter()
+                .map(|delay| DelayHistory {
+                    time: String::new(),
+                    delay: *delay,
+                })
+                .collect(),
+            interface: None,
+            routing_mark: None,
+            dialer_proxy: None,
+        }
+    }
+
+    #[test]
+    fn render_exposes_streamed_values_and_latest_delays() {
+        let proxies = HashMap::from([
+            (
+                "HK \"01\"".to_string(),
+                proxy("Shadowsocks", None, &[300, 120]),
+            ),
+            ("JP".to_string(), proxy("Vmess", None, &[90, 0])),
+            ("Proxy".to_string(), proxy("Selector", Some(vec![]), &[50])),
+        ]);
+        let streamed = StreamedMetrics {
+            traffic: Some(TrafficData { up: 10, down: 20 }),
+            memory: None,
+            connections: Some(ConnectionTotals {
+                active: 3,
+                download_total: 2048,
+                upload_total: 1024,
+            }),
+        };
+
+        let health = HealthReport {
+            api_reachable: true,
+            version: Some("v1.19.2".to_string()),
+            failing_providers: vec!["remote".to_string()],
+            ..HealthReport::default()
+        };
+
+        let text = render(&health, Some(&proxies), &streamed);
+        assert!(text.contains("mihomo_up 1\n"));
+        assert!(text.contains("mihomo_healthy 0\n"));
+        assert!(text.contains("mihomo_providers_failing 1\n"));
+        assert!(!text.contains("mihomo_goroutines"));
+        assert!(text.contains("mihomo_info{version=\"v1.19.2\"} 1\n"));
+        assert!(text.contains("mihomo_download_bytes_per_second 20\n"));
+        assert!(text.contains("# TYPE mihomo_upload_bytes_total counter\n"));
+        assert!(text.contains("mihomo_connections_active 3\n"));
+        assert!(!text.contains("mihomo_memory_inuse_bytes"));
+        assert!(text.contains(
+            "mihomo_proxy_delay_milliseconds{proxy=\"HK \\\"01\\\"\",type=\"Shadowsocks\"} 120\n"
+        ));
+        assert!(!text.contains("proxy=\"JP\""));
+        assert!(!text.contains("proxy=\"Proxy\""));
+    }
+
+    #[test]
+    fn render_reports_unreachable_controller() {
+        let text = render(&HealthReport::default(), None, &StreamedMetrics::default());
+        assert_eq!(
+            text,
+            "# HELP mihomo_up Whether the controller answered.\n# TYPE mihomo_up gauge\nmihomo_up 0\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn serve_answers_metrics_and_stops_on_cancel() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let client = MihomoClient::new("http://127.0.0.1:1", None).unwrap();
+        let cancel = CancellationToken::new();
+        let server = tokio::spawn(MetricsExporter::new(client).serve(listener, cancel.clone()));
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+        assert!(response.ends_with("mihomo_up 0\n"));
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(b"GET / HTTP/1.1\r\n\r\n").await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+
+        cancel.cancel();
+        server.await.unwrap().unwrap();
+    }
+}
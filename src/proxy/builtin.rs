@@ -0,0 +1,76 @@
+use crate::core::MihomoError;
+use std::str::FromStr;
+
+pub const DIRECT: &str = "DIRECT";
+pub const REJECT: &str = "REJECT";
+pub const REJECT_DROP: &str = "REJECT-DROP";
+pub const PASS: &str = "PASS";
+pub const COMPATIBLE: &str = "COMPATIBLE";
+/// Selector mihomo creates over every proxy and group; it only routes traffic in `global` mode.
+pub const GLOBAL: &str = "GLOBAL";
+
+/// Outbounds that mihomo always defines, regardless of the loaded config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BuiltinProxy {
+    Direct,
+    Reject,
+    RejectDrop,
+    Pass,
+    Compatible,
+}
+
+impl BuiltinProxy {
+    pub const ALL: [BuiltinProxy; 5] = [
+        BuiltinProxy::Direct,
+        BuiltinProxy::Reject,
+        BuiltinProxy::RejectDrop,
+        BuiltinProxy::Pass,
+        BuiltinProxy::Compatible,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BuiltinProxy::Direct => DIRECT,
+            BuiltinProxy::Reject => REJECT,
+            BuiltinProxy::RejectDrop => REJECT_DROP,
+            BuiltinProxy::Pass => PASS,
+            BuiltinProxy::Compatible => COMPATIBLE,
+        }
+    }
+}
+
+impl FromStr for BuiltinProxy {
+    type Err = MihomoError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        BuiltinProxy::ALL
+            .into_iter()
+            .find(|builtin| builtin.as_str() == s)
+            .ok_or_else(|| MihomoError::Proxy(format!("Not a builtin proxy: {}", s)))
+    }
+}
+
+/// Builtin names are matched exactly; mihomo treats proxy names as case-sensitive.
+pub fn is_builtin_proxy(name: &str) -> bool {
+    name.parse::<BuiltinProxy>().is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_names_roundtrip() {
+        for builtin in BuiltinProxy::ALL {
+            assert_eq!(builtin.as_str().parse::<BuiltinProxy>().ok(), Some(builtin));
+        }
+        assert!(matches!(
+            "HK-01".parse::<BuiltinProxy>(),
+            Err(MihomoError::Proxy(msg)) if msg == "Not a builtin proxy: HK-01"
+        ));
+        assert!(is_builtin_proxy("DIRECT"));
+        assert!(is_builtin_proxy("REJECT-DROP"));
+        assert!(!is_builtin_proxy("direct"));
+        assert!(!is_builtin_proxy("HK-01"));
+    }
+}
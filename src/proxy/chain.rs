@@ -0,0 +1,84 @@
+use crate::core::{MihomoError, ProxyInfo, Result};
+use std::collections::HashMap;
+
+/// Follows `name` to the proxies its traffic actually goes through: groups resolve to
+/// their current pick and nodes to their `dialer-proxy`. The chain starts with `name`
+/// and each entry dials out through the next, so the last entry is the first hop.
+pub(crate) fn resolve(proxies: &HashMap<String, ProxyInfo>, name: &str) -> Result<Vec<String>> {
+    let mut chain: Vec<String> = vec![];
+    let mut current = name.to_string();
+
+    loop {
+        if chain.contains(&current) {
+            chain.push(current);
+            return Err(MihomoError::config(format!(
+                "Proxy chain loops: {}",
+                chain.join(" -> ")
+            )));
+        }
+        let info = proxies
+            .get(&current)
+            .ok_or_else(|| MihomoError::NotFound(format!("Proxy '{}'", current)))?;
+        let next = match info.now.as_deref() {
+            Some(now) if !now.is_empty() => Some(now),
+            _ => info.dialer(),
+        }
+        .map(str::to_string);
+        chain.push(current);
+        match next {
+            Some(next) => current = next,
+            None => return Ok(chain),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(proxy_type: &str, now: Option<&str>, dialer: Option<&str>) -> ProxyInfo {
+        ProxyInfo {
+            proxy_type: proxy_type.to_string(),
+            now: now.map(str::to_string),
+            all: None,
+            history: vec![],
+            interface: None,
+            routing_mark: None,
+            dialer_proxy: dialer.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn follows_group_picks_and_dialer_proxies() {
+        let proxies = HashMap::from([
+            ("Proxy".to_string(), info("Selector", Some("exit"), None)),
+            ("exit".to_string(), info("Vmess", None, Some("Relays"))),
+            ("Relays".to_string(), info("URLTest", Some("hk"), None)),
+            ("hk".to_string(), info("Shadowsocks", None, Some(""))),
+        ]);
+        assert_eq!(
+            resolve(&proxies, "Proxy").unwrap(),
+            vec!["Proxy", "exit", "Relays", "hk"]
+        );
+        assert_eq!(resolve(&proxies, "hk").unwrap(), vec!["hk"]);
+        assert!(matches!(
+            resolve(&proxies, "missing"),
+            Err(MihomoError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn reports_loops_and_dangling_references() {
+        let proxies = HashMap::from([
+            ("a".to_string(), info("Vmess", None, Some("b"))),
+            ("b".to_string(), info("Trojan", None, Some("a"))),
+            ("c".to_string(), info("Trojan", None, Some("gone"))),
+        ]);
+        let err = resolve(&proxies, "a").unwrap_err();
+        assert!(err.to_string().contains("a -> b -> a"), "{}", err);
+        assert!(matches!(
+            resolve(&proxies, "c"),
+            Err(MihomoError::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,76 @@
+use serde_yaml::Value;
+use std::collections::HashSet;
+
+/// Renames proxies whose `name` repeats an earlier entry by appending ` #2`, ` #3`,
+/// ... (skipping suffixes already taken), so every name is unique. The first
+/// occurrence keeps its name.
+///
+/// `proxies` are the entries of a profile's `proxies:` list. Returns `(old, new)`
+/// for each rename, in list order.
+pub fn dedup_proxy_names(proxies: &mut [Value]) -> Vec<(String, String)> {
+    let mut taken: HashSet<String> = proxies
+        .iter()
+        .filter_map(|proxy| proxy.get("name")?.as_str().map(str::to_string))
+        .collect();
+    let mut seen = HashSet::new();
+    let mut renames = vec![];
+
+    for proxy in proxies.iter_mut() {
+        let Some(name) = proxy
+            .get("name")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+        else {
+            continue;
+        };
+        if seen.insert(name.clone()) {
+            continue;
+        }
+
+        let renamed = (2..)
+            .map(|n| format!("{} #{}", name, n))
+            .find(|candidate| !taken.contains(candidate))
+            .expect("unbounded suffix search");
+        taken.insert(renamed.clone());
+        seen.insert(renamed.clone());
+        proxy["name"] = Value::from(renamed.clone());
+        renames.push((name, renamed));
+    }
+
+    renames
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(proxies: &[Value]) -> Vec<&str> {
+        proxies
+            .iter()
+            .map(|proxy| proxy["name"].as_str().unwrap_or("-"))
+            .collect()
+    }
+
+    #[test]
+    fn renames_collisions_deterministically() {
+        let mut proxies: Vec<Value> = serde_yaml::from_str(
+            "[{name: HK}, {name: HK}, {name: 'HK #2'}, {name: HK}, {server: x}, {name: JP}]",
+        )
+        .unwrap();
+
+        let renames = dedup_proxy_names(&mut proxies);
+
+        assert_eq!(
+            names(&proxies),
+            vec!["HK", "HK #3", "HK #2", "HK #4", "-", "JP"]
+        );
+        assert_eq!(
+            renames,
+            vec![
+                ("HK".to_string(), "HK #3".to_string()),
+                ("HK".to_string(), "HK #4".to_string()),
+            ]
+        );
+        assert!(dedup_proxy_names(&mut proxies).is_empty());
+    }
+}
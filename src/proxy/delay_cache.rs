@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Successful delay measurements keyed by proxy and test URL, kept for a fixed TTL.
+#[derive(Debug)]
+pub(crate) struct DelayCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<(String, String), (Instant, u32)>>,
+}
+
+impl DelayCache {
+    pub(crate) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn get(&self, proxy: &str, test_url: &str) -> Option<u32> {
+        self.get_at(proxy, test_url, Instant::now())
+    }
+
+    fn get_at(&self, proxy: &str, test_url: &str, now: Instant) -> Option<u32> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let key = (proxy.to_string(), test_url.to_string());
+        match entries.get(&key) {
+            Some((measured_at, delay))
+                if now.saturating_duration_since(*measured_at) < self.ttl =>
+            {
+                Some(*delay)
+            }
+            Some(_) => {
+                entries.remove(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub(crate) fn insert(&self, proxy: &str, test_url: &str, delay: u32) {
+        self.insert_at(proxy, test_url, delay, Instant::now());
+    }
+
+    fn insert_at(&self, proxy: &str, test_url: &str, delay: u32, now: Instant) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries
+            .retain(|_, (measured_at, _)| now.saturating_duration_since(*measured_at) < self.ttl);
+        entries.insert((proxy.to_string(), test_url.to_string()), (now, delay));
+    }
+
+    pub(crate) fn clear(&self) {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entries_expire_after_ttl() {
+        let cache = DelayCache::new(Duration::from_secs(10));
+        let start = Instant::now();
+        cache.insert_at("HK-01", "http://a", 120, start);
+
+        assert_eq!(
+            cache.get_at("HK-01", "http://a", start + Duration::from_secs(9)),
+            Some(120)
+        );
+        assert_eq!(cache.get_at("HK-01", "http://b", start), None);
+        assert_eq!(
+            cache.get_at("HK-01", "http://a", start + Duration::from_secs(10)),
+            None
+        );
+    }
+}
@@ -0,0 +1,226 @@
+use super::scheduler::{DelayScheduler, DEFAULT_TEST_TIMEOUT_MS, DEFAULT_TEST_URL};
+use super::switch_lock::SwitchLocks;
+use crate::core::MihomoClient;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+/// Settings for [`ProxyManager::maintain_fastest`](super::ProxyManager::maintain_fastest).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaintainOptions {
+    pub interval: Duration,
+    pub test_url: String,
+    /// Delay-test timeout in milliseconds.
+    pub timeout: u32,
+    /// A live current pick is kept unless another member is faster by at least this
+    /// many milliseconds.
+    pub tolerance_ms: u32,
+    /// HTTP statuses that count as a pass, in mihomo's `expected` syntax. `None`
+    /// falls back to the manager's
+    /// [`with_expected_status`](super::ProxyManager::with_expected_status).
+    pub expected: Option<String>,
+}
+
+impl Default for MaintainOptions {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(300),
+            test_url: DEFAULT_TEST_URL.to_string(),
+            timeout: DEFAULT_TEST_TIMEOUT_MS,
+            tolerance_ms: 100,
+            expected: None,
+        }
+    }
+}
+
+impl MaintainOptions {
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn with_test(mut self, url: impl Into<String>, timeout: u32) -> Self {
+        self.test_url = url.into();
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_tolerance(mut self, tolerance_ms: u32) -> Self {
+        self.tolerance_ms = tolerance_ms;
+        self
+    }
+
+    pub fn with_expected(mut self, expected: impl Into<String>) -> Self {
+        self.expected = Some(expected.into());
+        self
+    }
+}
+
+/// What one maintenance round did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MaintainEvent {
+    /// The current pick is alive and within tolerance of the fastest member.
+    Held { proxy: String, delay: u32 },
+    /// `from_delay` is `None` when the previous pick failed its test.
+    Switched {
+        from: String,
+        to: String,
+        from_delay: Option<u32>,
+        to_delay: u32,
+    },
+    /// No member answered the delay test; the selection was left alone.
+    NoHealthyProxy,
+    /// Testing or switching failed; the next round retries.
+    Error(String),
+}
+
+/// Decides the action for one round from the group's current pick and the delays of
+/// the members that answered. Ties between equally fast members go to the first name.
+pub(crate) fn decide(
+    current: &str,
+    delays: &HashMap<String, u32>,
+    tolerance_ms: u32,
+) -> MaintainEvent {
+    let Some((best, best_delay)) = delays
+        .iter()
+        .min_by(|a, b| a.1.cmp(b.1).then_with(|| a.0.cmp(b.0)))
+    else {
+        return MaintainEvent::NoHealthyProxy;
+    };
+
+    match delays.get(current) {
+        Some(&delay) if best == current || delay < best_delay.saturating_add(tolerance_ms) => {
+            MaintainEvent::Held {
+                proxy: current.to_string(),
+                delay,
+            }
+        }
+        from_delay => MaintainEvent::Switched {
+            from: current.to_string(),
+            to: best.clone(),
+            from_delay: from_delay.copied(),
+            to_delay: *best_delay,
+        },
+    }
+}
+
+pub(crate) fn spawn(
+    client: MihomoClient,
+    locks: Arc<SwitchLocks>,
+    group: String,
+    options: MaintainOptions,
+) -> (DelayScheduler, mpsc::UnboundedReceiver<MaintainEvent>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let cancel = CancellationToken::new();
+    let token = cancel.clone();
+    let interval = options.interval.max(Duration::from_millis(1));
+
+    let task = tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                biased;
+                _ = token.cancelled() => break,
+                _ = ticker.tick() => {}
+            }
+            let event = tokio::select! {
+                biased;
+                _ = token.cancelled() => break,
+                event = run_round(&client, &locks, &group, &options) => event,
+            };
+            if tx.send(event).is_err() {
+                // Nobody is listening any more; keep maintaining silently.
+                log::trace!("Maintenance event for '{}' dropped", group);
+            }
+        }
+        log::debug!("Maintenance of '{}' stopped", group);
+    });
+
+    (DelayScheduler::from_parts(cancel, task), rx)
+}
+
+async fn run_round(
+    client: &MihomoClient,
+    locks: &SwitchLocks,
+    group: &str,
+    options: &MaintainOptions,
+) -> MaintainEvent {
+    let current = match client.get_proxy(group).await {
+        Ok(info) => info.now.unwrap_or_default(),
+        Err(err) => return MaintainEvent::Error(err.to_string()),
+    };
+    let delays = match client
+        .group_delay(
+            group,
+            &options.test_url,
+            options.timeout,
+            options.expected.as_deref(),
+        )
+        .await
+    {
+        Ok(delays) => delays,
+        Err(err) => return MaintainEvent::Error(err.to_string()),
+    };
+
+    let event = decide(&current, &delays, options.tolerance_ms);
+    if let MaintainEvent::Switched { to, .. } = &event {
+        let _guard = locks.lock(group).await;
+        if let Err(err) = client.switch_proxy(group, to).await {
+            return MaintainEvent::Error(err.to_string());
+        }
+        log::info!("Switched '{}' from '{}' to '{}'", group, current, to);
+    }
+    event
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delays(entries: &[(&str, u32)]) -> HashMap<String, u32> {
+        entries
+            .iter()
+            .map(|(name, delay)| (name.to_string(), *delay))
+            .collect()
+    }
+
+    #[test]
+    fn holds_within_tolerance_and_switches_past_it() {
+        assert_eq!(
+            decide("HK", &delays(&[("HK", 150), ("JP", 80)]), 100),
+            MaintainEvent::Held {
+                proxy: "HK".into(),
+                delay: 150
+            }
+        );
+        assert_eq!(
+            decide("HK", &delays(&[("HK", 200), ("JP", 80), ("SG", 80)]), 100),
+            MaintainEvent::Switched {
+                from: "HK".into(),
+                to: "JP".into(),
+                from_delay: Some(200),
+                to_delay: 80
+            }
+        );
+    }
+
+    #[test]
+    fn switches_away_from_dead_pick_and_reports_total_outage() {
+        assert_eq!(
+            decide("HK", &delays(&[("JP", 900)]), 100),
+            MaintainEvent::Switched {
+                from: "HK".into(),
+                to: "JP".into(),
+                from_delay: None,
+                to_delay: 900
+            }
+        );
+        assert_eq!(
+            decide("HK", &HashMap::new(), 100),
+            MaintainEvent::NoHealthyProxy
+        );
+    }
+}
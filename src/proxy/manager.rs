@@ -1,13 +1,104 @@
-use crate::core::{MihomoClient, ProxyGroup, ProxyInfo, ProxyNode, Result};
+use super::builtin::{is_builtin_proxy, BuiltinProxy, GLOBAL};
+use super::chain;
+use super::delay_cache::DelayCache;
+use super::maintain::{self, MaintainEvent, MaintainOptions};
+use super::scheduler::{DelayScheduler, DEFAULT_TEST_TIMEOUT_MS, DEFAULT_TEST_URL};
+use super::switch_lock::SwitchLocks;
+use super::test::{self, GroupTestOptions};
+use crate::core::{
+    fan_out, DelayOutcome, GroupChoices, MihomoClient, MihomoError, ProxyGroup, ProxyInfo,
+    ProxyNode, Result, SubscriptionAlert,
+};
+use futures_util::StreamExt;
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+const PROVIDER_UPDATE_CONCURRENCY: usize = 4;
+const CONNECTION_CLOSE_CONCURRENCY: usize = 8;
+const FIRST_HEALTHY_LOOKAHEAD: usize = 4;
 
 pub struct ProxyManager {
     client: MihomoClient,
+    test_url: String,
+    test_timeout: u32,
+    expected_status: Option<String>,
+    delay_cache: Option<DelayCache>,
+    switch_locks: Arc<SwitchLocks>,
 }
 
 impl ProxyManager {
     pub fn new(client: MihomoClient) -> Self {
-        Self { client }
+        Self {
+            client,
+            test_url: DEFAULT_TEST_URL.to_string(),
+            test_timeout: DEFAULT_TEST_TIMEOUT_MS,
+            expected_status: None,
+            delay_cache: None,
+            switch_locks: Arc::default(),
+        }
+    }
+
+    /// Overrides the delay-test URL and timeout (ms) used when callers don't pass one.
+    pub fn with_default_test(mut self, url: impl Into<String>, timeout: u32) -> Self {
+        self.test_url = url.into();
+        self.test_timeout = timeout;
+        self
+    }
+
+    /// Makes the manager's delay tests pass on these HTTP statuses instead of
+    /// mihomo's default 204, for test URLs that answer 200. This covers
+    /// [`Self::test_delay`], group tests, the delay scheduler and maintenance rounds
+    /// that don't set their own. See [`MihomoClient::test_delay_expecting`] for the
+    /// syntax.
+    pub fn with_expected_status(mut self, expected: impl Into<String>) -> Self {
+        self.expected_status = Some(expected.into());
+        self
+    }
+
+    /// Reuses successful [`Self::test_delay`] results for the same proxy and URL for `ttl`.
+    pub fn with_delay_cache(mut self, ttl: Duration) -> Self {
+        self.delay_cache = Some(DelayCache::new(ttl));
+        self
+    }
+
+    /// Forgets all cached delay results.
+    pub fn clear_delay_cache(&self) {
+        if let Some(cache) = &self.delay_cache {
+            cache.clear();
+        }
+    }
+
+    /// Delay-tests a single proxy, falling back to the manager's default URL and timeout.
+    pub async fn test_delay(
+        &self,
+        proxy: &str,
+        test_url: Option<&str>,
+        timeout: Option<u32>,
+    ) -> Result<u32> {
+        let test_url = test_url.unwrap_or(&self.test_url);
+        if let Some(delay) = self
+            .delay_cache
+            .as_ref()
+            .and_then(|cache| cache.get(proxy, test_url))
+        {
+            return Ok(delay);
+        }
+
+        let timeout = timeout.unwrap_or(self.test_timeout);
+        let delay = match &self.expected_status {
+            Some(expected) => {
+                self.client
+                    .test_delay_expecting(proxy, test_url, timeout, expected)
+                    .await?
+            }
+            None => self.client.test_delay(proxy, test_url, timeout).await?,
+        };
+        if let Some(cache) = &self.delay_cache {
+            cache.insert(proxy, test_url, delay);
+        }
+        Ok(delay)
     }
 
     pub async fn list_proxies(&self) -> Result<Vec<ProxyNode>> {
@@ -20,6 +111,9 @@ impl ProxyManager {
             if !is_group {
                 let delay = info.history.first().map(|h| h.delay);
                 nodes.push(ProxyNode {
+                    interface: info.bound_interface().map(str::to_string),
+                    routing_mark: info.bound_routing_mark(),
+                    dialer_proxy: info.dialer().map(str::to_string),
                     name,
                     proxy_type: info.proxy_type,
                     delay,
@@ -54,10 +148,396 @@ impl ProxyManager {
         Ok(groups)
     }
 
+    /// Nodes supplied by a single proxy provider, without fetching the global proxy map.
+    pub async fn get_provider_proxies(&self, name: &str) -> Result<Vec<ProxyNode>> {
+        let provider = self.client.get_provider(name).await?;
+        let mut nodes: Vec<ProxyNode> = provider
+            .proxies
+            .into_iter()
+            .map(|proxy| {
+                let delay = proxy.history.first().map(|h| h.delay);
+                ProxyNode {
+                    interface: proxy.bound_interface().map(str::to_string),
+                    routing_mark: proxy.bound_routing_mark(),
+                    dialer_proxy: None,
+                    name: proxy.name,
+                    proxy_type: proxy.proxy_type,
+                    delay,
+                    alive: delay.is_some(),
+                }
+            })
+            .collect();
+
+        nodes.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(nodes)
+    }
+
+    /// Refreshes every remote proxy provider and returns the updated names.
+    ///
+    /// Providers defined inline in the config ("Compatible") cannot be refreshed
+    /// and are skipped. If any update fails, all outcomes are reported through
+    /// [`MihomoError::PartialFailure`].
+    pub async fn update_all_providers(&self) -> Result<Vec<String>> {
+        let mut names: Vec<String> = self
+            .client
+            .get_providers()
+            .await?
+            .into_values()
+            .filter(|provider| provider.vehicle_type != "Compatible")
+            .map(|provider| provider.name)
+            .collect();
+        names.sort();
+
+        let mut succeeded = vec![];
+        let mut failed = vec![];
+        let results = fan_out(names, PROVIDER_UPDATE_CONCURRENCY, |name| async move {
+            self.client.update_provider(&name).await
+        })
+        .await;
+        for (name, result) in results {
+            match result {
+                Ok(()) => succeeded.push(name),
+                Err(err) => failed.push((name, err.to_string())),
+            }
+        }
+
+        if failed.is_empty() {
+            Ok(succeeded)
+        } else {
+            Err(MihomoError::PartialFailure { succeeded, failed })
+        }
+    }
+
+    /// Providers whose subscription expires within `expiry_days` or has used at least
+    /// `usage_threshold` (0.0–1.0) of its quota. Providers without subscription info are ignored.
+    pub async fn subscription_alerts(
+        &self,
+        expiry_days: i64,
+        usage_threshold: f64,
+    ) -> Result<Vec<SubscriptionAlert>> {
+        let mut providers: Vec<_> = self.client.get_providers().await?.into_values().collect();
+        providers.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut alerts = vec![];
+        for provider in providers {
+            let Some(info) = provider.subscription_info else {
+                continue;
+            };
+            match info.days_until_expiry() {
+                Some(days) if days < 0 => alerts.push(SubscriptionAlert::Expired {
+                    provider: provider.name.clone(),
+                }),
+                Some(days) if days <= expiry_days => alerts.push(SubscriptionAlert::ExpiringSoon {
+                    provider: provider.name.clone(),
+                    days,
+                }),
+                _ => {}
+            }
+            if let Some(usage_ratio) = info.usage_ratio().filter(|ratio| *ratio >= usage_threshold)
+            {
+                alerts.push(SubscriptionAlert::QuotaNearlyUsed {
+                    provider: provider.name,
+                    usage_ratio,
+                });
+            }
+        }
+        Ok(alerts)
+    }
+
+    /// Selector groups with their members and cached delays, from a single `/proxies` call.
+    pub async fn selectable_groups(&self) -> Result<Vec<GroupChoices>> {
+        let proxies = self.client.get_proxies().await?;
+        let mut choices = vec![];
+
+        for (name, info) in &proxies {
+            if info.proxy_type != "Selector" {
+                continue;
+            }
+
+            let mut members: Vec<ProxyNode> = info
+                .all
+                .iter()
+                .flatten()
+                .map(|member| {
+                    let member_info = proxies.get(member);
+                    let delay = member_info.and_then(|m| m.history.first()).map(|h| h.delay);
+                    ProxyNode {
+                        name: member.clone(),
+                        proxy_type: member_info
+                            .map(|m| m.proxy_type.clone())
+                            .unwrap_or_default(),
+                        delay,
+                        alive: delay.is_some(),
+                        interface: member_info
+                            .and_then(|m| m.bound_interface())
+                            .map(str::to_string),
+                        routing_mark: member_info.and_then(|m| m.bound_routing_mark()),
+                        dialer_proxy: member_info.and_then(|m| m.dialer()).map(str::to_string),
+                    }
+                })
+                .collect();
+            members.sort_by(|a, b| {
+                (a.delay.is_none(), a.delay, &a.name).cmp(&(b.delay.is_none(), b.delay, &b.name))
+            });
+
+            choices.push(GroupChoices {
+                name: name.clone(),
+                now: info.now.clone().unwrap_or_default(),
+                members,
+            });
+        }
+
+        choices.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(choices)
+    }
+
+    /// Overlapping switches of the same group through this manager are applied one
+    /// at a time in call order, so the last call to start is the one left in effect.
     pub async fn switch(&self, group: &str, proxy: &str) -> Result<()> {
+        let _guard = self.switch_locks.lock(group).await;
         self.client.switch_proxy(group, proxy).await
     }
 
+    /// Like [`Self::switch`], but first checks that `group` accepts a manual choice and
+    /// that `proxy` is one of its members. Selector groups switch normally; URLTest
+    /// and Fallback groups pin `proxy` until mihomo's next health check overrides it.
+    /// LoadBalance and Relay groups are rejected, as are builtin outbounds such as
+    /// `DIRECT`, which are never groups.
+    pub async fn switch_checked(&self, group: &str, proxy: &str) -> Result<()> {
+        if let Ok(builtin) = group.parse::<BuiltinProxy>() {
+            return Err(MihomoError::Proxy(format!(
+                "'{}' is a builtin proxy, not a group",
+                builtin.as_str()
+            )));
+        }
+        let info = self.client.get_proxy(group).await?;
+        match info.proxy_type.as_str() {
+            "Selector" | "URLTest" | "Fallback" => {}
+            "LoadBalance" | "Relay" => {
+                return Err(MihomoError::Proxy(format!(
+                    "Cannot manually select in a {} group ('{}')",
+                    info.proxy_type, group
+                )));
+            }
+            other => {
+                return Err(MihomoError::Proxy(format!(
+                    "'{}' is not a proxy group (type {})",
+                    group, other
+                )));
+            }
+        }
+        if !info
+            .all
+            .unwrap_or_default()
+            .iter()
+            .any(|name| name == proxy)
+        {
+            if is_builtin_proxy(proxy) {
+                return Err(MihomoError::Proxy(format!(
+                    "Builtin proxy '{}' is not listed in group '{}'; add it to the group's proxies to select it",
+                    proxy, group
+                )));
+            }
+            return Err(MihomoError::Proxy(format!(
+                "'{}' is not a member of group '{}'",
+                proxy, group
+            )));
+        }
+
+        self.switch(group, proxy).await
+    }
+
+    /// Switches `group` to `proxy`, then closes every connection routed through `group`
+    /// so it re-establishes via the new node. Returns the number of connections closed.
+    /// If any close fails, the outcome per connection ID is reported through
+    /// [`MihomoError::PartialFailure`].
+    pub async fn switch_and_reset(&self, group: &str, proxy: &str) -> Result<usize> {
+        self.switch(group, proxy).await?;
+
+        let ids: Vec<String> = self
+            .client
+            .get_connections()
+            .await?
+            .connections
+            .into_iter()
+            .filter(|conn| conn.chains.iter().any(|name| name == group))
+            .map(|conn| conn.id)
+            .collect();
+
+        let mut succeeded = vec![];
+        let mut failed = vec![];
+        let results = fan_out(ids, CONNECTION_CLOSE_CONCURRENCY, |id| async move {
+            self.client.close_connection(&id).await
+        })
+        .await;
+        for (id, result) in results {
+            match result {
+                Ok(()) => succeeded.push(id),
+                Err(err) => failed.push((id, err.to_string())),
+            }
+        }
+
+        if !failed.is_empty() {
+            return Err(MihomoError::PartialFailure { succeeded, failed });
+        }
+        log::debug!(
+            "Closed {} connections through '{}' after switching to '{}'",
+            succeeded.len(),
+            group,
+            proxy
+        );
+        Ok(succeeded.len())
+    }
+
+    /// Switches every selector group that contains `proxy` to it, reporting each group's outcome.
+    pub async fn switch_in_all_groups(&self, proxy: &str) -> Result<Vec<(String, Result<()>)>> {
+        let groups = self.list_groups().await?;
+        let mut results = vec![];
+
+        for group in groups {
+            if group.group_type != "Selector" || !group.all.iter().any(|name| name == proxy) {
+                continue;
+            }
+            let result = self.switch(&group.name, proxy).await;
+            results.push((group.name, result));
+        }
+
+        Ok(results)
+    }
+
+    /// [`GroupTestOptions`] seeded with the manager's default test URL, timeout and
+    /// expected status, for [`Self::test_all_groups`].
+    pub fn group_test_options(&self) -> GroupTestOptions {
+        GroupTestOptions {
+            test_url: self.test_url.clone(),
+            timeout: self.test_timeout,
+            expected: self.expected_status.clone(),
+            ..GroupTestOptions::default()
+        }
+    }
+
+    /// Delay-tests every `Selector` and `URLTest` group's members, at most
+    /// `options.concurrency` at a time, and reports each member's outcome per group.
+    /// Start from [`Self::group_test_options`] to use the manager's defaults. Members
+    /// are answered from the delay cache when one is enabled.
+    pub async fn test_all_groups(
+        &self,
+        options: &GroupTestOptions,
+    ) -> Result<HashMap<String, Vec<(String, DelayOutcome)>>> {
+        let cache = self.delay_cache.as_ref();
+        if options.expected.is_some() || self.expected_status.is_none() {
+            return test::test_all_groups_cached(&self.client, options, cache).await;
+        }
+        let options = GroupTestOptions {
+            expected: self.expected_status.clone(),
+            ..options.clone()
+        };
+        test::test_all_groups_cached(&self.client, &options, cache).await
+    }
+
+    /// Delay-tests `groups` every `interval` in the background so mihomo's latency
+    /// history stays fresh, using the manager's default test URL, timeout and
+    /// expected status.
+    /// The first round runs immediately.
+    pub fn start_delay_scheduler(&self, groups: Vec<String>, interval: Duration) -> DelayScheduler {
+        self.start_delay_scheduler_with(groups, interval, &self.test_url, self.test_timeout)
+    }
+
+    pub fn start_delay_scheduler_with(
+        &self,
+        groups: Vec<String>,
+        interval: Duration,
+        test_url: &str,
+        timeout: u32,
+    ) -> DelayScheduler {
+        DelayScheduler::spawn(
+            self.client.clone(),
+            groups,
+            interval,
+            test_url.to_string(),
+            timeout,
+            self.expected_status.clone(),
+        )
+    }
+
+    /// [`MaintainOptions`] seeded with the manager's default test URL, timeout and
+    /// expected status, for [`Self::maintain_fastest`].
+    pub fn maintain_options(&self) -> MaintainOptions {
+        MaintainOptions {
+            test_url: self.test_url.clone(),
+            timeout: self.test_timeout,
+            expected: self.expected_status.clone(),
+            ..MaintainOptions::default()
+        }
+    }
+
+    /// Keeps `group` on its fastest member in the background. Every
+    /// `options.interval` the group is delay-tested, and the selection only moves
+    /// when the current pick fails or another member beats it by
+    /// `options.tolerance_ms`. Each round reports what it did on the returned channel.
+    /// Start from [`Self::maintain_options`] to test with the manager's defaults.
+    pub fn maintain_fastest(
+        &self,
+        group: &str,
+        mut options: MaintainOptions,
+    ) -> (DelayScheduler, mpsc::UnboundedReceiver<MaintainEvent>) {
+        if options.expected.is_none() {
+            options.expected = self.expected_status.clone();
+        }
+        maintain::spawn(
+            self.client.clone(),
+            self.switch_locks.clone(),
+            group.to_string(),
+            options,
+        )
+    }
+
+    /// First member of `group`, in configured order, that answers a delay test. This
+    /// mirrors Fallback selection. Up to four members are tested ahead at a time, and
+    /// the remaining tests are dropped once an answer is known.
+    pub async fn first_healthy(&self, group: &str) -> Result<Option<String>> {
+        let members = self.client.get_proxy(group).await?.all.unwrap_or_default();
+        let mut results = futures_util::stream::iter(members)
+            .map(|member| async move {
+                let healthy = self.test_delay(&member, None, None).await.is_ok();
+                (member, healthy)
+            })
+            .buffered(FIRST_HEALTHY_LOOKAHEAD);
+
+        while let Some((member, healthy)) = results.next().await {
+            if healthy {
+                return Ok(Some(member));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The builtin `GLOBAL` selector, which routes all traffic when mihomo runs in `global` mode.
+    pub async fn global_group(&self) -> Result<ProxyGroup> {
+        let info = self.client.get_proxy(GLOBAL).await?;
+        Ok(ProxyGroup {
+            name: GLOBAL.to_string(),
+            group_type: info.proxy_type,
+            now: info.now.unwrap_or_default(),
+            all: info.all.unwrap_or_default(),
+        })
+    }
+
+    /// Selects `proxy` in `GLOBAL`. This has no effect on routing unless mihomo is in
+    /// `global` mode.
+    pub async fn set_global(&self, proxy: &str) -> Result<()> {
+        self.switch(GLOBAL, proxy).await
+    }
+
+    /// Emergency fail-open: switches the core to `direct` mode, so new traffic
+    /// bypasses every proxy, then closes all connections so existing ones reconnect
+    /// directly. Connections are left alone if the mode change fails.
+    pub async fn panic_direct(&self) -> Result<()> {
+        log::warn!("Switching to direct mode and closing all connections");
+        self.client.set_mode("direct").await?;
+        self.client.close_all_connections().await
+    }
+
     pub async fn get_current(&self, group: &str) -> Result<String> {
         let info = self.client.get_proxy(group).await?;
         Ok(info.now.unwrap_or_default())
@@ -67,6 +547,51 @@ impl ProxyManager {
         self.client.get_proxies().await
     }
 
+    /// The outbound path for `name`, following group selections and `dialer-proxy`
+    /// references. The chain starts with `name`; the last entry is the first hop
+    /// traffic leaves through.
+    pub async fn resolve_dial_chain(&self, name: &str) -> Result<Vec<String>> {
+        let proxies = self.client.get_proxies().await?;
+        chain::resolve(&proxies, name)
+    }
+
+    /// Returns groups in which no member answered a delay test.
+    ///
+    /// Each group is tested once through the controller's group delay endpoint,
+    /// so the number of in-flight requests stays bounded by the group count. The
+    /// manager's expected status applies.
+    pub async fn unhealthy_groups(&self, test_url: &str, timeout: u32) -> Result<Vec<String>> {
+        let groups = self.list_groups().await?;
+        let mut unhealthy = vec![];
+
+        for group in groups {
+            if group.all.is_empty() {
+                continue;
+            }
+
+            match self
+                .client
+                .group_delay(
+                    &group.name,
+                    test_url,
+                    timeout,
+                    self.expected_status.as_deref(),
+                )
+                .await
+            {
+                Ok(delays) if delays.values().any(|delay| *delay > 0) => {}
+                Ok(_) => unhealthy.push(group.name),
+                Err(err) if matches!(err.http_status(), Some(408 | 503 | 504)) => {
+                    log::debug!("Group '{}' failed delay test: {}", group.name, err);
+                    unhealthy.push(group.name);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(unhealthy)
+    }
+
     fn is_group_type(proxy_type: &str) -> bool {
         matches!(
             proxy_type,
@@ -1,5 +1,19 @@
+pub mod builtin;
+mod chain;
+mod dedup;
+mod delay_cache;
+pub mod maintain;
 pub mod manager;
+pub mod scheduler;
+mod switch_lock;
 pub mod test;
 
+pub use builtin::{is_builtin_proxy, BuiltinProxy};
+pub use dedup::dedup_proxy_names;
+pub use maintain::{MaintainEvent, MaintainOptions};
 pub use manager::ProxyManager;
-pub use test::{test_all_delays, test_delay};
+pub use scheduler::DelayScheduler;
+pub use test::{
+    test_all_delay_outcomes, test_all_delays, test_all_delays_with_cancel, test_all_groups,
+    test_delay, GroupTestOptions,
+};
@@ -0,0 +1,124 @@
+use crate::core::MihomoClient;
+use futures_util::StreamExt;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+pub const DEFAULT_TEST_URL: &str = "http://www.gstatic.com/generate_204";
+pub const DEFAULT_TEST_TIMEOUT_MS: u32 = 5000;
+const MAX_CONCURRENT_GROUP_TESTS: usize = 4;
+
+/// Handle to a background task that periodically delay-tests proxy groups.
+///
+/// The task stops when [`DelayScheduler::stop`] is called or the handle is dropped.
+#[derive(Debug)]
+pub struct DelayScheduler {
+    cancel: CancellationToken,
+    task: Option<JoinHandle<()>>,
+}
+
+impl DelayScheduler {
+    pub(crate) fn spawn(
+        client: MihomoClient,
+        groups: Vec<String>,
+        interval: Duration,
+        test_url: String,
+        timeout: u32,
+        expected: Option<String>,
+    ) -> Self {
+        let cancel = CancellationToken::new();
+        let token = cancel.clone();
+        let interval = interval.max(Duration::from_millis(1));
+
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                tokio::select! {
+                    biased;
+                    _ = token.cancelled() => break,
+                    _ = ticker.tick() => {}
+                }
+                tokio::select! {
+                    biased;
+                    _ = token.cancelled() => break,
+                    _ = run_round(&client, &groups, &test_url, timeout, expected.as_deref()) => {}
+                }
+            }
+            log::debug!("Delay scheduler stopped");
+        });
+
+        Self {
+            cancel,
+            task: Some(task),
+        }
+    }
+
+    pub(crate) fn from_parts(cancel: CancellationToken, task: JoinHandle<()>) -> Self {
+        Self {
+            cancel,
+            task: Some(task),
+        }
+    }
+
+    pub fn stop(&self) {
+        self.cancel.cancel();
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.task.as_ref().is_some_and(|task| !task.is_finished())
+    }
+
+    /// Stops the scheduler and waits for any in-flight round to be dropped.
+    pub async fn shutdown(mut self) {
+        self.cancel.cancel();
+        if let Some(task) = self.task.take() {
+            let _ = task.await;
+        }
+    }
+}
+
+impl Drop for DelayScheduler {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
+
+// Each test owns its inputs; borrowing across `buffer_unordered` makes the spawned
+// future fail the `Send` check.
+async fn run_round(
+    client: &MihomoClient,
+    groups: &[String],
+    test_url: &str,
+    timeout: u32,
+    expected: Option<&str>,
+) {
+    futures_util::stream::iter(groups.to_vec())
+        .map(|group| {
+            test_group(
+                client.clone(),
+                group,
+                test_url.to_string(),
+                timeout,
+                expected.map(str::to_string),
+            )
+        })
+        .buffer_unordered(MAX_CONCURRENT_GROUP_TESTS)
+        .collect::<()>()
+        .await;
+}
+
+async fn test_group(
+    client: MihomoClient,
+    group: String,
+    test_url: String,
+    timeout: u32,
+    expected: Option<String>,
+) {
+    if let Err(err) = client
+        .group_delay(&group, &test_url, timeout, expected.as_deref())
+        .await
+    {
+        log::warn!("Scheduled delay test for '{}' failed: {}", group, err);
+    }
+}
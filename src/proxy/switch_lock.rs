@@ -0,0 +1,42 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::OwnedMutexGuard;
+
+/// One async lock per group name, so switches on the same group reach the
+/// controller one at a time and in the order they were requested.
+#[derive(Debug, Default)]
+pub(crate) struct SwitchLocks {
+    groups: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl SwitchLocks {
+    pub(crate) async fn lock(&self, group: &str) -> OwnedMutexGuard<()> {
+        let lock = {
+            let mut groups = self.groups.lock().unwrap_or_else(|e| e.into_inner());
+            groups.entry(group.to_string()).or_default().clone()
+        };
+        lock.lock_owned().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn same_group_serializes_other_groups_do_not() {
+        let locks = SwitchLocks::default();
+        let held = locks.lock("Proxy").await;
+
+        let other = tokio::time::timeout(Duration::from_millis(50), locks.lock("Auto")).await;
+        assert!(other.is_ok());
+
+        let same = tokio::time::timeout(Duration::from_millis(50), locks.lock("Proxy")).await;
+        assert!(same.is_err());
+
+        drop(held);
+        let same = tokio::time::timeout(Duration::from_millis(50), locks.lock("Proxy")).await;
+        assert!(same.is_ok());
+    }
+}
@@ -1,5 +1,13 @@
-use crate::core::{MihomoClient, Result};
-use std::collections::HashMap;
+use super::builtin::GLOBAL;
+use super::delay_cache::DelayCache;
+use super::scheduler::{DEFAULT_TEST_TIMEOUT_MS, DEFAULT_TEST_URL};
+use crate::core::{fan_out, DelayOutcome, MihomoClient, Result};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+/// Maximum number of delay tests in flight for the bulk helpers.
+const DELAY_TEST_CONCURRENCY: usize = 8;
 
 fn is_group_type(proxy_type: &str) -> bool {
     matches!(
@@ -22,25 +30,198 @@ pub async fn test_all_delays(
     test_url: &str,
     timeout: u32,
 ) -> Result<HashMap<String, u32>> {
-    let proxies = client.get_proxies().await?;
-    let mut results = HashMap::new();
+    test_all_delays_with_cancel(client, test_url, timeout, &CancellationToken::new()).await
+}
 
-    for (name, info) in proxies {
-        if !is_group_type(&info.proxy_type) {
-            if let Ok(delay) = client.test_delay(&name, test_url, timeout).await {
-                results.insert(name, delay);
-            }
+/// Same as [`test_all_delays`], but stops as soon as `cancel` fires and returns
+/// the delays measured so far. In-flight requests are dropped, not awaited.
+pub async fn test_all_delays_with_cancel(
+    client: &MihomoClient,
+    test_url: &str,
+    timeout: u32,
+    cancel: &CancellationToken,
+) -> Result<HashMap<String, u32>> {
+    let outcomes = test_all_delay_outcomes(client, test_url, timeout, cancel).await?;
+    Ok(outcomes
+        .into_iter()
+        .filter_map(|(name, outcome)| outcome.delay().map(|delay| (name, delay)))
+        .collect())
+}
+
+/// Like [`test_all_delays_with_cancel`], but keeps failed nodes alongside their
+/// outcome. Results are sorted by proxy name.
+pub async fn test_all_delay_outcomes(
+    client: &MihomoClient,
+    test_url: &str,
+    timeout: u32,
+    cancel: &CancellationToken,
+) -> Result<Vec<(String, DelayOutcome)>> {
+    let proxies = tokio::select! {
+        biased;
+        _ = cancel.cancelled() => return Ok(vec![]),
+        proxies = client.get_proxies() => proxies?,
+    };
+    let mut names: Vec<String> = proxies
+        .into_iter()
+        .filter(|(_, info)| !is_group_type(&info.proxy_type))
+        .map(|(name, _)| name)
+        .collect();
+    names.sort();
+
+    // Outcomes are collected as they finish, so a cancelled run keeps what it measured.
+    let total = names.len();
+    let finished = Mutex::new(Vec::with_capacity(total));
+    tokio::select! {
+        biased;
+        _ = cancel.cancelled() => {
+            log::debug!(
+                "Delay tests cancelled after {} of {} results",
+                finished.lock().unwrap().len(),
+                total
+            );
         }
+        _ = fan_out(names, DELAY_TEST_CONCURRENCY, |name| {
+            let finished = &finished;
+            async move {
+                let outcome = client.test_delay_outcome(&name, test_url, timeout).await;
+                finished.lock().unwrap().push((name, outcome));
+                Ok(())
+            }
+        }) => {}
     }
 
+    let mut results = finished.into_inner().unwrap();
+    results.sort_by(|a, b| a.0.cmp(&b.0));
     Ok(results)
 }
 
+/// Settings for [`test_all_groups`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupTestOptions {
+    pub test_url: String,
+    /// Delay-test timeout in milliseconds.
+    pub timeout: u32,
+    /// Maximum number of delay tests in flight.
+    pub concurrency: usize,
+    /// HTTP statuses that count as a pass, in mihomo's `expected` syntax. `None`
+    /// keeps mihomo's default of 204, or the manager's
+    /// [`with_expected_status`](super::ProxyManager::with_expected_status) when run
+    /// through [`ProxyManager::test_all_groups`](super::ProxyManager::test_all_groups).
+    pub expected: Option<String>,
+}
+
+impl Default for GroupTestOptions {
+    fn default() -> Self {
+        Self {
+            test_url: DEFAULT_TEST_URL.to_string(),
+            timeout: DEFAULT_TEST_TIMEOUT_MS,
+            concurrency: DELAY_TEST_CONCURRENCY,
+            expected: None,
+        }
+    }
+}
+
+impl GroupTestOptions {
+    pub fn with_test(mut self, url: impl Into<String>, timeout: u32) -> Self {
+        self.test_url = url.into();
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    pub fn with_expected(mut self, expected: impl Into<String>) -> Self {
+        self.expected = Some(expected.into());
+        self
+    }
+}
+
+/// Delay-tests the members of every `Selector` and `URLTest` group and reports the
+/// outcomes per group, in each group's member order. `GLOBAL` is skipped, and a
+/// member shared by several groups is only tested once.
+pub async fn test_all_groups(
+    client: &MihomoClient,
+    options: &GroupTestOptions,
+) -> Result<HashMap<String, Vec<(String, DelayOutcome)>>> {
+    test_all_groups_cached(client, options, None).await
+}
+
+/// [`test_all_groups`] that answers members from `cache` when it can and records
+/// fresh successes in it.
+pub(crate) async fn test_all_groups_cached(
+    client: &MihomoClient,
+    options: &GroupTestOptions,
+    cache: Option<&DelayCache>,
+) -> Result<HashMap<String, Vec<(String, DelayOutcome)>>> {
+    let groups: Vec<(String, Vec<String>)> = client
+        .get_proxies()
+        .await?
+        .into_iter()
+        .filter(|(name, info)| {
+            name != GLOBAL && matches!(info.proxy_type.as_str(), "Selector" | "URLTest")
+        })
+        .map(|(name, info)| (name, info.all.unwrap_or_default()))
+        .collect();
+
+    let members: Vec<String> = groups
+        .iter()
+        .flat_map(|(_, members)| members.iter().cloned())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let outcomes: HashMap<String, DelayOutcome> =
+        fan_out(members, options.concurrency, |member| async move {
+            if let Some(delay) = cache.and_then(|cache| cache.get(&member, &options.test_url)) {
+                return Ok(DelayOutcome::Ok(delay));
+            }
+            let outcome = client
+                .delay_outcome(
+                    &member,
+                    &options.test_url,
+                    options.timeout,
+                    options.expected.as_deref(),
+                )
+                .await;
+            if let (Some(cache), Some(delay)) = (cache, outcome.delay()) {
+                cache.insert(&member, &options.test_url, delay);
+            }
+            Ok(outcome)
+        })
+        .await
+        .into_iter()
+        .map(|(member, outcome)| {
+            let outcome = outcome.unwrap_or_else(|err| DelayOutcome::ApiError(err.to_string()));
+            (member, outcome)
+        })
+        .collect();
+
+    Ok(groups
+        .into_iter()
+        .map(|(group, members)| {
+            let report = members
+                .into_iter()
+                .map(|member| {
+                    let outcome = outcomes[&member].clone();
+                    (member, outcome)
+                })
+                .collect();
+            (group, report)
+        })
+        .collect())
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{is_group_type, test_all_delays, test_delay};
-    use crate::core::MihomoClient;
+    use super::{
+        is_group_type, test_all_delay_outcomes, test_all_delays, test_all_delays_with_cancel,
+        test_all_groups, test_delay, GroupTestOptions,
+    };
+    use crate::core::{DelayOutcome, MihomoClient};
     use mockito::Server;
+    use tokio_util::sync::CancellationToken;
 
     #[test]
     fn test_is_group_type() {
@@ -131,4 +312,145 @@ mod tests {
         assert!(!result.contains_key("GLOBAL"));
         assert!(!result.contains_key("JP-01"));
     }
+
+    #[tokio::test]
+    async fn test_all_groups_tests_shared_members_once() {
+        let mut server = Server::new_async().await;
+        server
+            .mock("GET", "/proxies")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{
+                    "proxies": {
+                        "GLOBAL": {"type":"Selector","now":"HK","all":["HK","JP","SG"]},
+                        "Select": {"type":"Selector","now":"HK","all":["JP","HK"]},
+                        "Auto": {"type":"URLTest","now":"HK","all":["HK"]},
+                        "Balance": {"type":"LoadBalance","now":"SG","all":["SG"]},
+                        "HK": {"type":"Shadowsocks","history":[]},
+                        "JP": {"type":"Shadowsocks","history":[]},
+                        "SG": {"type":"Shadowsocks","history":[]}
+                    }
+                }"#,
+            )
+            .create_async()
+            .await;
+        let hk = server
+            .mock("GET", "/proxies/HK/delay")
+            .match_query(mockito::Matcher::Any)
+            .with_status(200)
+            .with_body(r#"{"delay":40}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/proxies/JP/delay")
+            .match_query(mockito::Matcher::Any)
+            .with_status(504)
+            .create_async()
+            .await;
+
+        let client = MihomoClient::new(&server.url(), None).expect("create client");
+        let report = test_all_groups(&client, &GroupTestOptions::default().with_concurrency(2))
+            .await
+            .expect("test all groups");
+
+        hk.assert_async().await;
+        assert_eq!(report.len(), 2);
+        assert_eq!(
+            report["Select"],
+            vec![
+                ("JP".to_string(), DelayOutcome::Timeout),
+                ("HK".to_string(), DelayOutcome::Ok(40)),
+            ]
+        );
+        assert_eq!(
+            report["Auto"],
+            vec![("HK".to_string(), DelayOutcome::Ok(40))]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_all_delay_outcomes_keeps_failed_nodes() {
+        let mut server = Server::new_async().await;
+        server
+            .mock("GET", "/proxies")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{
+                    "proxies": {
+                        "GLOBAL": {"type":"Selector","now":"HK-01","all":["HK-01","JP-01","US-01"]},
+                        "US-01": {"type":"Shadowsocks","history":[]},
+                        "JP-01": {"type":"Shadowsocks","history":[]},
+                        "HK-01": {"type":"Shadowsocks","history":[]}
+                    }
+                }"#,
+            )
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/proxies/HK-01/delay")
+            .match_query(mockito::Matcher::Any)
+            .with_status(200)
+            .with_body(r#"{"delay":88}"#)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/proxies/JP-01/delay")
+            .match_query(mockito::Matcher::Any)
+            .with_status(504)
+            .with_body(r#"{"message":"Timeout"}"#)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/proxies/US-01/delay")
+            .match_query(mockito::Matcher::Any)
+            .with_status(503)
+            .with_body(r#"{"message":"An error occurred in the delay test"}"#)
+            .create_async()
+            .await;
+
+        let client = MihomoClient::new(&server.url(), None).expect("create client");
+        let results = test_all_delay_outcomes(
+            &client,
+            "https://example.com",
+            5000,
+            &CancellationToken::new(),
+        )
+        .await
+        .expect("test all delays");
+
+        assert_eq!(
+            results,
+            vec![
+                ("HK-01".to_string(), DelayOutcome::Ok(88)),
+                ("JP-01".to_string(), DelayOutcome::Timeout),
+                ("US-01".to_string(), DelayOutcome::Unreachable),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_all_delays_with_cancel_stops_without_further_requests() {
+        let mut server = Server::new_async().await;
+        let proxies = server
+            .mock("GET", "/proxies")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"proxies":{"HK-01":{"type":"Shadowsocks","history":[]}}}"#)
+            .expect(0)
+            .create_async()
+            .await;
+
+        let client = MihomoClient::new(&server.url(), None).expect("create client");
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+
+        let results = test_all_delays_with_cancel(&client, "https://example.com", 5000, &cancel)
+            .await
+            .expect("cancelled run should succeed");
+        proxies.assert_async().await;
+        assert!(results.is_empty());
+    }
 }
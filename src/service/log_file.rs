@@ -0,0 +1,96 @@
+use crate::core::Result;
+use std::fs::OpenOptions;
+use std::path::PathBuf;
+use std::process::Stdio;
+
+const DEFAULT_MAX_BYTES: u64 = 10 * 1024 * 1024;
+
+/// File that receives mihomo's stdout and stderr, rotated to `<path>.1` once it
+/// reaches `max_bytes`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFile {
+    pub path: PathBuf,
+    pub max_bytes: u64,
+}
+
+impl LogFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            max_bytes: DEFAULT_MAX_BYTES,
+        }
+    }
+
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes.max(1);
+        self
+    }
+
+    pub fn rotated_path(&self) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(".1");
+        PathBuf::from(name)
+    }
+
+    /// Moves the log to `<path>.1`, replacing the previous one, if it has reached
+    /// `max_bytes`. The log is copied and then truncated rather than renamed, so a
+    /// running process keeps appending to `path`; lines written in between are lost.
+    pub async fn rotate_if_needed(&self) -> Result<bool> {
+        let size = match tokio::fs::metadata(&self.path).await {
+            Ok(meta) => meta.len(),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+            Err(err) => return Err(err.into()),
+        };
+        if size < self.max_bytes {
+            return Ok(false);
+        }
+
+        tokio::fs::copy(&self.path, self.rotated_path()).await?;
+        tokio::fs::OpenOptions::new()
+            .write(true)
+            .open(&self.path)
+            .await?
+            .set_len(0)
+            .await?;
+        log::debug!("Rotated {} ({} bytes)", self.path.display(), size);
+        Ok(true)
+    }
+
+    /// Stdout and stderr handles appending to the log, creating it if needed.
+    pub(crate) fn open(&self) -> Result<(Stdio, Stdio)> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let stderr = file.try_clone()?;
+        Ok((file.into(), stderr.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn rotates_only_past_the_size_limit() {
+        let dir = tempdir().expect("create temp dir");
+        let log = LogFile::new(dir.path().join("mihomo.log")).with_max_bytes(8);
+        assert_eq!(log.rotated_path(), dir.path().join("mihomo.log.1"));
+        assert!(!log.rotate_if_needed().await.expect("missing log"));
+
+        tokio::fs::write(&log.path, "short").await.unwrap();
+        assert!(!log.rotate_if_needed().await.expect("small log"));
+
+        tokio::fs::write(&log.path, "first line\n").await.unwrap();
+        assert!(log.rotate_if_needed().await.expect("large log"));
+        assert_eq!(
+            tokio::fs::read_to_string(log.rotated_path()).await.unwrap(),
+            "first line\n"
+        );
+        assert_eq!(tokio::fs::read_to_string(&log.path).await.unwrap(), "");
+    }
+}
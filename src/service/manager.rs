@@ -1,6 +1,14 @@
+use super::log_file::LogFile;
 use super::process;
-use crate::core::{get_home_dir, MihomoError, Result};
-use std::path::PathBuf;
+use crate::config::{include, ConfigManager};
+use crate::core::{
+    get_home_dir, is_port_available, parse_port_from_addr, validate_instance_name, MihomoClient,
+    MihomoError, Result, ServiceConfigInfo,
+};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Child;
+use std::sync::Mutex;
 use std::time::Duration;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -13,12 +21,19 @@ pub struct ServiceManager {
     binary_path: PathBuf,
     config_path: PathBuf,
     pid_file: PathBuf,
+    instance: Option<String>,
     stop_retries: u32,
     stop_interval: Duration,
+    stop_grace: Duration,
+    force_stop: bool,
+    managed: bool,
+    child: Mutex<Option<Child>>,
+    log_file: Option<LogFile>,
 }
 
 const DEFAULT_STOP_RETRIES: u32 = 50;
 const DEFAULT_STOP_INTERVAL_MS: u64 = 100;
+const DEFAULT_STOP_GRACE_SECS: u64 = 5;
 
 impl ServiceManager {
     pub fn new(binary_path: PathBuf, config_path: PathBuf) -> Self {
@@ -29,8 +44,14 @@ impl ServiceManager {
             binary_path,
             config_path,
             pid_file,
+            instance: None,
             stop_retries: DEFAULT_STOP_RETRIES,
             stop_interval: Duration::from_millis(DEFAULT_STOP_INTERVAL_MS),
+            stop_grace: Duration::from_secs(DEFAULT_STOP_GRACE_SECS),
+            force_stop: true,
+            managed: false,
+            child: Mutex::new(None),
+            log_file: None,
         }
     }
 
@@ -41,8 +62,14 @@ impl ServiceManager {
             binary_path,
             config_path,
             pid_file,
+            instance: None,
             stop_retries: DEFAULT_STOP_RETRIES,
             stop_interval: Duration::from_millis(DEFAULT_STOP_INTERVAL_MS),
+            stop_grace: Duration::from_secs(DEFAULT_STOP_GRACE_SECS),
+            force_stop: true,
+            managed: false,
+            child: Mutex::new(None),
+            log_file: None,
         }
     }
 
@@ -51,17 +78,113 @@ impl ServiceManager {
             binary_path,
             config_path,
             pid_file,
+            instance: None,
             stop_retries: DEFAULT_STOP_RETRIES,
             stop_interval: Duration::from_millis(DEFAULT_STOP_INTERVAL_MS),
+            stop_grace: Duration::from_secs(DEFAULT_STOP_GRACE_SECS),
+            force_stop: true,
+            managed: false,
+            child: Mutex::new(None),
+            log_file: None,
         }
     }
 
+    /// Names this instance so it tracks its own PID file (`mihomo-<name>.pid`) next to
+    /// the default one, and logs to `mihomo-<name>.log` there unless a log file was
+    /// already set. Each instance needs its own config with a distinct
+    /// external-controller address and ports; see [`Self::check_distinct_controllers`].
+    pub fn with_instance(mut self, name: &str) -> Result<Self> {
+        validate_instance_name(name)?;
+        self.pid_file = self.pid_file.with_file_name(format!("mihomo-{}.pid", name));
+        if self.log_file.is_none() {
+            self.log_file = Some(LogFile::new(
+                self.pid_file.with_file_name(format!("mihomo-{}.log", name)),
+            ));
+        }
+        self.instance = Some(name.to_string());
+        Ok(self)
+    }
+
+    /// Fails when two of `managers` expose their external controller on the same port,
+    /// so instances sharing a controller are caught before either starts. Configs that
+    /// can't be read or don't set a controller are skipped.
+    pub async fn check_distinct_controllers(managers: &[ServiceManager]) -> Result<()> {
+        let mut seen: HashMap<u16, &str> = HashMap::new();
+        for manager in managers {
+            let Ok(config) = include::resolve_file(&manager.config_path).await else {
+                continue;
+            };
+            let Some(port) = config
+                .get("external-controller")
+                .and_then(|v| v.as_str())
+                .and_then(parse_port_from_addr)
+            else {
+                continue;
+            };
+            let name = manager.instance().unwrap_or("default");
+            if let Some(other) = seen.insert(port, name) {
+                return Err(MihomoError::Service(format!(
+                    "Instances '{}' and '{}' both use controller port {}",
+                    other, name, port
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn instance(&self) -> Option<&str> {
+        self.instance.as_deref()
+    }
+
     pub fn with_stop_wait(mut self, retries: u32, interval: Duration) -> Self {
         self.stop_retries = retries.max(1);
         self.stop_interval = interval.max(Duration::from_millis(1));
         self
     }
 
+    /// In managed mode the manager keeps the process it starts and kills it when
+    /// dropped, instead of leaving it running detached. Useful for tests and
+    /// short-lived programs.
+    pub fn with_managed(mut self, managed: bool) -> Self {
+        self.managed = managed;
+        self
+    }
+
+    /// How long `stop` waits after asking the process to exit before escalating to a
+    /// hard kill. A zero grace period kills immediately.
+    pub fn with_stop_grace(mut self, grace: Duration) -> Self {
+        self.stop_grace = grace;
+        self
+    }
+
+    /// Whether `stop` hard-kills a process that outlives the grace period (the
+    /// default). When disabled, `stop` reports a timeout and leaves it running.
+    pub fn with_force_stop(mut self, force: bool) -> Self {
+        self.force_stop = force;
+        self
+    }
+
+    /// Sends mihomo's stdout and stderr to `log` instead of discarding them. The log
+    /// is rotated on every start once it reaches its size limit; call
+    /// [`Self::rotate_log`] periodically to also rotate it while the service runs.
+    pub fn with_log_file(mut self, log: LogFile) -> Self {
+        self.log_file = Some(log);
+        self
+    }
+
+    pub fn log_file(&self) -> Option<&LogFile> {
+        self.log_file.as_ref()
+    }
+
+    /// Rotates the log file if it has reached its size limit. Returns whether it was
+    /// rotated; always `false` without a log file.
+    pub async fn rotate_log(&self) -> Result<bool> {
+        match &self.log_file {
+            Some(log) => log.rotate_if_needed().await,
+            None => Ok(false),
+        }
+    }
+
     pub async fn start(&self) -> Result<()> {
         if self.is_running().await {
             return Err(MihomoError::Service(
@@ -69,7 +192,22 @@ impl ServiceManager {
             ));
         }
 
-        let pid = process::spawn_daemon(&self.binary_path, &self.config_path).await?;
+        // mihomo doesn't resolve `include:` itself, so it gets the merged profile.
+        let config = include::launch_path(&self.config_path).await?;
+        self.check_ports_free(&config).await?;
+
+        let child = match &self.log_file {
+            Some(log) => {
+                log.rotate_if_needed().await?;
+                let (stdout, stderr) = log.open()?;
+                process::spawn_child_with_output(&self.binary_path, &config, stdout, stderr).await?
+            }
+            None => process::spawn_child(&self.binary_path, &config).await?,
+        };
+        let pid = child.id();
+        if self.managed {
+            *self.child.lock().unwrap_or_else(|e| e.into_inner()) = Some(child);
+        }
 
         tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
 
@@ -84,6 +222,55 @@ impl ServiceManager {
         Ok(())
     }
 
+    /// Client for the controller this service exposes, using the `external-controller`
+    /// and `secret` from the config file it is started with, so the two can't drift.
+    pub async fn client(&self) -> Result<MihomoClient> {
+        let config = include::resolve_file(&self.config_path).await?;
+        let controller = config
+            .get("external-controller")
+            .and_then(|v| v.as_str())
+            .unwrap_or("127.0.0.1:9090");
+        let url = ConfigManager::normalize_external_controller(controller)?;
+        let secret = config
+            .get("secret")
+            .and_then(|v| v.as_str())
+            .filter(|secret| !secret.is_empty());
+        MihomoClient::new(&url, secret.map(str::to_string))
+    }
+
+    /// Fails fast when a listener or controller port from the config is already bound,
+    /// instead of letting mihomo exit with an opaque startup failure. Configs that
+    /// can't be read are left for mihomo to report.
+    async fn check_ports_free(&self, config: &Path) -> Result<()> {
+        let Ok(content) = tokio::fs::read_to_string(config).await else {
+            return Ok(());
+        };
+        let Ok(config) = serde_yaml::from_str::<serde_yaml::Value>(&content) else {
+            return Ok(());
+        };
+
+        let mut ports = serde_yaml::from_value::<ServiceConfigInfo>(config.clone())
+            .map(|info| info.active_ports())
+            .unwrap_or_default();
+        if let Some(port) = config
+            .get("external-controller")
+            .and_then(|v| v.as_str())
+            .and_then(parse_port_from_addr)
+        {
+            ports.push(("external-controller".to_string(), port));
+        }
+
+        for (kind, port) in ports {
+            if !is_port_available(port) {
+                return Err(MihomoError::Service(format!(
+                    "Port {} ({}) is already in use",
+                    port, kind
+                )));
+            }
+        }
+        Ok(())
+    }
+
     pub async fn stop(&self) -> Result<()> {
         let record = process::read_pid_record(&self.pid_file).await?;
 
@@ -92,14 +279,33 @@ impl ServiceManager {
             return Err(MihomoError::Service("Service is not running".to_string()));
         }
 
-        process::kill_process_checked(record.pid, record.start_time)?;
+        let is_stopped = || !process::is_process_alive_checked(record.pid, record.start_time);
+        let mut stopped = false;
+        if !self.stop_grace.is_zero() {
+            process::terminate_process_checked(record.pid, record.start_time)?;
+            let grace_retries = self
+                .stop_grace
+                .as_millis()
+                .div_ceil(self.stop_interval.as_millis())
+                .max(1);
+            stopped = Self::wait_for_stop(
+                is_stopped,
+                u32::try_from(grace_retries).unwrap_or(u32::MAX),
+                self.stop_interval,
+            )
+            .await;
+        }
 
-        let stopped = Self::wait_for_stop(
-            || !process::is_process_alive_checked(record.pid, record.start_time),
-            self.stop_retries,
-            self.stop_interval,
-        )
-        .await;
+        if !stopped && (self.force_stop || self.stop_grace.is_zero()) {
+            if !is_stopped() {
+                log::debug!(
+                    "Process {} outlived the stop grace period, killing",
+                    record.pid
+                );
+                process::kill_process_checked(record.pid, record.start_time)?;
+            }
+            stopped = Self::wait_for_stop(is_stopped, self.stop_retries, self.stop_interval).await;
+        }
 
         if !stopped {
             return Err(MihomoError::Service(
@@ -107,10 +313,21 @@ impl ServiceManager {
             ));
         }
 
+        self.reap_child(record.pid);
         process::remove_pid_file(&self.pid_file).await?;
         Ok(())
     }
 
+    /// Waits on the managed child if it is `pid`, so it does not linger as a zombie.
+    fn reap_child(&self, pid: u32) {
+        let mut child = self.child.lock().unwrap_or_else(|e| e.into_inner());
+        if child.as_ref().is_some_and(|c| c.id() == pid) {
+            if let Some(mut c) = child.take() {
+                let _ = c.wait();
+            }
+        }
+    }
+
     pub async fn restart(&self) -> Result<()> {
         if self.is_running().await {
             self.stop().await?;
@@ -151,6 +368,22 @@ impl ServiceManager {
     }
 }
 
+impl Drop for ServiceManager {
+    fn drop(&mut self) {
+        let child = self
+            .child
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+        if let Some(mut child) = child {
+            log::debug!("Stopping managed process {}", child.id());
+            let _ = child.kill();
+            let _ = child.wait();
+            let _ = std::fs::remove_file(&self.pid_file);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -200,6 +433,111 @@ mod tests {
         assert!(!stopped);
     }
 
+    #[test]
+    fn test_with_instance_namespaces_pid_file() {
+        let dir = tempdir().expect("create temp dir");
+        let manager = ServiceManager::with_home(
+            PathBuf::from("/bin/echo"),
+            PathBuf::from("/tmp/config.yaml"),
+            dir.path().to_path_buf(),
+        );
+        let test = ServiceManager::with_home(
+            PathBuf::from("/bin/echo"),
+            PathBuf::from("/tmp/test.yaml"),
+            dir.path().to_path_buf(),
+        )
+        .with_instance("test")
+        .expect("valid instance name");
+
+        assert_eq!(manager.pid_file, dir.path().join("mihomo.pid"));
+        assert_eq!(test.pid_file, dir.path().join("mihomo-test.pid"));
+        assert_eq!(test.instance(), Some("test"));
+        assert_eq!(manager.log_file(), None);
+        assert_eq!(
+            test.log_file().map(|log| log.path.clone()),
+            Some(dir.path().join("mihomo-test.log"))
+        );
+        let custom = ServiceManager::with_home(
+            PathBuf::from("/bin/echo"),
+            PathBuf::from("/tmp/test.yaml"),
+            dir.path().to_path_buf(),
+        )
+        .with_log_file(LogFile::new(dir.path().join("custom.log")))
+        .with_instance("test")
+        .expect("valid instance name");
+        assert_eq!(
+            custom.log_file().map(|log| log.path.clone()),
+            Some(dir.path().join("custom.log"))
+        );
+        assert!(ServiceManager::with_home(
+            PathBuf::from("/bin/echo"),
+            PathBuf::from("/tmp/test.yaml"),
+            dir.path().to_path_buf(),
+        )
+        .with_instance("../prod")
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_check_distinct_controllers_rejects_shared_port() {
+        let dir = tempdir().expect("create temp dir");
+        let write = |name: &str, controller: &str| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, format!("external-controller: {}\n", controller))
+                .expect("write config");
+            path
+        };
+        let instance = |config: PathBuf, name: Option<&str>| {
+            let manager =
+                ServiceManager::with_home(PathBuf::from("/bin/echo"), config, dir.path().into());
+            match name {
+                Some(name) => manager.with_instance(name).expect("valid instance name"),
+                None => manager,
+            }
+        };
+
+        let default = instance(write("a.yaml", "127.0.0.1:9090"), None);
+        let other = instance(write("b.yaml", "127.0.0.1:9091"), Some("other"));
+        let clash = instance(write("c.yaml", ":9090"), Some("clash"));
+
+        ServiceManager::check_distinct_controllers(&[default, other])
+            .await
+            .expect("distinct controllers");
+        let default = instance(dir.path().join("a.yaml"), None);
+        let err = ServiceManager::check_distinct_controllers(&[default, clash])
+            .await
+            .expect_err("shared controller port");
+        assert_eq!(
+            err.to_string(),
+            "Service error: Instances 'default' and 'clash' both use controller port 9090"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_instances_track_status_independently() {
+        let dir = tempdir().expect("create temp dir");
+        let default = ServiceManager::with_home(
+            PathBuf::from("/bin/echo"),
+            PathBuf::from("/tmp/config.yaml"),
+            dir.path().to_path_buf(),
+        );
+        let other = ServiceManager::with_home(
+            PathBuf::from("/bin/echo"),
+            PathBuf::from("/tmp/other.yaml"),
+            dir.path().to_path_buf(),
+        )
+        .with_instance("other")
+        .expect("valid instance name");
+
+        let pid = std::process::id();
+        process::write_pid_record(&other.pid_file, pid, process::get_process_start_time(pid))
+            .await
+            .expect("write pid");
+
+        assert_eq!(other.status().await.unwrap(), ServiceStatus::Running(pid));
+        assert_eq!(default.status().await.unwrap(), ServiceStatus::Stopped);
+    }
+
     #[test]
     fn test_with_stop_wait_overrides_defaults() {
         let manager = ServiceManager::with_pid_file(
@@ -1,4 +1,6 @@
+pub mod log_file;
 pub mod manager;
 pub mod process;
 
+pub use log_file::LogFile;
 pub use manager::{ServiceManager, ServiceStatus};
@@ -1,7 +1,7 @@
 use crate::core::{MihomoError, Result};
 use std::path::Path;
-use std::process::{Command, Stdio};
-use sysinfo::{Pid, ProcessStatus, ProcessesToUpdate, System};
+use std::process::{Child, Command, Stdio};
+use sysinfo::{Pid, ProcessStatus, ProcessesToUpdate, Signal, System};
 use tokio::fs;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -11,6 +11,21 @@ pub struct PidRecord {
 }
 
 pub async fn spawn_daemon(binary: &Path, config: &Path) -> Result<u32> {
+    Ok(spawn_child(binary, config).await?.id())
+}
+
+/// Like [`spawn_daemon`], but hands back the `Child` so the caller can own its lifetime.
+pub async fn spawn_child(binary: &Path, config: &Path) -> Result<Child> {
+    spawn_child_with_output(binary, config, Stdio::null(), Stdio::null()).await
+}
+
+/// Like [`spawn_child`], but sends the process's stdout and stderr to the given handles.
+pub async fn spawn_child_with_output(
+    binary: &Path,
+    config: &Path,
+    stdout: Stdio,
+    stderr: Stdio,
+) -> Result<Child> {
     if !binary.exists() {
         return Err(MihomoError::NotFound(format!(
             "Binary not found: {}",
@@ -33,13 +48,12 @@ pub async fn spawn_daemon(binary: &Path, config: &Path) -> Result<u32> {
         .arg("-f")
         .arg(config)
         .stdin(Stdio::null())
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
+        .stdout(stdout)
+        .stderr(stderr)
         .spawn()
         .map_err(|e| MihomoError::Service(format!("Failed to spawn process: {}", e)))?;
 
-    let pid = child.id();
-    Ok(pid)
+    Ok(child)
 }
 
 pub fn kill_process(pid: u32) -> Result<()> {
@@ -98,6 +112,31 @@ pub fn kill_process_checked(pid: u32, expected_start_time: Option<u64>) -> Resul
     kill_process(pid)
 }
 
+/// Asks the process to exit (SIGTERM on Unix). Platforms without signals fall back
+/// to a hard kill.
+pub fn terminate_process_checked(pid: u32, expected_start_time: Option<u64>) -> Result<()> {
+    if !is_process_alive_checked(pid, expected_start_time) {
+        return Err(MihomoError::Service(format!(
+            "PID {} no longer matches tracked process",
+            pid
+        )));
+    }
+
+    let mut system = System::new();
+    system.refresh_processes(ProcessesToUpdate::All, true);
+    let Some(process) = system.process(Pid::from_u32(pid)) else {
+        return Ok(());
+    };
+    match process.kill_with(Signal::Term) {
+        Some(true) => Ok(()),
+        Some(false) => Err(MihomoError::Service(format!(
+            "Failed to signal process {}",
+            pid
+        ))),
+        None => kill_process(pid),
+    }
+}
+
 pub async fn read_pid_file(path: &Path) -> Result<u32> {
     Ok(read_pid_record(path).await?.pid)
 }
@@ -124,6 +124,8 @@ async fn fetch_latest_with_base(api_base: &str, channel: Channel) -> Result<Chan
     })
 }
 
+const MAX_RELEASES_PER_PAGE: usize = 100;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReleaseInfo {
     #[serde(rename = "tag_name")]
@@ -133,10 +135,40 @@ pub struct ReleaseInfo {
     pub prerelease: bool,
 }
 
+impl ReleaseInfo {
+    /// Whether this release belongs to `channel`, using the same rules as [`fetch_latest`].
+    pub fn matches_channel(&self, channel: Channel) -> bool {
+        let tag = self.version.to_lowercase();
+        match channel {
+            Channel::Stable => !self.prerelease,
+            Channel::Beta => self.prerelease,
+            Channel::Nightly => self.prerelease || tag.contains("nightly") || tag.contains("alpha"),
+        }
+    }
+}
+
 pub async fn fetch_releases(limit: usize) -> Result<Vec<ReleaseInfo>> {
     fetch_releases_with_base("https://api.github.com", limit).await
 }
 
+pub async fn fetch_releases_by_channel(limit: usize, channel: Channel) -> Result<Vec<ReleaseInfo>> {
+    fetch_releases_by_channel_with_base("https://api.github.com", limit, channel).await
+}
+
+async fn fetch_releases_by_channel_with_base(
+    api_base: &str,
+    limit: usize,
+    channel: Channel,
+) -> Result<Vec<ReleaseInfo>> {
+    // Filtering happens client-side, so fetch a full page to still fill `limit`.
+    let releases = fetch_releases_with_base(api_base, MAX_RELEASES_PER_PAGE).await?;
+    Ok(releases
+        .into_iter()
+        .filter(|release| release.matches_channel(channel))
+        .take(limit)
+        .collect())
+}
+
 async fn fetch_releases_with_base(api_base: &str, limit: usize) -> Result<Vec<ReleaseInfo>> {
     let client = reqwest::Client::new();
     let resp = client
@@ -333,4 +365,40 @@ mod tests {
         fail.assert_async().await;
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn fetch_releases_by_channel_filters_and_limits() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("GET", "/repos/MetaCubeX/mihomo/releases")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "per_page".into(),
+                "100".into(),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"[
+                    {"tag_name":"Prerelease-Alpha","name":"Alpha","published_at":"2026-01-03T00:00:00Z","prerelease":true},
+                    {"tag_name":"v1.21.0","name":"v1.21.0","published_at":"2026-01-02T00:00:00Z","prerelease":false},
+                    {"tag_name":"v1.20.0","name":"v1.20.0","published_at":"2026-01-01T00:00:00Z","prerelease":false}
+                ]"#,
+            )
+            .expect(2)
+            .create_async()
+            .await;
+
+        let stable = fetch_releases_by_channel_with_base(&server.url(), 1, Channel::Stable)
+            .await
+            .expect("fetch stable releases");
+        assert_eq!(stable.len(), 1);
+        assert_eq!(stable[0].version, "v1.21.0");
+
+        let nightly = fetch_releases_by_channel_with_base(&server.url(), 5, Channel::Nightly)
+            .await
+            .expect("fetch nightly releases");
+        assert_eq!(nightly.len(), 1);
+        assert_eq!(nightly[0].version, "Prerelease-Alpha");
+        mock.assert_async().await;
+    }
 }
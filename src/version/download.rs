@@ -1,35 +1,95 @@
 use crate::core::{MihomoError, Result};
+use futures_util::StreamExt;
 use std::path::Path;
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
+use tokio_util::sync::CancellationToken;
+
+const RELEASE_DOWNLOAD_URL: &str = "https://github.com/MetaCubeX/mihomo/releases/download";
+
+/// GitHub release assets redirect once to their CDN; a longer chain usually means a
+/// captive portal or proxy login page.
+const MAX_REDIRECTS: usize = 5;
+
+/// Progress of an install, in the order the stages happen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstallEvent {
+    Resolving,
+    /// `total` is `None` when the server doesn't send a length.
+    Downloading {
+        done: u64,
+        total: Option<u64>,
+    },
+    Verifying,
+    Extracting,
+    Done,
+}
 
 pub struct Downloader {
     client: reqwest::Client,
+    base_url: String,
 }
 
 impl Downloader {
     pub fn new() -> Self {
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::limited(MAX_REDIRECTS))
+            .build()
+            .unwrap_or_default();
         Self {
-            client: reqwest::Client::new(),
+            client,
+            base_url: RELEASE_DOWNLOAD_URL.to_string(),
         }
     }
 
+    /// Downloads release assets from `<base_url>/<version>/<asset>` instead of GitHub,
+    /// e.g. from a mirror.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
     pub async fn download_version(&self, version: &str, dest: &Path) -> Result<()> {
+        self.download_version_with_progress(version, dest, &CancellationToken::new(), |_| {})
+            .await
+    }
+
+    /// Like [`Self::download_version`], but reports each stage to `on_event` and stops
+    /// with an error once `cancel` fires during the download. Nothing is written to
+    /// `dest` before the archive has been fully received and verified.
+    pub async fn download_version_with_progress<F>(
+        &self,
+        version: &str,
+        dest: &Path,
+        cancel: &CancellationToken,
+        mut on_event: F,
+    ) -> Result<()>
+    where
+        F: FnMut(InstallEvent),
+    {
         let platform = Self::detect_platform();
         let os_name = Self::get_os_name();
         let extension = Self::get_file_extension();
         let filename = format!("mihomo-{}-{}-{}.{}", os_name, platform, version, extension);
         let url = format!(
-            "https://github.com/MetaCubeX/mihomo/releases/download/{}/{}",
-            version, filename
+            "{}/{}/{}",
+            self.base_url.trim_end_matches('/'),
+            version,
+            filename
         );
+        let cancelled =
+            || MihomoError::version(format!("Download of version {} cancelled", version));
 
-        let resp = self
+        let request = self
             .client
             .get(&url)
             .header("User-Agent", "mihomo-rs")
-            .send()
-            .await?;
+            .send();
+        let resp = tokio::select! {
+            biased;
+            _ = cancel.cancelled() => return Err(cancelled()),
+            resp = request => resp?,
+        };
 
         if !resp.status().is_success() {
             return Err(MihomoError::version(format!(
@@ -39,9 +99,48 @@ impl Downloader {
             )));
         }
 
-        let bytes = resp.bytes().await?;
+        let final_url = resp.url().clone();
+        if final_url.as_str() != url {
+            log::debug!("Download of {} redirected to {}", filename, final_url);
+        }
+        let content_type = resp
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let content_length = resp.content_length();
+
+        on_event(InstallEvent::Downloading {
+            done: 0,
+            total: content_length,
+        });
+        let mut bytes = Vec::new();
+        let mut body = resp.bytes_stream();
+        loop {
+            let chunk = tokio::select! {
+                biased;
+                _ = cancel.cancelled() => return Err(cancelled()),
+                chunk = body.next() => chunk,
+            };
+            let Some(chunk) = chunk else { break };
+            bytes.extend_from_slice(&chunk?);
+            on_event(InstallEvent::Downloading {
+                done: bytes.len() as u64,
+                total: content_length,
+            });
+        }
+
+        on_event(InstallEvent::Verifying);
+        Self::validate_download(&bytes, extension, content_type.as_deref(), content_length)
+            .map_err(|reason| {
+                MihomoError::version(format!(
+                    "Downloaded {} from {} is not a valid archive: {}",
+                    filename, final_url, reason
+                ))
+            })?;
 
         // Decompress based on file extension
+        on_event(InstallEvent::Extracting);
         let decompressed = if extension == "zip" {
             Self::decompress_zip(&bytes)?
         } else {
@@ -50,6 +149,7 @@ impl Downloader {
 
         let mut file = fs::File::create(dest).await?;
         file.write_all(&decompressed).await?;
+        file.sync_all().await?;
 
         #[cfg(unix)]
         {
@@ -89,6 +189,36 @@ impl Downloader {
         }
     }
 
+    /// Rejects responses that can't be the requested archive, such as an HTML error
+    /// page served with status 200 or a body cut short.
+    fn validate_download(
+        bytes: &[u8],
+        extension: &str,
+        content_type: Option<&str>,
+        content_length: Option<u64>,
+    ) -> std::result::Result<(), String> {
+        if let Some(content_type) = content_type {
+            let mime = content_type.split(';').next().unwrap_or_default().trim();
+            if mime.starts_with("text/") || mime == "application/json" {
+                return Err(format!("unexpected content type '{}'", mime));
+            }
+        }
+        if let Some(expected) = content_length {
+            if expected != bytes.len() as u64 {
+                return Err(format!("received {} of {} bytes", bytes.len(), expected));
+            }
+        }
+        let magic: &[u8] = if extension == "zip" {
+            b"PK\x03\x04"
+        } else {
+            &[0x1f, 0x8b]
+        };
+        if !bytes.starts_with(magic) {
+            return Err(format!("missing {} header", extension));
+        }
+        Ok(())
+    }
+
     fn decompress_gz(bytes: &[u8]) -> Result<Vec<u8>> {
         use flate2::read::GzDecoder;
         use std::io::Read;
@@ -193,6 +323,27 @@ mod tests {
         assert_eq!(Downloader::get_os_name(), "darwin");
     }
 
+    #[test]
+    fn test_validate_download_rejects_error_pages_and_truncation() {
+        let gz = [0x1f, 0x8b, 0x08, 0x00];
+        assert!(Downloader::validate_download(
+            &gz,
+            "gz",
+            Some("application/octet-stream"),
+            Some(4)
+        )
+        .is_ok());
+        assert!(Downloader::validate_download(b"PK\x03\x04rest", "zip", None, None).is_ok());
+
+        let page = b"<html>Proxy login</html>";
+        let err = Downloader::validate_download(page, "gz", Some("text/html; charset=utf-8"), None)
+            .unwrap_err();
+        assert!(err.contains("text/html"), "{}", err);
+        assert!(Downloader::validate_download(page, "gz", None, None).is_err());
+        assert!(Downloader::validate_download(&gz, "gz", None, Some(20)).is_err());
+        assert!(Downloader::validate_download(&gz, "zip", None, None).is_err());
+    }
+
     #[test]
     fn test_decompress_gz() {
         use flate2::write::GzEncoder;
@@ -276,6 +427,90 @@ mod tests {
             .contains("Expected 1 file in zip archive, found 2"));
     }
 
+    fn release_path(version: &str) -> String {
+        format!(
+            "/{}/mihomo-{}-{}-{}.{}",
+            version,
+            Downloader::get_os_name(),
+            Downloader::detect_platform(),
+            version,
+            Downloader::get_file_extension()
+        )
+    }
+
+    #[tokio::test]
+    #[cfg(not(windows))]
+    async fn test_download_with_progress_reports_stages() {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(b"#!/bin/sh\n").unwrap();
+        let archive = encoder.finish().unwrap();
+
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", release_path("v1.0.0").as_str())
+            .with_status(200)
+            .with_header("content-type", "application/octet-stream")
+            .with_body(&archive)
+            .create_async()
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("mihomo");
+        let mut events = vec![];
+        Downloader::new()
+            .with_base_url(server.url())
+            .download_version_with_progress("v1.0.0", &dest, &CancellationToken::new(), |e| {
+                events.push(e)
+            })
+            .await
+            .expect("download");
+
+        let total = Some(archive.len() as u64);
+        assert_eq!(
+            events.first(),
+            Some(&InstallEvent::Downloading { done: 0, total })
+        );
+        assert!(events.contains(&InstallEvent::Downloading {
+            done: archive.len() as u64,
+            total
+        }));
+        assert_eq!(
+            events[events.len() - 2..],
+            [InstallEvent::Verifying, InstallEvent::Extracting]
+        );
+        assert_eq!(std::fs::read(&dest).unwrap(), b"#!/bin/sh\n");
+    }
+
+    #[tokio::test]
+    async fn test_download_with_progress_stops_when_cancelled() {
+        let mut server = mockito::Server::new_async().await;
+        let asset = server
+            .mock("GET", release_path("v1.0.0").as_str())
+            .with_status(200)
+            .with_body("never read")
+            .expect(0)
+            .create_async()
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("mihomo");
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let err = Downloader::new()
+            .with_base_url(server.url())
+            .download_version_with_progress("v1.0.0", &dest, &cancel, |_| {})
+            .await
+            .expect_err("cancelled download");
+
+        assert!(err.to_string().contains("cancelled"), "{}", err);
+        assert!(!dest.exists());
+        asset.assert_async().await;
+    }
+
     #[test]
     fn test_decompress_gz_with_invalid_data() {
         let invalid_data = b"This is not gzip compressed data";
@@ -1,13 +1,15 @@
-use super::channel::{fetch_latest, Channel};
-use super::download::Downloader;
+use super::channel::{fetch_latest, Channel, ChannelInfo};
+use super::download::{Downloader, InstallEvent};
 use crate::core::{get_home_dir, validate_version_name, ErrorCode, MihomoError, Result};
 use semver::Version;
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering as CmpOrdering;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
 use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::fs;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
 
 static TEMP_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
 
@@ -40,6 +42,35 @@ impl VersionManager {
     }
 
     pub async fn install(&self, version: &str) -> Result<()> {
+        self.install_inner(version, &CancellationToken::new(), |_| {})
+            .await
+    }
+
+    /// Like [`Self::install`], but reports each stage on `events` and gives up, leaving
+    /// nothing installed, once `cancel` fires during the download. Events that can't
+    /// be delivered because the receiver was dropped are discarded.
+    pub async fn install_with_progress(
+        &self,
+        version: &str,
+        events: mpsc::UnboundedSender<InstallEvent>,
+        cancel: &CancellationToken,
+    ) -> Result<()> {
+        self.install_inner(version, cancel, |event| {
+            let _ = events.send(event);
+        })
+        .await
+    }
+
+    async fn install_inner<F>(
+        &self,
+        version: &str,
+        cancel: &CancellationToken,
+        mut on_event: F,
+    ) -> Result<()>
+    where
+        F: FnMut(InstallEvent),
+    {
+        on_event(InstallEvent::Resolving);
         validate_version_name(version).map_err(|_| {
             MihomoError::version_with_code(
                 ErrorCode::InvalidVersion,
@@ -56,38 +87,114 @@ impl VersionManager {
             )));
         }
 
-        let binary_name = if cfg!(windows) {
-            "mihomo.exe"
-        } else {
-            "mihomo"
-        };
+        self.download_binary(version, cancel, &mut on_event).await?;
+        on_event(InstallEvent::Done);
+        Ok(())
+    }
+
+    /// Downloads `version` and moves it over the installed binary path in one rename,
+    /// so an interrupted download never leaves a truncated binary behind.
+    async fn download_binary<F>(
+        &self,
+        version: &str,
+        cancel: &CancellationToken,
+        on_event: F,
+    ) -> Result<PathBuf>
+    where
+        F: FnMut(InstallEvent),
+    {
+        fs::create_dir_all(&self.install_dir).await?;
+        let binary_name = Self::binary_name();
 
         // Download to a temp file under install_dir to reduce cross-device rename failures.
         let temp_path = self.temp_download_path(version, binary_name);
 
         let downloader = Downloader::new();
-        if let Err(err) = downloader.download_version(version, &temp_path).await {
+        if let Err(err) = downloader
+            .download_version_with_progress(version, &temp_path, cancel, on_event)
+            .await
+        {
             let _ = fs::remove_file(&temp_path).await;
             return Err(err);
         }
 
         // Move to final location only after successful download
+        let version_dir = self.install_dir.join(version);
         fs::create_dir_all(&version_dir).await?;
         let binary_path = version_dir.join(binary_name);
-        if let Err(err) = fs::rename(&temp_path, &binary_path).await {
-            if err.kind() == std::io::ErrorKind::CrossesDevices {
-                if let Err(copy_err) = fs::copy(&temp_path, &binary_path).await {
-                    let _ = fs::remove_file(&temp_path).await;
+        let result = Self::replace_file(&temp_path, &binary_path).await;
+        let _ = fs::remove_file(&temp_path).await;
+        result?;
+
+        Ok(binary_path)
+    }
+
+    async fn replace_file(src: &Path, dest: &Path) -> Result<()> {
+        match fs::rename(src, dest).await {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == std::io::ErrorKind::CrossesDevices => {
+                // Stage a copy next to the destination so the final step is still a rename.
+                let staged = dest.with_extension("partial");
+                if let Err(copy_err) = fs::copy(src, &staged).await {
+                    let _ = fs::remove_file(&staged).await;
                     return Err(copy_err.into());
                 }
-                let _ = fs::remove_file(&temp_path).await;
-            } else {
-                let _ = fs::remove_file(&temp_path).await;
-                return Err(err.into());
+                if let Err(rename_err) = fs::rename(&staged, dest).await {
+                    let _ = fs::remove_file(&staged).await;
+                    return Err(rename_err.into());
+                }
+                Ok(())
             }
+            Err(err) => Err(err.into()),
         }
+    }
 
-        Ok(())
+    /// Installs `version` unless a usable binary is already present, returning its path.
+    ///
+    /// With `force`, the binary is downloaded again and swapped in over the existing one.
+    pub async fn ensure_installed(&self, version: &str, force: bool) -> Result<PathBuf> {
+        validate_version_name(version).map_err(|_| {
+            MihomoError::version_with_code(
+                ErrorCode::InvalidVersion,
+                format!("Invalid version '{}'", version),
+            )
+        })?;
+
+        let binary_path = self.install_dir.join(version).join(Self::binary_name());
+        if !force && Self::is_usable_binary(&binary_path).await {
+            log::debug!("Version {} already installed, skipping download", version);
+            return Ok(binary_path);
+        }
+
+        self.download_binary(version, &CancellationToken::new(), |_| {})
+            .await
+    }
+
+    async fn is_usable_binary(path: &Path) -> bool {
+        let Ok(metadata) = fs::metadata(path).await else {
+            return false;
+        };
+        if !metadata.is_file() || metadata.len() == 0 {
+            return false;
+        }
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            metadata.permissions().mode() & 0o111 != 0
+        }
+        #[cfg(not(unix))]
+        {
+            true
+        }
+    }
+
+    fn binary_name() -> &'static str {
+        if cfg!(windows) {
+            "mihomo.exe"
+        } else {
+            "mihomo"
+        }
     }
 
     fn temp_download_path(&self, version: &str, binary_name: &str) -> PathBuf {
@@ -151,6 +258,25 @@ impl VersionManager {
         Version::parse(raw.trim_start_matches('v')).ok()
     }
 
+    /// Returns the latest stable release if it is newer than the default version.
+    pub async fn latest_newer_than_installed(&self) -> Result<Option<ChannelInfo>> {
+        let latest = fetch_latest(Channel::Stable).await?;
+        self.newer_than_installed(latest).await
+    }
+
+    async fn newer_than_installed(&self, latest: ChannelInfo) -> Result<Option<ChannelInfo>> {
+        let installed = self.get_default().await?;
+        Ok(Self::is_newer_version(&latest.version, &installed).then_some(latest))
+    }
+
+    /// Semver comparison ignoring a leading `v`; non-semver names are never newer.
+    pub fn is_newer_version(candidate: &str, current: &str) -> bool {
+        match (Self::parse_semver(candidate), Self::parse_semver(current)) {
+            (Some(candidate), Some(current)) => candidate > current,
+            _ => false,
+        }
+    }
+
     pub async fn set_default(&self, version: &str) -> Result<()> {
         validate_version_name(version).map_err(|_| {
             MihomoError::version_with_code(
@@ -234,11 +360,7 @@ impl VersionManager {
             default
         };
 
-        let binary_name = if cfg!(windows) {
-            "mihomo.exe"
-        } else {
-            "mihomo"
-        };
+        let binary_name = Self::binary_name();
 
         let path = self.install_dir.join(&version).join(binary_name);
         if !path.exists() {
@@ -283,6 +405,7 @@ impl VersionManager {
 #[cfg(test)]
 mod tests {
     use super::VersionManager;
+    use crate::version::{Channel, ChannelInfo};
     use std::path::Path;
     use std::path::PathBuf;
     use tempfile::tempdir;
@@ -317,6 +440,46 @@ mod tests {
         assert!(VersionManager::parse_semver("v1").is_none());
     }
 
+    #[test]
+    fn test_is_newer_version_compares_numerically() {
+        assert!(VersionManager::is_newer_version("v1.18.10", "v1.18.1"));
+        assert!(!VersionManager::is_newer_version("v1.18.1", "v1.18.10"));
+        assert!(!VersionManager::is_newer_version("v1.18.1", "1.18.1"));
+        assert!(VersionManager::is_newer_version("v1.19.0", "v1.19.0-alpha"));
+        assert!(!VersionManager::is_newer_version("v1.19.0", "snapshot"));
+    }
+
+    #[tokio::test]
+    async fn test_newer_than_installed_uses_default_version() {
+        let temp = tempdir().expect("create temp dir");
+        let vm = VersionManager::with_home(temp.path().to_path_buf())
+            .expect("version manager should be created");
+        fs::create_dir_all(vm.install_dir.join("v1.18.1"))
+            .await
+            .expect("create version dir");
+        vm.set_default("v1.18.1")
+            .await
+            .expect("set default version");
+
+        let release = |version: &str| ChannelInfo {
+            channel: Channel::Stable,
+            version: version.to_string(),
+            release_date: String::new(),
+        };
+
+        let newer = vm
+            .newer_than_installed(release("v1.18.10"))
+            .await
+            .expect("compare versions");
+        assert_eq!(newer.map(|info| info.version).as_deref(), Some("v1.18.10"));
+
+        let same = vm
+            .newer_than_installed(release("v1.18.1"))
+            .await
+            .expect("compare versions");
+        assert!(same.is_none());
+    }
+
     #[tokio::test]
     async fn test_list_installed_uses_semver_order() {
         let temp = tempdir().expect("create temp dir");
@@ -400,6 +563,54 @@ mod tests {
         assert!(Path::new(&existing).exists());
     }
 
+    #[tokio::test]
+    async fn test_is_usable_binary_rejects_missing_and_empty_files() {
+        let temp = tempdir().expect("create temp dir");
+        let missing = temp.path().join("missing");
+        assert!(!VersionManager::is_usable_binary(&missing).await);
+
+        let empty = temp.path().join("empty");
+        fs::write(&empty, b"").await.expect("write empty binary");
+        assert!(!VersionManager::is_usable_binary(&empty).await);
+
+        let binary = temp.path().join("mihomo");
+        fs::write(&binary, b"fake-binary")
+            .await
+            .expect("write fake binary");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o644))
+                .await
+                .expect("clear exec bits");
+            assert!(!VersionManager::is_usable_binary(&binary).await);
+            fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755))
+                .await
+                .expect("set exec bits");
+        }
+        assert!(VersionManager::is_usable_binary(&binary).await);
+    }
+
+    #[tokio::test]
+    async fn test_replace_file_swaps_existing_binary() {
+        let temp = tempdir().expect("create temp dir");
+        let staged = temp.path().join(".mihomo.downloading");
+        let dest = temp.path().join("mihomo");
+        fs::write(&dest, b"old-binary")
+            .await
+            .expect("write old binary");
+        fs::write(&staged, b"new-binary")
+            .await
+            .expect("write staged binary");
+
+        VersionManager::replace_file(&staged, &dest)
+            .await
+            .expect("replace binary");
+
+        assert_eq!(fs::read(&dest).await.expect("read binary"), b"new-binary");
+        assert!(!staged.exists());
+    }
+
     #[tokio::test]
     async fn test_set_get_default_and_binary_path_roundtrip() {
         let temp = tempdir().expect("create temp dir");
@@ -410,11 +621,7 @@ mod tests {
         fs::create_dir_all(&version_dir)
             .await
             .expect("create version directory");
-        let binary_name = if cfg!(windows) {
-            "mihomo.exe"
-        } else {
-            "mihomo"
-        };
+        let binary_name = VersionManager::binary_name();
         let binary_path = version_dir.join(binary_name);
         fs::write(&binary_path, b"fake-binary")
             .await
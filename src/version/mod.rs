@@ -2,6 +2,8 @@ pub mod channel;
 pub mod download;
 pub mod manager;
 
-pub use channel::{fetch_latest, fetch_releases, Channel, ChannelInfo, ReleaseInfo};
-pub use download::Downloader;
+pub use channel::{
+    fetch_latest, fetch_releases, fetch_releases_by_channel, Channel, ChannelInfo, ReleaseInfo,
+};
+pub use download::{Downloader, InstallEvent};
 pub use manager::{VersionInfo, VersionManager};
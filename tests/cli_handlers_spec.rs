@@ -4,7 +4,7 @@ mod common;
 
 use mihomo_rs::cli::{
     run_cli_command, run_cli_command_with_exit, Commands, ConfigAction, ConnectionAction,
-    DoctorAction, ProxyAction, ServiceAction, VersionAction,
+    ConnectionColumn, ConnectionSort, DoctorAction, ProxyAction, ServiceAction, VersionAction,
 };
 use mihomo_rs::service::process;
 use mihomo_rs::{ConfigManager, VersionManager};
@@ -194,10 +194,19 @@ async fn run_cli_command_covers_config_version_and_service_paths() {
     run_cli_command(Commands::Config {
         action: ConfigAction::Show {
             profile: Some("default".to_string()),
+            anonymize: false,
         },
     })
     .await
     .expect("config show");
+    run_cli_command(Commands::Config {
+        action: ConfigAction::Show {
+            profile: Some("default".to_string()),
+            anonymize: true,
+        },
+    })
+    .await
+    .expect("config show --anonymize");
     run_cli_command(Commands::Config {
         action: ConfigAction::Use {
             profile: "alt".to_string(),
@@ -363,6 +372,14 @@ async fn run_cli_command_covers_proxy_connection_and_memory_paths() {
     let proxies_payload = common::mock_proxies_payload();
     let connections_payload = common::mock_connections_payload();
 
+    let mock_version = server
+        .mock("GET", "/version")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"version":"v1.19.0"}"#)
+        .expect(6)
+        .create_async()
+        .await;
     let mock_get_proxies = server
         .mock("GET", "/proxies")
         .with_status(200)
@@ -477,6 +494,13 @@ async fn run_cli_command_covers_proxy_connection_and_memory_paths() {
         action: ConnectionAction::List {
             host: None,
             process: None,
+            sort: Some(ConnectionSort::Download),
+            columns: vec![
+                ConnectionColumn::Host,
+                ConnectionColumn::Rule,
+                ConnectionColumn::Duration,
+                ConnectionColumn::Download,
+            ],
         },
     })
     .await
@@ -490,6 +514,8 @@ async fn run_cli_command_covers_proxy_connection_and_memory_paths() {
         action: ConnectionAction::List {
             host: Some("example".to_string()),
             process: None,
+            sort: None,
+            columns: vec![],
         },
     })
     .await
@@ -498,6 +524,8 @@ async fn run_cli_command_covers_proxy_connection_and_memory_paths() {
         action: ConnectionAction::List {
             host: None,
             process: Some("curl".to_string()),
+            sort: None,
+            columns: vec![],
         },
     })
     .await
@@ -556,6 +584,7 @@ async fn run_cli_command_covers_proxy_connection_and_memory_paths() {
         .expect("memory command");
 
     mock_get_proxies.assert_async().await;
+    mock_version.assert_async().await;
     mock_switch.assert_async().await;
     mock_delay_hk.assert_async().await;
     mock_delay_jp.assert_async().await;
@@ -613,9 +642,12 @@ async fn run_cli_command_covers_logs_traffic_and_version_network_error_paths() {
     .await
     .is_err());
     assert!(run_cli_command(Commands::Update).await.is_err());
-    assert!(run_cli_command(Commands::ListRemote { limit: 1 })
-        .await
-        .is_err());
+    assert!(run_cli_command(Commands::ListRemote {
+        limit: 1,
+        channel: None,
+    })
+    .await
+    .is_err());
 
     if let Some(value) = old_home {
         env::set_var("MIHOMO_HOME", value);
@@ -745,6 +777,8 @@ async fn run_cli_command_covers_connection_stream_and_empty_branches() {
         action: ConnectionAction::List {
             host: None,
             process: None,
+            sort: None,
+            columns: vec![],
         },
     })
     .await
@@ -784,6 +818,8 @@ async fn run_cli_command_covers_connection_stream_and_empty_branches() {
         action: ConnectionAction::List {
             host: None,
             process: None,
+            sort: None,
+            columns: vec![],
         },
     })
     .await
@@ -959,7 +995,10 @@ async fn run_cli_command_covers_config_and_proxy_empty_branches() {
         .await
         .expect("write invalid config.toml");
     assert!(run_cli_command(Commands::Config {
-        action: ConfigAction::Show { profile: None },
+        action: ConfigAction::Show {
+            profile: None,
+            anonymize: false,
+        },
     })
     .await
     .is_err());
@@ -979,6 +1018,14 @@ async fn run_cli_command_covers_config_and_proxy_empty_branches() {
     cm.set_current("default")
         .await
         .expect("set default profile");
+    let mock_version = server
+        .mock("GET", "/version")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"version":"v1.19.0"}"#)
+        .expect(3)
+        .create_async()
+        .await;
     let empty_proxies = r#"{"proxies":{}}"#;
     let mock_proxies = server
         .mock("GET", "/proxies")
@@ -1006,6 +1053,7 @@ async fn run_cli_command_covers_config_and_proxy_empty_branches() {
     .expect("proxy current empty");
 
     mock_proxies.assert_async().await;
+    mock_version.assert_async().await;
 
     if let Some(value) = old_home {
         env::set_var("MIHOMO_HOME", value);
@@ -1,8 +1,10 @@
 mod common;
 
 use futures_util::StreamExt;
+use mihomo_rs::core::{DelayOutcome, HealthReport, RequestTrace};
 use mihomo_rs::{MihomoClient, MihomoError};
 use mockito::{Matcher, Server};
+use std::sync::{Arc, Mutex};
 use tokio::net::TcpListener;
 use tokio_tungstenite::{accept_async, tungstenite::Message as WsMessage};
 
@@ -124,6 +126,330 @@ async fn reload_config_with_path_sends_json_body() {
     reload_with_path.assert_async().await;
 }
 
+#[tokio::test]
+async fn reload_config_inline_sends_payload() {
+    let mut server = Server::new_async().await;
+
+    let inline = server
+        .mock("PUT", "/configs")
+        .match_query(Matcher::UrlEncoded("force".into(), "true".into()))
+        .match_body(Matcher::JsonString(
+            r#"{"payload":"mode: direct\n"}"#.to_string(),
+        ))
+        .with_status(204)
+        .create_async()
+        .await;
+
+    let client = MihomoClient::new(&server.url(), None).expect("create client");
+    client
+        .reload_config_inline("mode: direct\n")
+        .await
+        .expect("reload inline");
+
+    inline.assert_async().await;
+}
+
+#[tokio::test]
+async fn rotate_secret_rewrites_config_and_verifies_new_secret() {
+    let mut server = Server::new_async().await;
+    let temp = tempfile::tempdir().expect("tempdir");
+    let config_path = temp.path().join("config.yaml");
+    let path = config_path.to_str().unwrap().to_string();
+    std::fs::write(&config_path, "mixed-port: 7890\nsecret: old\n").expect("write config");
+
+    let reload = server
+        .mock("PUT", "/configs")
+        .match_query(Matcher::UrlEncoded("force".into(), "true".into()))
+        .match_header("authorization", "Bearer old")
+        .match_body(Matcher::Json(serde_json::json!({ "path": path })))
+        .with_status(204)
+        .create_async()
+        .await;
+    let verify = server
+        .mock("GET", "/version")
+        .match_header("authorization", "Bearer new")
+        .with_status(200)
+        .with_body(r#"{"version":"v1.19.2","meta":true}"#)
+        .create_async()
+        .await;
+
+    let client = MihomoClient::new(&server.url(), Some("old".into())).expect("create client");
+    let rotated = client.rotate_secret(&path, "new").await.expect("rotate");
+    rotated
+        .get_version()
+        .await
+        .expect("new client authenticates");
+
+    let config: serde_yaml::Value =
+        serde_yaml::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    assert_eq!(config["secret"].as_str(), Some("new"));
+    assert_eq!(config["mixed-port"].as_u64(), Some(7890));
+    reload.assert_async().await;
+    verify.expect_at_least(1).assert_async().await;
+}
+
+#[tokio::test]
+async fn rotate_secret_restores_config_when_reload_fails() {
+    let mut server = Server::new_async().await;
+    let temp = tempfile::tempdir().expect("tempdir");
+    let config_path = temp.path().join("config.yaml");
+    let original = "secret: old\n";
+    std::fs::write(&config_path, original).expect("write config");
+
+    let _reload = server
+        .mock("PUT", "/configs")
+        .match_query(Matcher::Any)
+        .with_status(500)
+        .with_body("boom")
+        .expect_at_least(1)
+        .create_async()
+        .await;
+
+    let client = MihomoClient::new(&server.url(), Some("old".into())).expect("create client");
+    let Err(err) = client
+        .rotate_secret(config_path.to_str().unwrap(), "new")
+        .await
+    else {
+        panic!("rotation should fail");
+    };
+
+    assert!(matches!(err, MihomoError::Service(_)));
+    assert_eq!(std::fs::read_to_string(&config_path).unwrap(), original);
+    assert!(client
+        .rotate_secret(config_path.to_str().unwrap(), "")
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn upgrade_endpoints_use_post() {
+    let mut server = Server::new_async().await;
+    let core_mock = server
+        .mock("POST", "/upgrade")
+        .match_header("authorization", "Bearer secret")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"status":"ok"}"#)
+        .create_async()
+        .await;
+    let ui_mock = server
+        .mock("POST", "/upgrade/ui")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"status":"ok"}"#)
+        .create_async()
+        .await;
+    let client =
+        MihomoClient::new(&server.url(), Some("secret".to_string())).expect("create client");
+
+    client.upgrade_core().await.expect("upgrade core");
+    client.upgrade_ui().await.expect("upgrade ui");
+
+    core_mock.assert_async().await;
+    ui_mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn get_rule_stats_totals_ruleset_sizes() {
+    let mut server = Server::new_async().await;
+    let rules_mock = server
+        .mock("GET", "/rules")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"rules":[
+                {"type":"RuleSet","payload":"ads","proxy":"REJECT","size":300},
+                {"type":"RuleSet","payload":"cn","proxy":"DIRECT","size":700},
+                {"type":"Match","payload":"","proxy":"Proxy","size":-1}
+            ]}"#,
+        )
+        .create_async()
+        .await;
+    let client = MihomoClient::new(&server.url(), None).expect("create client");
+
+    let stats = client.get_rule_stats().await.expect("rule stats");
+
+    rules_mock.assert_async().await;
+    assert_eq!(stats.total, 3);
+    assert_eq!(stats.rulesets, 2);
+    assert_eq!(stats.ruleset_entries, 1000);
+    assert_eq!(stats.largest_rulesets[0], ("cn".to_string(), 700));
+}
+
+#[tokio::test]
+async fn request_observer_sees_method_path_and_status() {
+    let mut server = Server::new_async().await;
+    let version_mock = server
+        .mock("GET", "/version")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"version":"v1.19.0"}"#)
+        .create_async()
+        .await;
+    let missing_mock = server
+        .mock("GET", "/proxies/missing")
+        .with_status(404)
+        .create_async()
+        .await;
+
+    let traces = Arc::new(Mutex::new(Vec::new()));
+    let sink = traces.clone();
+    let client = MihomoClient::new(&server.url(), None)
+        .expect("create client")
+        .with_request_observer(Arc::new(move |trace: RequestTrace| {
+            sink.lock().unwrap().push(trace);
+        }));
+
+    client.get_version().await.expect("version");
+    client.get_proxy("missing").await.expect_err("404");
+
+    version_mock.assert_async().await;
+    missing_mock.assert_async().await;
+    let traces = traces.lock().unwrap();
+    assert_eq!(traces.len(), 2);
+    assert_eq!(traces[0].method, "GET");
+    assert_eq!(traces[0].path, "/version");
+    assert_eq!(traces[0].status, Some(200));
+    assert!(traces[0].error.is_none());
+    assert_eq!(traces[1].path, "/proxies/missing");
+    assert_eq!(traces[1].status, Some(404));
+    assert!(traces[1].error.is_some());
+}
+
+#[tokio::test]
+async fn custom_user_agent_and_headers_are_sent() {
+    let mut server = Server::new_async().await;
+    let default_mock = server
+        .mock("GET", "/memory")
+        .match_header("user-agent", "mihomo-rs")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"inuse":1,"oslimit":2}"#)
+        .create_async()
+        .await;
+    let custom_mock = server
+        .mock("GET", "/version")
+        .match_header("user-agent", "my-dashboard/2.0")
+        .match_header("x-gateway-key", "abc")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"version":"v1.19.0"}"#)
+        .create_async()
+        .await;
+
+    let client = MihomoClient::new(&server.url(), None).expect("create client");
+    client.get_memory().await.expect("memory");
+
+    let client = client
+        .with_user_agent("my-dashboard/2.0")
+        .with_header("X-Gateway-Key", "abc");
+    client.get_version().await.expect("version");
+
+    default_mock.assert_async().await;
+    custom_mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn get_service_config_lists_active_ports() {
+    let mut server = Server::new_async().await;
+    let configs_mock = server
+        .mock("GET", "/configs")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"port":0,"socks-port":7891,"mixed-port":7890,"mode":"global","tun":{"enable":false}}"#)
+        .create_async()
+        .await;
+    let client = MihomoClient::new(&server.url(), None).expect("create client");
+
+    let config = client.get_service_config().await.expect("configs");
+
+    configs_mock.assert_async().await;
+    assert_eq!(config.mode, "global");
+    assert_eq!(
+        config.active_ports(),
+        vec![("socks".to_string(), 7891), ("mixed".to_string(), 7890)]
+    );
+}
+
+#[tokio::test]
+async fn lenient_proxies_report_entries_that_fail_to_parse() {
+    let mut server = Server::new_async().await;
+    let proxies_mock = server
+        .mock("GET", "/proxies")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"proxies":{
+                "HK-01":{"type":"Shadowsocks","history":[]},
+                "weird":{"history":[]},
+                "GLOBAL":{"type":"Selector","now":"HK-01","all":["HK-01"]}
+            }}"#,
+        )
+        .expect(2)
+        .create_async()
+        .await;
+    let client = MihomoClient::new(&server.url(), None).expect("create client");
+
+    let parsed = client.get_proxies_lenient().await.expect("lenient proxies");
+    assert_eq!(parsed.proxies.len(), 2);
+    assert_eq!(parsed.skipped.len(), 1);
+    assert_eq!(parsed.skipped[0].0, "weird");
+
+    let err = client.get_proxies().await.expect_err("strict fetch fails");
+    assert!(err.to_string().contains("'weird'"));
+
+    proxies_mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn delay_outcome_separates_dead_nodes_from_api_errors() {
+    let mut server = Server::new_async().await;
+    let mut mocks = vec![];
+    for (proxy, status, body) in [
+        ("fast", 200, r#"{"delay":42}"#),
+        ("slow", 504, r#"{"message":"Timeout"}"#),
+        (
+            "dead",
+            503,
+            r#"{"message":"An error occurred in the delay test"}"#,
+        ),
+        ("missing", 404, r#"{"message":"Resource not found"}"#),
+    ] {
+        mocks.push(
+            server
+                .mock("GET", format!("/proxies/{}/delay", proxy).as_str())
+                .match_query(Matcher::Any)
+                .with_status(status)
+                .with_header("content-type", "application/json")
+                .with_body(body)
+                .create_async()
+                .await,
+        );
+    }
+    let client = MihomoClient::new(&server.url(), None).expect("create client");
+    let url = "http://www.gstatic.com/generate_204";
+
+    assert_eq!(
+        client.test_delay_outcome("fast", url, 1000).await,
+        DelayOutcome::Ok(42)
+    );
+    assert_eq!(
+        client.test_delay_outcome("slow", url, 1000).await,
+        DelayOutcome::Timeout
+    );
+    assert_eq!(
+        client.test_delay_outcome("dead", url, 1000).await,
+        DelayOutcome::Unreachable
+    );
+    let missing = client.test_delay_outcome("missing", url, 1000).await;
+    assert!(matches!(missing, DelayOutcome::ApiError(_)));
+    assert!(!missing.is_dead());
+
+    for mock in mocks {
+        mock.assert_async().await;
+    }
+}
+
 #[tokio::test]
 async fn memory_and_connection_endpoints_work() {
     let mut server = Server::new_async().await;
@@ -501,3 +827,152 @@ async fn websocket_binary_messages_are_ignored_for_traffic_and_connections() {
             .is_none()
     );
 }
+
+#[tokio::test]
+async fn wait_until_ready_retries_until_controller_answers() {
+    let mut server = Server::new_async().await;
+    let not_ready = server
+        .mock("GET", "/version")
+        .with_status(503)
+        .expect_at_least(2)
+        .create_async()
+        .await;
+
+    let client = MihomoClient::new(&server.url(), None).expect("create client");
+    let err = client
+        .wait_until_ready(std::time::Duration::from_millis(150))
+        .await
+        .expect_err("controller never became ready");
+    assert!(err.to_string().contains("Controller not ready"));
+    not_ready.assert_async().await;
+
+    server
+        .mock("GET", "/version")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"version":"v1.19.0"}"#)
+        .create_async()
+        .await;
+    let version = client
+        .wait_until_ready(std::time::Duration::from_secs(2))
+        .await
+        .expect("ready");
+    assert_eq!(version.version, "v1.19.0");
+}
+
+#[tokio::test]
+async fn dropping_idle_stream_closes_websocket_promptly() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+    let addr = listener.local_addr().expect("addr");
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.expect("accept");
+        let mut ws = accept_async(stream).await.expect("accept ws");
+        // Never send anything, like a log stream with nothing to report.
+        ws.next().await
+    });
+
+    let client = MihomoClient::new(&format!("http://{}", addr), None).expect("client");
+    let rx = client.stream_logs(None).await.expect("stream logs");
+    drop(rx);
+
+    let seen = tokio::time::timeout(std::time::Duration::from_secs(2), server)
+        .await
+        .expect("server should see the client hang up")
+        .expect("server task");
+    assert!(
+        matches!(seen, None | Some(Ok(WsMessage::Close(_))) | Some(Err(_))),
+        "unexpected message: {:?}",
+        seen
+    );
+}
+
+#[tokio::test]
+async fn patch_service_config_sends_only_set_fields() {
+    let mut server = Server::new_async().await;
+    let patch_mock = server
+        .mock("PATCH", "/configs")
+        .match_body(Matcher::Json(serde_json::json!({ "allow-lan": true })))
+        .with_status(204)
+        .create_async()
+        .await;
+    let get_mock = server
+        .mock("GET", "/configs")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"mixed-port":7890,"mode":"rule","allow-lan":true}"#)
+        .expect(2)
+        .create_async()
+        .await;
+
+    let client = MihomoClient::new(&server.url(), None).expect("create client");
+    let patch = mihomo_rs::core::ServiceConfigPatch {
+        allow_lan: Some(true),
+        ..Default::default()
+    };
+    let merged = client.patch_service_config(&patch).await.expect("patch");
+    assert!(merged.allow_lan);
+    assert_eq!(merged.mixed_port, 7890);
+    assert_eq!(merged.mode, "rule");
+
+    // An empty patch sends nothing and just reports the current settings.
+    client
+        .patch_service_config(&Default::default())
+        .await
+        .expect("empty patch");
+    patch_mock.assert_async().await;
+    get_mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn healthcheck_summarises_reachable_controller() {
+    let mut server = Server::new_async().await;
+    let _version = server
+        .mock("GET", "/version")
+        .with_status(200)
+        .with_body(r#"{"version":"v1.19.2","meta":true}"#)
+        .create_async()
+        .await;
+    let _connections = server
+        .mock("GET", "/connections")
+        .with_status(200)
+        .with_body(r#"{"downloadTotal":0,"uploadTotal":0,"connections":[]}"#)
+        .create_async()
+        .await;
+    let _providers = server
+        .mock("GET", "/providers/proxies")
+        .with_status(200)
+        .with_body(
+            r#"{"providers":{
+                "default":{"name":"default","type":"Proxy","vehicleType":"Compatible","proxies":[]},
+                "sub":{"name":"sub","type":"Proxy","vehicleType":"HTTP","proxies":[]}
+            }}"#,
+        )
+        .create_async()
+        .await;
+    let _pprof = server
+        .mock("GET", "/debug/pprof/goroutine")
+        .match_query(Matcher::UrlEncoded("debug".into(), "1".into()))
+        .with_status(200)
+        .with_body("goroutine profile: total 42\n1 @ 0x1\n")
+        .create_async()
+        .await;
+
+    let client = MihomoClient::new(&server.url(), None).expect("client");
+    let report = client.healthcheck().await;
+
+    assert!(report.api_reachable);
+    assert_eq!(report.version.as_deref(), Some("v1.19.2"));
+    assert_eq!(report.goroutines, Some(42));
+    assert_eq!(report.connection_count, Some(0));
+    assert_eq!(report.memory_pct, None);
+    assert_eq!(report.failing_providers, vec!["sub".to_string()]);
+    assert!(!report.is_healthy());
+}
+
+#[tokio::test]
+async fn healthcheck_reports_unreachable_controller() {
+    let client = MihomoClient::new("http://127.0.0.1:1", None).expect("client");
+    let report = client.healthcheck().await;
+    assert_eq!(report, HealthReport::default());
+    assert!(!report.api_reachable);
+}
@@ -1,10 +1,14 @@
 mod common;
 
 use common::{config_without_controller, default_test_config, setup_temp_home, temp_home_path};
-use mihomo_rs::{ConfigManager, MihomoError};
+use mihomo_rs::config::WatchOptions;
+use mihomo_rs::{ConfigManager, MihomoClient, MihomoError};
+use mockito::{Matcher, Server};
 use std::sync::OnceLock;
+use std::time::Duration;
 use tokio::fs;
-use tokio::sync::Mutex;
+use tokio::sync::{mpsc, Mutex};
+use tokio_util::sync::CancellationToken;
 
 fn env_lock() -> &'static Mutex<()> {
     static LOCK: OnceLock<Mutex<()>> = OnceLock::new();
@@ -402,3 +406,444 @@ async fn special_character_configs_dir_roundtrips_and_updates_current_path() {
         Some(special_path)
     );
 }
+
+#[tokio::test]
+async fn export_and_import_profile_carries_provider_caches() {
+    let _guard = env_lock().lock().await;
+
+    let source_temp = setup_temp_home();
+    let source = ConfigManager::with_home(temp_home_path(&source_temp)).expect("source manager");
+    let profile = format!(
+        "{}proxy-providers:\n  sub:\n    type: http\n    url: https://example.com/sub\n    path: ./providers/sub.yaml\n",
+        default_test_config()
+    );
+    source.save("laptop", &profile).await.expect("save profile");
+    let providers_dir = temp_home_path(&source_temp)
+        .join("configs")
+        .join("providers");
+    fs::create_dir_all(&providers_dir)
+        .await
+        .expect("create providers dir");
+    fs::write(providers_dir.join("sub.yaml"), "proxies: []\n")
+        .await
+        .expect("write provider cache");
+
+    let archive = source_temp.path().join("laptop.zip");
+    source
+        .export_profile("laptop", &archive)
+        .await
+        .expect("export profile");
+
+    let target_temp = setup_temp_home();
+    let target = ConfigManager::with_home(temp_home_path(&target_temp)).expect("target manager");
+    let name = target
+        .import_profile(&archive)
+        .await
+        .expect("import profile");
+
+    assert_eq!(name, "laptop");
+    assert_eq!(target.load("laptop").await.expect("load imported"), profile);
+    let cache = temp_home_path(&target_temp)
+        .join("configs")
+        .join("providers")
+        .join("sub.yaml");
+    assert_eq!(
+        fs::read_to_string(cache).await.expect("read cache"),
+        "proxies: []\n"
+    );
+
+    let err = target
+        .import_profile(&archive)
+        .await
+        .expect_err("second import should not overwrite");
+    assert!(matches!(err, MihomoError::Config(_)));
+}
+
+#[tokio::test]
+async fn import_profile_refuses_to_overwrite_existing_provider_cache() {
+    let _guard = env_lock().lock().await;
+
+    let source_temp = setup_temp_home();
+    let source = ConfigManager::with_home(temp_home_path(&source_temp)).expect("source manager");
+    source
+        .save(
+            "laptop",
+            &format!(
+                "{}proxy-providers:\n  sub:\n    type: http\n    url: https://example.com/sub\n    path: ./providers/sub.yaml\n",
+                default_test_config()
+            ),
+        )
+        .await
+        .expect("save profile");
+    let source_providers = temp_home_path(&source_temp)
+        .join("configs")
+        .join("providers");
+    fs::create_dir_all(&source_providers)
+        .await
+        .expect("create providers dir");
+    fs::write(source_providers.join("sub.yaml"), "proxies: []\n")
+        .await
+        .expect("write provider cache");
+    let archive = source_temp.path().join("laptop.zip");
+    source
+        .export_profile("laptop", &archive)
+        .await
+        .expect("export profile");
+
+    let target_temp = setup_temp_home();
+    let target = ConfigManager::with_home(temp_home_path(&target_temp)).expect("target manager");
+    let target_providers = temp_home_path(&target_temp)
+        .join("configs")
+        .join("providers");
+    fs::create_dir_all(&target_providers)
+        .await
+        .expect("create target providers dir");
+    fs::write(target_providers.join("sub.yaml"), "proxies: [existing]\n")
+        .await
+        .expect("write existing cache");
+
+    let err = target
+        .import_profile(&archive)
+        .await
+        .expect_err("import should not overwrite another profile's cache");
+    assert!(err.to_string().contains("providers/sub.yaml"), "{}", err);
+    assert_eq!(
+        fs::read_to_string(target_providers.join("sub.yaml"))
+            .await
+            .expect("read cache"),
+        "proxies: [existing]\n"
+    );
+    assert!(target.load("laptop").await.is_err());
+}
+
+fn watch_channel() -> (
+    impl FnMut(&serde_yaml::Value) + Send + 'static,
+    mpsc::UnboundedReceiver<serde_yaml::Value>,
+) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    (
+        move |config: &serde_yaml::Value| {
+            let _ = tx.send(config.clone());
+        },
+        rx,
+    )
+}
+
+async fn next_change(
+    changes: &mut mpsc::UnboundedReceiver<serde_yaml::Value>,
+) -> serde_yaml::Value {
+    tokio::time::timeout(Duration::from_secs(2), changes.recv())
+        .await
+        .expect("change should be observed")
+        .expect("watch channel open")
+}
+
+#[tokio::test]
+async fn watch_calls_back_on_change_and_skips_invalid_yaml() {
+    let _guard = env_lock().lock().await;
+
+    let temp = setup_temp_home();
+    let manager = ConfigManager::with_home(temp_home_path(&temp)).expect("create config manager");
+    manager
+        .save("default", &default_test_config())
+        .await
+        .expect("save profile");
+    let path = manager.get_current_path().await.expect("current path");
+
+    let (on_change, mut changes) = watch_channel();
+    let cancel = CancellationToken::new();
+    let watcher = manager
+        .watch(
+            WatchOptions::default().with_interval(Duration::from_millis(20)),
+            cancel.clone(),
+            on_change,
+        )
+        .await
+        .expect("start watch");
+
+    fs::write(&path, "port: [unclosed\n")
+        .await
+        .expect("write invalid config");
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    fs::write(&path, "port: 7999\n")
+        .await
+        .expect("write new config");
+
+    let config = next_change(&mut changes).await;
+    assert_eq!(config.get("port").and_then(|v| v.as_u64()), Some(7999));
+
+    cancel.cancel();
+    watcher.await.expect("watcher stops");
+}
+
+#[tokio::test]
+async fn watch_follows_profile_switches() {
+    let _guard = env_lock().lock().await;
+
+    let temp = setup_temp_home();
+    let manager = ConfigManager::with_home(temp_home_path(&temp)).expect("create config manager");
+    manager
+        .save("default", &default_test_config())
+        .await
+        .expect("save default");
+    manager
+        .save("work", "port: 7001\n")
+        .await
+        .expect("save work");
+
+    let (on_change, mut changes) = watch_channel();
+    let cancel = CancellationToken::new();
+    let _watcher = manager
+        .watch(
+            WatchOptions::default().with_interval(Duration::from_millis(20)),
+            cancel.clone(),
+            on_change,
+        )
+        .await
+        .expect("start watch");
+
+    manager.set_current("work").await.expect("switch profile");
+    let config = next_change(&mut changes).await;
+    assert_eq!(config.get("port").and_then(|v| v.as_u64()), Some(7001));
+
+    let work = manager.get_current_path().await.expect("current path");
+    fs::write(&work, "port: 7002\n")
+        .await
+        .expect("edit work profile");
+    let config = next_change(&mut changes).await;
+    assert_eq!(config.get("port").and_then(|v| v.as_u64()), Some(7002));
+    cancel.cancel();
+}
+
+#[tokio::test]
+async fn watch_with_reload_loads_changes_into_running_core() {
+    let _guard = env_lock().lock().await;
+
+    let temp = setup_temp_home();
+    let manager = ConfigManager::with_home(temp_home_path(&temp)).expect("create config manager");
+    manager
+        .save("default", &default_test_config())
+        .await
+        .expect("save profile");
+    let path = manager.get_current_path().await.expect("current path");
+
+    let mut server = Server::new_async().await;
+    let reload = server
+        .mock("PUT", "/configs")
+        .match_query(Matcher::Any)
+        .match_body(Matcher::PartialJson(
+            serde_json::json!({ "path": path.to_str().unwrap() }),
+        ))
+        .with_status(204)
+        .expect(1)
+        .create_async()
+        .await;
+    let client = MihomoClient::new(&server.url(), None).expect("client");
+
+    let (on_change, mut changes) = watch_channel();
+    let cancel = CancellationToken::new();
+    let watcher = manager
+        .watch(
+            WatchOptions::default()
+                .with_interval(Duration::from_millis(20))
+                .with_reload(client),
+            cancel.clone(),
+            on_change,
+        )
+        .await
+        .expect("start watch");
+
+    fs::write(&path, "port: 7999\n")
+        .await
+        .expect("write new config");
+    next_change(&mut changes).await;
+    // The reload follows the callback; give it a few polls to land.
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    cancel.cancel();
+    watcher.await.expect("watcher stops");
+    reload.assert_async().await;
+}
+
+#[tokio::test]
+async fn load_resolved_merges_included_rule_files() {
+    let _guard = env_lock().lock().await;
+
+    let temp = setup_temp_home();
+    let manager = ConfigManager::with_home(temp_home_path(&temp)).expect("create config manager");
+    manager
+        .save(
+            "split",
+            &format!(
+                "{}include:\n  - rules/streaming.yaml\nrules:\n  - MATCH,DIRECT\n",
+                default_test_config()
+            ),
+        )
+        .await
+        .expect("save profile");
+    let rules_dir = temp_home_path(&temp).join("configs").join("rules");
+    fs::create_dir_all(&rules_dir)
+        .await
+        .expect("create rules dir");
+    fs::write(
+        rules_dir.join("streaming.yaml"),
+        "rules:\n  - DOMAIN-SUFFIX,netflix.com,Streaming\n",
+    )
+    .await
+    .expect("write included rules");
+
+    let config = manager.load_resolved("split").await.expect("load resolved");
+
+    let rules: Vec<&str> = config["rules"]
+        .as_sequence()
+        .expect("rules list")
+        .iter()
+        .filter_map(|rule| rule.as_str())
+        .collect();
+    assert_eq!(
+        rules,
+        vec!["DOMAIN-SUFFIX,netflix.com,Streaming", "MATCH,DIRECT"]
+    );
+    assert!(config.get("include").is_none());
+    assert!(external_controller_of(&serde_yaml::to_string(&config).unwrap()).is_some());
+}
+
+#[tokio::test]
+async fn set_group_test_url_patches_named_group() {
+    let _guard = env_lock().lock().await;
+
+    let temp = setup_temp_home();
+    let manager = ConfigManager::with_home(temp_home_path(&temp)).expect("create config manager");
+    manager
+        .save(
+            "groups",
+            &format!(
+                "{}proxy-groups:\n  - name: Auto\n    type: url-test\n    url: http://old.example/204\n    interval: 300\n  - name: Manual\n    type: select\n",
+                default_test_config()
+            ),
+        )
+        .await
+        .expect("save profile");
+
+    manager
+        .set_group_test_url("groups", "Auto", "http://cp.cloudflare.com", Some(60))
+        .await
+        .expect("patch group");
+    let err = manager
+        .set_group_test_url("groups", "Missing", "http://cp.cloudflare.com", None)
+        .await
+        .expect_err("unknown group");
+
+    let config: serde_yaml::Value =
+        serde_yaml::from_str(&manager.load("groups").await.expect("load")).expect("yaml");
+    let auto = &config["proxy-groups"][0];
+    assert_eq!(auto["url"].as_str(), Some("http://cp.cloudflare.com"));
+    assert_eq!(auto["interval"].as_u64(), Some(60));
+    assert!(config["proxy-groups"][1].get("url").is_none());
+    assert!(matches!(err, MihomoError::NotFound(_)));
+}
+
+#[tokio::test]
+async fn lint_profile_reports_unreachable_rules() {
+    let _guard = env_lock().lock().await;
+
+    let temp = setup_temp_home();
+    let manager = ConfigManager::with_home(temp_home_path(&temp)).expect("create config manager");
+    manager
+        .save(
+            "messy",
+            &format!(
+                "{}rules:\n  - DOMAIN-SUFFIX,example.com,Proxy\n  - DOMAIN,www.example.com,DIRECT\n  - MATCH,DIRECT\n",
+                default_test_config()
+            ),
+        )
+        .await
+        .expect("save profile");
+
+    let warnings = manager.lint_profile("messy").await.expect("lint");
+
+    assert_eq!(
+        warnings,
+        vec![mihomo_rs::config::LintWarning::Shadowed { index: 1, by: 0 }]
+    );
+}
+
+#[tokio::test]
+async fn config_edits_preserve_unmodelled_sections() {
+    let _guard = env_lock().lock().await;
+
+    let temp = setup_temp_home();
+    let manager = ConfigManager::with_home(temp_home_path(&temp)).expect("create config manager");
+    manager
+        .save(
+            "custom",
+            &format!(
+                "{}tun:\n  enable: true\n  stack: system\n  auto-route: true\nsniffer:\n  enable: true\nhosts:\n  router.lan: 192.168.1.1\nprofile:\n  store-selected: true\nproxy-groups:\n  - name: Auto\n    type: url-test\n",
+                config_without_controller()
+            ),
+        )
+        .await
+        .expect("save profile");
+    manager.set_current("custom").await.expect("set current");
+
+    manager
+        .ensure_external_controller()
+        .await
+        .expect("ensure controller");
+    manager
+        .set_group_test_url("custom", "Auto", "http://cp.cloudflare.com", None)
+        .await
+        .expect("patch group");
+
+    let config: serde_yaml::Value =
+        serde_yaml::from_str(&manager.load("custom").await.expect("load")).expect("yaml");
+    assert_eq!(config["tun"]["stack"].as_str(), Some("system"));
+    assert_eq!(config["tun"]["auto-route"].as_bool(), Some(true));
+    assert_eq!(config["sniffer"]["enable"].as_bool(), Some(true));
+    assert_eq!(config["hosts"]["router.lan"].as_str(), Some("192.168.1.1"));
+    assert_eq!(config["profile"]["store-selected"].as_bool(), Some(true));
+}
+
+#[tokio::test]
+async fn set_tun_validates_and_keeps_other_tun_keys() {
+    let _guard = env_lock().lock().await;
+
+    let temp = setup_temp_home();
+    let manager = ConfigManager::with_home(temp_home_path(&temp)).expect("create config manager");
+    manager
+        .save(
+            "tun",
+            &format!(
+                "{}tun:\n  enable: false\n  mtu: 9000\n",
+                default_test_config()
+            ),
+        )
+        .await
+        .expect("save profile");
+    assert!(manager
+        .get_sniffer("tun")
+        .await
+        .expect("get sniffer")
+        .is_none());
+
+    let mut tun = manager
+        .get_tun("tun")
+        .await
+        .expect("get tun")
+        .expect("tun section");
+    tun.enable = true;
+    tun.stack = Some("mixed".into());
+    tun.dns_hijack = vec!["any:53".into()];
+    manager.set_tun("tun", &tun).await.expect("set tun");
+
+    tun.stack = Some("bogus".into());
+    let err = manager.set_tun("tun", &tun).await.expect_err("bad stack");
+    assert!(matches!(err, MihomoError::Config(_)));
+
+    let config: serde_yaml::Value =
+        serde_yaml::from_str(&manager.load("tun").await.expect("load")).expect("yaml");
+    assert_eq!(config["tun"]["enable"].as_bool(), Some(true));
+    assert_eq!(config["tun"]["stack"].as_str(), Some("mixed"));
+    assert_eq!(config["tun"]["dns-hijack"][0].as_str(), Some("any:53"));
+    assert_eq!(config["tun"]["mtu"].as_u64(), Some(9000));
+    assert_eq!(config["port"].as_u64(), Some(7890));
+}
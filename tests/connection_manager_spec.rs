@@ -32,6 +32,32 @@ async fn list_get_all_and_statistics_are_consistent() {
     assert_eq!(stats, (4096, 2048, 2));
 }
 
+#[tokio::test]
+async fn top_proxies_by_bandwidth_orders_by_total_bytes() {
+    let mut server = Server::new_async().await;
+    let connections_mock = server
+        .mock("GET", "/connections")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(common::mock_connections_payload())
+        .create_async()
+        .await;
+
+    let client = MihomoClient::new(&server.url(), None).expect("create client");
+    let manager = ConnectionManager::new(client);
+
+    let top = manager
+        .top_proxies_by_bandwidth(1)
+        .await
+        .expect("top proxies");
+
+    connections_mock.assert_async().await;
+    assert_eq!(top.len(), 1);
+    assert_eq!(top[0].proxy, "HK-01");
+    assert_eq!(top[0].total(), 700);
+    assert_eq!(top[0].connections, 1);
+}
+
 #[tokio::test]
 async fn filter_methods_match_expected_connections() {
     let mut server = Server::new_async().await;
@@ -181,3 +207,78 @@ async fn stream_forwards_connection_snapshots() {
         .expect("snapshot item");
     assert_eq!(snapshot.connections.len(), 0);
 }
+
+#[tokio::test]
+async fn matching_rule_resolves_connection_to_configured_rule() {
+    let mut server = Server::new_async().await;
+    let rules_mock = server
+        .mock("GET", "/rules")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"rules":[
+                {"type":"DomainSuffix","payload":"example.com","proxy":"Proxy","size":-1},
+                {"type":"Match","payload":"","proxy":"DIRECT","size":-1}
+            ]}"#,
+        )
+        .create_async()
+        .await;
+    let client = MihomoClient::new(&server.url(), None).expect("create client");
+    let manager = ConnectionManager::new(client);
+    let conn: mihomo_rs::core::Connection = serde_json::from_str(
+        r#"{"id":"c1","rule":"DomainSuffix","rulePayload":"example.com","chains":["HK-01","Proxy"]}"#,
+    )
+    .expect("connection");
+
+    let (index, rule) = manager
+        .matching_rule(&conn)
+        .await
+        .expect("rules")
+        .expect("rule found");
+
+    rules_mock.assert_async().await;
+    assert_eq!(index, 0);
+    assert_eq!(rule.proxy, "Proxy");
+}
+
+#[tokio::test]
+async fn export_json_writes_connections_with_resolved_rules() {
+    let mut server = Server::new_async().await;
+    server
+        .mock("GET", "/connections")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"downloadTotal":20,"uploadTotal":10,"connections":[
+                {"id":"c1","metadata":{"host":"example.com"},"upload":10,"download":20,
+                 "start":"2024-01-01T00:00:00Z","chains":["HK-01","Proxy"],
+                 "rule":"DomainSuffix","rulePayload":"example.com"}
+            ]}"#,
+        )
+        .create_async()
+        .await;
+    server
+        .mock("GET", "/rules")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"rules":[{"type":"DomainSuffix","payload":"example.com","proxy":"Proxy"}]}"#)
+        .create_async()
+        .await;
+    let temp = tempfile::tempdir().expect("tempdir");
+    let path = temp.path().join("connections.json");
+
+    let client = MihomoClient::new(&server.url(), None).expect("create client");
+    let written = ConnectionManager::new(client)
+        .export_json(&path)
+        .await
+        .expect("export");
+
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).expect("read export")).unwrap();
+    assert_eq!(written, 1);
+    assert_eq!(json["uploadTotal"], 10);
+    assert_eq!(json["connections"][0]["id"], "c1");
+    assert_eq!(json["connections"][0]["ruleTarget"], "Proxy");
+    assert!(json["connections"][0]["durationSecs"].as_u64().is_some());
+    assert!(json["exportedAt"].is_string());
+}
@@ -1,6 +1,6 @@
 #[cfg(unix)]
 mod unix_tests {
-    use mihomo_rs::{start_service, stop_service, switch_proxy};
+    use mihomo_rs::{bootstrap, start_service, stop_service, switch_proxy};
     use mockito::{Matcher, Server};
     use std::env;
     use std::os::unix::fs::PermissionsExt;
@@ -83,6 +83,16 @@ while true; do :; done
             .await
             .expect("top-level stop_service should succeed");
 
+        bootstrap(Some("v-test"), Some(&config_path))
+            .await
+            .expect("bootstrap should start the stopped service");
+        bootstrap(None, Some(&config_path))
+            .await
+            .expect("bootstrap should be a no-op while running");
+        stop_service(&config_path)
+            .await
+            .expect("stop after bootstrap");
+
         if let Some(prev) = old_home {
             env::set_var("MIHOMO_HOME", prev);
         } else {
@@ -1,7 +1,10 @@
 mod common;
 
-use mihomo_rs::{MihomoClient, ProxyManager};
+use mihomo_rs::core::{DelayOutcome, SubscriptionAlert};
+use mihomo_rs::proxy::{GroupTestOptions, MaintainEvent, MaintainOptions};
+use mihomo_rs::{MihomoClient, MihomoError, ProxyManager};
 use mockito::Server;
+use std::time::Duration;
 
 #[tokio::test]
 async fn list_proxies_filters_groups_and_sorts_nodes() {
@@ -82,3 +85,1002 @@ async fn list_groups_switch_and_get_current() {
     assert_eq!(current, "JP-01");
     assert_eq!(all.len(), 3);
 }
+
+#[tokio::test]
+async fn unhealthy_groups_reports_groups_without_live_members() {
+    let mut server = Server::new_async().await;
+    let proxies_mock = server
+        .mock("GET", "/proxies")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{
+                "proxies": {
+                    "HK": {"type":"Selector","now":"HK-01","all":["HK-01"]},
+                    "JP": {"type":"URLTest","now":"JP-01","all":["JP-01","JP-02"]},
+                    "EMPTY": {"type":"Selector","all":[]},
+                    "HK-01": {"type":"Shadowsocks","history":[]},
+                    "JP-01": {"type":"Shadowsocks","history":[]},
+                    "JP-02": {"type":"Shadowsocks","history":[]}
+                }
+            }"#,
+        )
+        .create_async()
+        .await;
+
+    let hk_mock = server
+        .mock("GET", "/group/HK/delay")
+        .match_query(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("timeout".into(), "3000".into()),
+            mockito::Matcher::UrlEncoded("url".into(), "https://example.com".into()),
+        ]))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"HK-01":120}"#)
+        .create_async()
+        .await;
+
+    let jp_mock = server
+        .mock("GET", "/group/JP/delay")
+        .match_query(mockito::Matcher::Any)
+        .with_status(504)
+        .with_body(r#"{"message":"Timeout"}"#)
+        .create_async()
+        .await;
+
+    let client = MihomoClient::new(&server.url(), None).expect("create client");
+    let manager = ProxyManager::new(client);
+
+    let unhealthy = manager
+        .unhealthy_groups("https://example.com", 3000)
+        .await
+        .expect("unhealthy groups");
+
+    proxies_mock.assert_async().await;
+    hk_mock.assert_async().await;
+    jp_mock.assert_async().await;
+    assert_eq!(unhealthy, vec!["JP".to_string()]);
+}
+
+#[tokio::test]
+async fn unhealthy_groups_propagates_unexpected_errors() {
+    let mut server = Server::new_async().await;
+    let _proxies_mock = server
+        .mock("GET", "/proxies")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(common::mock_proxies_payload())
+        .create_async()
+        .await;
+    let _delay_mock = server
+        .mock("GET", "/group/GLOBAL/delay")
+        .match_query(mockito::Matcher::Any)
+        .with_status(401)
+        .create_async()
+        .await;
+
+    let client = MihomoClient::new(&server.url(), None).expect("create client");
+    let manager = ProxyManager::new(client);
+
+    assert!(manager
+        .unhealthy_groups("https://example.com", 3000)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn get_provider_proxies_returns_sorted_provider_nodes() {
+    let mut server = Server::new_async().await;
+    let provider_mock = server
+        .mock("GET", "/providers/proxies/sub%20A")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{
+                "name":"sub A",
+                "type":"Proxy",
+                "vehicleType":"HTTP",
+                "updatedAt":"2026-01-01T00:00:00Z",
+                "proxies":[
+                    {"name":"US-01","type":"Trojan","history":[],"interface":"wg0","routing-mark":6666},
+                    {"name":"SG-01","type":"Vmess","history":[{"time":"2026-01-01T00:00:00Z","delay":88}]}
+                ]
+            }"#,
+        )
+        .create_async()
+        .await;
+
+    let client = MihomoClient::new(&server.url(), None).expect("create client");
+    let manager = ProxyManager::new(client);
+
+    let nodes = manager
+        .get_provider_proxies("sub A")
+        .await
+        .expect("provider proxies");
+
+    provider_mock.assert_async().await;
+    assert_eq!(nodes.len(), 2);
+    assert_eq!(nodes[0].name, "SG-01");
+    assert_eq!(nodes[0].delay, Some(88));
+    assert!(nodes[0].alive);
+    assert_eq!(nodes[1].name, "US-01");
+    assert!(!nodes[1].alive);
+    assert_eq!(nodes[1].interface.as_deref(), Some("wg0"));
+    assert_eq!(nodes[1].routing_mark, Some(6666));
+    assert_eq!(nodes[0].interface, None);
+}
+
+#[tokio::test]
+async fn get_providers_tolerates_null_proxies() {
+    let mut server = Server::new_async().await;
+    let providers_mock = server
+        .mock("GET", "/providers/proxies")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"providers":{"empty":{"name":"empty","type":"Proxy","vehicleType":"File","proxies":null}}}"#,
+        )
+        .create_async()
+        .await;
+
+    let client = MihomoClient::new(&server.url(), None).expect("create client");
+    let providers = client.get_providers().await.expect("providers");
+
+    providers_mock.assert_async().await;
+    assert_eq!(providers["empty"].proxy_count(), 0);
+    assert_eq!(providers["empty"].vehicle_type, "File");
+}
+
+#[tokio::test]
+async fn switch_in_all_groups_targets_selectors_containing_proxy() {
+    let mut server = Server::new_async().await;
+    let proxies_mock = server
+        .mock("GET", "/proxies")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{
+                "proxies": {
+                    "GLOBAL": {"type":"Selector","now":"JP-01","all":["HK-01","JP-01"]},
+                    "Streaming": {"type":"Selector","now":"JP-01","all":["HK-01","JP-01"]},
+                    "Games": {"type":"Selector","now":"JP-01","all":["JP-01"]},
+                    "Auto": {"type":"URLTest","now":"JP-01","all":["HK-01","JP-01"]},
+                    "HK-01": {"type":"Shadowsocks","history":[]},
+                    "JP-01": {"type":"Shadowsocks","history":[]}
+                }
+            }"#,
+        )
+        .create_async()
+        .await;
+    let global_mock = server
+        .mock("PUT", "/proxies/GLOBAL")
+        .with_status(204)
+        .create_async()
+        .await;
+    let streaming_mock = server
+        .mock("PUT", "/proxies/Streaming")
+        .with_status(400)
+        .with_body(r#"{"message":"Selector update error"}"#)
+        .create_async()
+        .await;
+
+    let client = MihomoClient::new(&server.url(), None).expect("create client");
+    let manager = ProxyManager::new(client);
+
+    let results = manager
+        .switch_in_all_groups("HK-01")
+        .await
+        .expect("batch switch");
+
+    proxies_mock.assert_async().await;
+    global_mock.assert_async().await;
+    streaming_mock.assert_async().await;
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].0, "GLOBAL");
+    assert!(results[0].1.is_ok());
+    assert_eq!(results[1].0, "Streaming");
+    assert!(results[1].1.is_err());
+}
+
+#[tokio::test]
+async fn switch_and_reset_closes_connections_through_group() {
+    let mut server = Server::new_async().await;
+    let switch_mock = server
+        .mock("PUT", "/proxies/Streaming")
+        .with_status(204)
+        .create_async()
+        .await;
+    let connections_mock = server
+        .mock("GET", "/connections")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"downloadTotal":0,"uploadTotal":0,"connections":[
+                {"id":"c1","chains":["HK-01","Streaming"]},
+                {"id":"c2","chains":["JP-01","GLOBAL"]},
+                {"id":"c3","chains":["HK-01","Streaming","GLOBAL"]}
+            ]}"#,
+        )
+        .create_async()
+        .await;
+    let close_c1 = server
+        .mock("DELETE", "/connections/c1")
+        .with_status(204)
+        .create_async()
+        .await;
+    let close_c3 = server
+        .mock("DELETE", "/connections/c3")
+        .with_status(204)
+        .create_async()
+        .await;
+    let close_c2 = server
+        .mock("DELETE", "/connections/c2")
+        .expect(0)
+        .create_async()
+        .await;
+
+    let client = MihomoClient::new(&server.url(), None).expect("create client");
+    let manager = ProxyManager::new(client);
+
+    let closed = manager
+        .switch_and_reset("Streaming", "JP-01")
+        .await
+        .expect("switch and reset");
+
+    switch_mock.assert_async().await;
+    connections_mock.assert_async().await;
+    close_c1.assert_async().await;
+    close_c3.assert_async().await;
+    close_c2.assert_async().await;
+    assert_eq!(closed, 2);
+}
+
+#[tokio::test]
+async fn switch_and_reset_reports_failed_closes() {
+    let mut server = Server::new_async().await;
+    let _switch = server
+        .mock("PUT", "/proxies/Streaming")
+        .with_status(204)
+        .create_async()
+        .await;
+    let _connections = server
+        .mock("GET", "/connections")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"downloadTotal":0,"uploadTotal":0,"connections":[
+                {"id":"c1","chains":["HK-01","Streaming"]},
+                {"id":"c2","chains":["HK-02","Streaming"]}
+            ]}"#,
+        )
+        .create_async()
+        .await;
+    let close_c1 = server
+        .mock("DELETE", "/connections/c1")
+        .with_status(500)
+        .create_async()
+        .await;
+    let close_c2 = server
+        .mock("DELETE", "/connections/c2")
+        .with_status(204)
+        .create_async()
+        .await;
+
+    let client = MihomoClient::new(&server.url(), None).expect("create client");
+    let err = ProxyManager::new(client)
+        .switch_and_reset("Streaming", "JP-01")
+        .await
+        .expect_err("failed close should be reported");
+
+    close_c1.assert_async().await;
+    close_c2.assert_async().await;
+    match err {
+        MihomoError::PartialFailure { succeeded, failed } => {
+            assert_eq!(succeeded, vec!["c2".to_string()]);
+            assert_eq!(failed.len(), 1);
+            assert_eq!(failed[0].0, "c1");
+        }
+        other => panic!("expected partial failure, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn update_all_providers_reports_partial_failures() {
+    let mut server = Server::new_async().await;
+    let providers_mock = server
+        .mock("GET", "/providers/proxies")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"providers":{
+                "default":{"name":"default","type":"Proxy","vehicleType":"Compatible","proxies":[]},
+                "airport":{"name":"airport","type":"Proxy","vehicleType":"HTTP","proxies":[]},
+                "backup":{"name":"backup","type":"Proxy","vehicleType":"HTTP","proxies":[]}
+            }}"#,
+        )
+        .create_async()
+        .await;
+    let airport_mock = server
+        .mock("PUT", "/providers/proxies/airport")
+        .with_status(204)
+        .create_async()
+        .await;
+    let backup_mock = server
+        .mock("PUT", "/providers/proxies/backup")
+        .with_status(503)
+        .with_body(r#"{"message":"fetch failed"}"#)
+        .create_async()
+        .await;
+
+    let client = MihomoClient::new(&server.url(), None).expect("create client");
+    let manager = ProxyManager::new(client);
+
+    let err = manager
+        .update_all_providers()
+        .await
+        .expect_err("one provider failed");
+
+    providers_mock.assert_async().await;
+    airport_mock.assert_async().await;
+    backup_mock.assert_async().await;
+    match err {
+        MihomoError::PartialFailure { succeeded, failed } => {
+            assert_eq!(succeeded, vec!["airport".to_string()]);
+            assert_eq!(failed.len(), 1);
+            assert_eq!(failed[0].0, "backup");
+        }
+        other => panic!("expected partial failure, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn selectable_groups_lists_selectors_with_members_sorted_by_delay() {
+    let mut server = Server::new_async().await;
+    let proxies_mock = server
+        .mock("GET", "/proxies")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{
+                "proxies": {
+                    "GLOBAL": {"type":"Selector","now":"Auto","all":["JP-01","Auto","HK-01","US-01"]},
+                    "Auto": {"type":"URLTest","now":"HK-01","all":["HK-01","JP-01"]},
+                    "HK-01": {"type":"Shadowsocks","history":[{"time":"t","delay":120}]},
+                    "JP-01": {"type":"Trojan","history":[]},
+                    "US-01": {"type":"Vmess","history":[{"time":"t","delay":45}]}
+                }
+            }"#,
+        )
+        .create_async()
+        .await;
+
+    let client = MihomoClient::new(&server.url(), None).expect("create client");
+    let manager = ProxyManager::new(client);
+
+    let choices = manager
+        .selectable_groups()
+        .await
+        .expect("selectable groups");
+
+    proxies_mock.assert_async().await;
+    assert_eq!(choices.len(), 1);
+    assert_eq!(choices[0].name, "GLOBAL");
+    assert_eq!(choices[0].now, "Auto");
+    let members: Vec<(&str, Option<u32>)> = choices[0]
+        .members
+        .iter()
+        .map(|m| (m.name.as_str(), m.delay))
+        .collect();
+    assert_eq!(
+        members,
+        vec![
+            ("US-01", Some(45)),
+            ("HK-01", Some(120)),
+            ("Auto", None),
+            ("JP-01", None)
+        ]
+    );
+    assert_eq!(choices[0].members[2].proxy_type, "URLTest");
+}
+
+#[tokio::test]
+async fn delay_scheduler_tests_groups_until_stopped() {
+    let mut server = Server::new_async().await;
+    let auto_mock = server
+        .mock("GET", "/group/Auto/delay")
+        .match_query(mockito::Matcher::Any)
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"HK-01":120}"#)
+        .expect_at_least(2)
+        .create_async()
+        .await;
+    let fallback_mock = server
+        .mock("GET", "/group/Fallback/delay")
+        .match_query(mockito::Matcher::Any)
+        .with_status(503)
+        .expect_at_least(2)
+        .create_async()
+        .await;
+
+    let client = MihomoClient::new(&server.url(), None).expect("create client");
+    let manager = ProxyManager::new(client);
+
+    let scheduler = manager.start_delay_scheduler(
+        vec!["Auto".to_string(), "Fallback".to_string()],
+        std::time::Duration::from_millis(20),
+    );
+    tokio::time::sleep(std::time::Duration::from_millis(150)).await;
+    assert!(scheduler.is_running());
+    scheduler.shutdown().await;
+
+    auto_mock.assert_async().await;
+    fallback_mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn subscription_alerts_flag_expiring_and_exhausted_providers() {
+    let soon = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+        + 2 * 86400
+        + 3600;
+    let mut server = Server::new_async().await;
+    let providers_mock = server
+        .mock("GET", "/providers/proxies")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(format!(
+            r#"{{"providers":{{
+                "default":{{"name":"default","type":"Proxy","vehicleType":"Compatible","proxies":[]}},
+                "lapsed":{{"name":"lapsed","type":"Proxy","vehicleType":"HTTP","proxies":[],
+                    "subscriptionInfo":{{"Upload":0,"Download":0,"Total":0,"Expire":1000}}}},
+                "soon":{{"name":"soon","type":"Proxy","vehicleType":"HTTP","proxies":[],
+                    "subscriptionInfo":{{"Upload":0,"Download":95,"Total":100,"Expire":{}}}}},
+                "fine":{{"name":"fine","type":"Proxy","vehicleType":"HTTP","proxies":[],
+                    "subscriptionInfo":{{"Upload":0,"Download":10,"Total":100,"Expire":0}}}}
+            }}}}"#,
+            soon
+        ))
+        .create_async()
+        .await;
+    let client = MihomoClient::new(&server.url(), None).expect("create client");
+    let manager = ProxyManager::new(client);
+
+    let alerts = manager
+        .subscription_alerts(7, 0.9)
+        .await
+        .expect("subscription alerts");
+
+    providers_mock.assert_async().await;
+    assert_eq!(
+        alerts,
+        vec![
+            SubscriptionAlert::Expired {
+                provider: "lapsed".to_string()
+            },
+            SubscriptionAlert::ExpiringSoon {
+                provider: "soon".to_string(),
+                days: 2
+            },
+            SubscriptionAlert::QuotaNearlyUsed {
+                provider: "soon".to_string(),
+                usage_ratio: 0.95
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_delay_falls_back_to_manager_defaults() {
+    let mut server = Server::new_async().await;
+    let default_mock = server
+        .mock("GET", "/proxies/HK-01/delay")
+        .match_query(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("url".into(), "http://cp.cloudflare.com".into()),
+            mockito::Matcher::UrlEncoded("timeout".into(), "3000".into()),
+        ]))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"delay":80}"#)
+        .create_async()
+        .await;
+    let override_mock = server
+        .mock("GET", "/proxies/HK-01/delay")
+        .match_query(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("url".into(), "http://example.com".into()),
+            mockito::Matcher::UrlEncoded("timeout".into(), "3000".into()),
+        ]))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"delay":90}"#)
+        .create_async()
+        .await;
+
+    let client = MihomoClient::new(&server.url(), None).expect("create client");
+    let manager = ProxyManager::new(client).with_default_test("http://cp.cloudflare.com", 3000);
+
+    let delay = manager
+        .test_delay("HK-01", None, None)
+        .await
+        .expect("delay");
+    let overridden = manager
+        .test_delay("HK-01", Some("http://example.com"), None)
+        .await
+        .expect("delay");
+
+    default_mock.assert_async().await;
+    override_mock.assert_async().await;
+    assert_eq!(delay, 80);
+    assert_eq!(overridden, 90);
+}
+
+#[tokio::test]
+async fn test_delay_sends_expected_status_and_rejects_bad_syntax() {
+    let mut server = Server::new_async().await;
+    let delay_mock = server
+        .mock("GET", "/proxies/HK-01/delay")
+        .match_query(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("url".into(), "http://health.internal/ok".into()),
+            mockito::Matcher::UrlEncoded("expected".into(), "200-299/301".into()),
+        ]))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"delay":35}"#)
+        .create_async()
+        .await;
+
+    let client = MihomoClient::new(&server.url(), None).expect("create client");
+    let manager = ProxyManager::new(client.clone())
+        .with_default_test("http://health.internal/ok", 3000)
+        .with_expected_status("200-299/301");
+    let delay = manager
+        .test_delay("HK-01", None, None)
+        .await
+        .expect("delay");
+    delay_mock.assert_async().await;
+    assert_eq!(delay, 35);
+
+    for bad in ["", "2xx", "299-200", "200/"] {
+        let err = client
+            .test_delay_expecting("HK-01", "http://health.internal/ok", 3000, bad)
+            .await
+            .expect_err("invalid expected status");
+        assert!(matches!(err, MihomoError::Config(_)), "{}: {:?}", bad, err);
+    }
+}
+
+#[tokio::test]
+async fn expected_status_applies_to_group_tests() {
+    let mut server = Server::new_async().await;
+    let expected = || mockito::Matcher::UrlEncoded("expected".into(), "200".into());
+    server
+        .mock("GET", "/proxies")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{
+                "proxies": {
+                    "Proxy": {"type":"Selector","now":"A","all":["A"]},
+                    "A": {"type":"Shadowsocks","history":[]}
+                }
+            }"#,
+        )
+        .create_async()
+        .await;
+    let _group_mock = server
+        .mock("GET", "/proxies/Proxy")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"type":"Selector","now":"A","all":["A"]}"#)
+        .create_async()
+        .await;
+    let member_mock = server
+        .mock("GET", "/proxies/A/delay")
+        .match_query(expected())
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"delay":40}"#)
+        .create_async()
+        .await;
+    let group_mock = server
+        .mock("GET", "/group/Proxy/delay")
+        .match_query(expected())
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"A":40}"#)
+        .expect_at_least(3)
+        .create_async()
+        .await;
+
+    let client = MihomoClient::new(&server.url(), None).expect("create client");
+    let manager = ProxyManager::new(client.clone()).with_expected_status("200");
+
+    let report = manager
+        .test_all_groups(&GroupTestOptions::default())
+        .await
+        .expect("test all groups");
+    assert_eq!(
+        report["Proxy"],
+        vec![("A".to_string(), DelayOutcome::Ok(40))]
+    );
+    let unhealthy = manager
+        .unhealthy_groups("https://example.com", 3000)
+        .await
+        .expect("unhealthy groups");
+    assert!(unhealthy.is_empty());
+
+    let scheduler =
+        manager.start_delay_scheduler(vec!["Proxy".to_string()], Duration::from_secs(60));
+    let (maintainer, mut events) = manager.maintain_fastest(
+        "Proxy",
+        MaintainOptions::default().with_interval(Duration::from_secs(60)),
+    );
+    let event = events.recv().await.expect("first round event");
+    maintainer.shutdown().await;
+    assert_eq!(
+        event,
+        MaintainEvent::Held {
+            proxy: "A".into(),
+            delay: 40
+        }
+    );
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    scheduler.shutdown().await;
+
+    member_mock.assert_async().await;
+    group_mock.assert_async().await;
+
+    let err = client
+        .test_group_delay_expecting("Proxy", "https://example.com", 3000, "2xx")
+        .await
+        .expect_err("invalid expected status");
+    assert!(matches!(err, MihomoError::Config(_)), "{:?}", err);
+}
+
+#[tokio::test]
+async fn global_group_and_set_global_target_builtin_selector() {
+    let mut server = Server::new_async().await;
+    let global_mock = server
+        .mock("GET", "/proxies/GLOBAL")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"type":"Selector","now":"DIRECT","all":["DIRECT","REJECT","HK-01"]}"#)
+        .create_async()
+        .await;
+    let switch_mock = server
+        .mock("PUT", "/proxies/GLOBAL")
+        .match_body(mockito::Matcher::JsonString(
+            r#"{"name":"HK-01"}"#.to_string(),
+        ))
+        .with_status(204)
+        .create_async()
+        .await;
+
+    let client = MihomoClient::new(&server.url(), None).expect("create client");
+    let manager = ProxyManager::new(client);
+
+    let global = manager.global_group().await.expect("global group");
+    manager.set_global("HK-01").await.expect("set global");
+
+    global_mock.assert_async().await;
+    switch_mock.assert_async().await;
+    assert_eq!(global.name, "GLOBAL");
+    assert_eq!(global.now, "DIRECT");
+    assert_eq!(global.all.len(), 3);
+}
+
+#[tokio::test]
+async fn delay_cache_reuses_recent_results() {
+    let mut server = Server::new_async().await;
+    let delay_mock = server
+        .mock("GET", "/proxies/HK-01/delay")
+        .match_query(mockito::Matcher::Any)
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"delay":75}"#)
+        .expect(2)
+        .create_async()
+        .await;
+
+    let client = MihomoClient::new(&server.url(), None).expect("create client");
+    let manager = ProxyManager::new(client).with_delay_cache(std::time::Duration::from_secs(60));
+
+    for _ in 0..3 {
+        assert_eq!(
+            manager
+                .test_delay("HK-01", None, None)
+                .await
+                .expect("delay"),
+            75
+        );
+    }
+    manager.clear_delay_cache();
+    assert_eq!(
+        manager
+            .test_delay("HK-01", None, None)
+            .await
+            .expect("delay"),
+        75
+    );
+
+    delay_mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_all_groups_uses_manager_defaults_and_delay_cache() {
+    let mut server = Server::new_async().await;
+    server
+        .mock("GET", "/proxies")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{
+                "proxies": {
+                    "Proxy": {"type":"Selector","now":"A","all":["A"]},
+                    "A": {"type":"Shadowsocks","history":[]}
+                }
+            }"#,
+        )
+        .create_async()
+        .await;
+    let delay_mock = server
+        .mock("GET", "/proxies/A/delay")
+        .match_query(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("timeout".into(), "1500".into()),
+            mockito::Matcher::UrlEncoded("url".into(), "http://health.internal/ok".into()),
+        ]))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"delay":25}"#)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let client = MihomoClient::new(&server.url(), None).expect("create client");
+    let manager = ProxyManager::new(client)
+        .with_default_test("http://health.internal/ok", 1500)
+        .with_delay_cache(Duration::from_secs(60));
+    let options = manager.group_test_options().with_concurrency(2);
+    assert_eq!(options.test_url, "http://health.internal/ok");
+    assert_eq!(options.timeout, 1500);
+
+    for _ in 0..2 {
+        let report = manager
+            .test_all_groups(&options)
+            .await
+            .expect("test all groups");
+        assert_eq!(
+            report["Proxy"],
+            vec![("A".to_string(), DelayOutcome::Ok(25))]
+        );
+    }
+    delay_mock.assert_async().await;
+    assert_eq!(
+        manager
+            .test_delay("A", None, None)
+            .await
+            .expect("cached delay"),
+        25
+    );
+}
+
+#[tokio::test]
+async fn first_healthy_returns_first_responsive_member_in_order() {
+    let mut server = Server::new_async().await;
+    let group_mock = server
+        .mock("GET", "/proxies/Fallback")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"type":"Fallback","now":"A","all":["A","B","C"]}"#)
+        .create_async()
+        .await;
+    let a_mock = server
+        .mock("GET", "/proxies/A/delay")
+        .match_query(mockito::Matcher::Any)
+        .with_status(504)
+        .with_body(r#"{"message":"Timeout"}"#)
+        .create_async()
+        .await;
+    let _b_mock = server
+        .mock("GET", "/proxies/B/delay")
+        .match_query(mockito::Matcher::Any)
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"delay":300}"#)
+        .create_async()
+        .await;
+    let _c_mock = server
+        .mock("GET", "/proxies/C/delay")
+        .match_query(mockito::Matcher::Any)
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"delay":20}"#)
+        .create_async()
+        .await;
+
+    let client = MihomoClient::new(&server.url(), None).expect("create client");
+    let manager = ProxyManager::new(client);
+
+    let healthy = manager
+        .first_healthy("Fallback")
+        .await
+        .expect("first healthy");
+
+    group_mock.assert_async().await;
+    a_mock.assert_async().await;
+    assert_eq!(healthy.as_deref(), Some("B"));
+}
+
+#[tokio::test]
+async fn maintain_fastest_switches_off_a_dead_pick_and_reports_it() {
+    let mut server = Server::new_async().await;
+    let _group_mock = server
+        .mock("GET", "/proxies/Proxy")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"type":"Selector","now":"A","all":["A","B","C"]}"#)
+        .create_async()
+        .await;
+    let _delay_mock = server
+        .mock("GET", "/group/Proxy/delay")
+        .match_query(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("timeout".into(), "1500".into()),
+            mockito::Matcher::UrlEncoded("url".into(), "http://health.internal/ok".into()),
+        ]))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"B":300,"C":120}"#)
+        .create_async()
+        .await;
+    let switch_mock = server
+        .mock("PUT", "/proxies/Proxy")
+        .match_body(mockito::Matcher::JsonString(r#"{"name":"C"}"#.into()))
+        .with_status(204)
+        .expect_at_least(1)
+        .create_async()
+        .await;
+
+    let client = MihomoClient::new(&server.url(), None).expect("create client");
+    let manager = ProxyManager::new(client).with_default_test("http://health.internal/ok", 1500);
+    let (maintainer, mut events) = manager.maintain_fastest(
+        "Proxy",
+        manager
+            .maintain_options()
+            .with_interval(Duration::from_secs(60)),
+    );
+
+    let event = events.recv().await.expect("first round event");
+    maintainer.shutdown().await;
+
+    switch_mock.assert_async().await;
+    assert_eq!(
+        event,
+        MaintainEvent::Switched {
+            from: "A".into(),
+            to: "C".into(),
+            from_delay: None,
+            to_delay: 120
+        }
+    );
+}
+
+#[tokio::test]
+async fn switch_checked_validates_group_type_and_membership() {
+    let mut server = Server::new_async().await;
+    for (group, body) in [
+        ("Auto", r#"{"type":"URLTest","now":"A","all":["A","B"]}"#),
+        (
+            "Balance",
+            r#"{"type":"LoadBalance","now":"A","all":["A","B"]}"#,
+        ),
+        ("A", r#"{"type":"Shadowsocks","history":[]}"#),
+    ] {
+        server
+            .mock("GET", format!("/proxies/{}", group).as_str())
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(body)
+            .create_async()
+            .await;
+    }
+    let pin_mock = server
+        .mock("PUT", "/proxies/Auto")
+        .match_body(mockito::Matcher::JsonString(r#"{"name":"B"}"#.into()))
+        .with_status(204)
+        .expect(1)
+        .create_async()
+        .await;
+    let other_puts = server
+        .mock("PUT", "/proxies/Balance")
+        .expect(0)
+        .create_async()
+        .await;
+
+    let client = MihomoClient::new(&server.url(), None).expect("create client");
+    let manager = ProxyManager::new(client);
+
+    manager
+        .switch_checked("Auto", "B")
+        .await
+        .expect("pin URLTest");
+    let balance = manager
+        .switch_checked("Balance", "B")
+        .await
+        .expect_err("LoadBalance rejects manual selection");
+    let missing = manager
+        .switch_checked("Auto", "C")
+        .await
+        .expect_err("not a member");
+    let not_group = manager
+        .switch_checked("A", "B")
+        .await
+        .expect_err("not a group");
+    let builtin_group = manager
+        .switch_checked("DIRECT", "A")
+        .await
+        .expect_err("builtin is not a group");
+    let builtin_member = manager
+        .switch_checked("Auto", "DIRECT")
+        .await
+        .expect_err("builtin not listed");
+
+    pin_mock.assert_async().await;
+    other_puts.assert_async().await;
+    assert!(
+        matches!(&balance, MihomoError::Proxy(msg) if msg.contains("Cannot manually select in a LoadBalance group"))
+    );
+    assert!(matches!(&missing, MihomoError::Proxy(msg) if msg.contains("'C' is not a member")));
+    assert!(matches!(&not_group, MihomoError::Proxy(msg) if msg.contains("not a proxy group")));
+    assert!(
+        matches!(&builtin_group, MihomoError::Proxy(msg) if msg == "'DIRECT' is a builtin proxy, not a group")
+    );
+    assert!(
+        matches!(&builtin_member, MihomoError::Proxy(msg) if msg.starts_with("Builtin proxy 'DIRECT' is not listed in group 'Auto'"))
+    );
+}
+
+#[tokio::test]
+async fn panic_direct_sets_direct_mode_then_closes_connections() {
+    let mut server = Server::new_async().await;
+    let mode_mock = server
+        .mock("PATCH", "/configs")
+        .match_body(mockito::Matcher::JsonString(r#"{"mode":"direct"}"#.into()))
+        .with_status(204)
+        .create_async()
+        .await;
+    let close_mock = server
+        .mock("DELETE", "/connections")
+        .with_status(204)
+        .create_async()
+        .await;
+
+    let client = MihomoClient::new(&server.url(), None).expect("create client");
+    ProxyManager::new(client)
+        .panic_direct()
+        .await
+        .expect("panic direct");
+
+    mode_mock.assert_async().await;
+    close_mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn panic_direct_keeps_connections_when_mode_change_fails() {
+    let mut server = Server::new_async().await;
+    server
+        .mock("PATCH", "/configs")
+        .with_status(500)
+        .create_async()
+        .await;
+    let close_mock = server
+        .mock("DELETE", "/connections")
+        .expect(0)
+        .create_async()
+        .await;
+
+    let client = MihomoClient::new(&server.url(), None).expect("create client");
+    assert!(ProxyManager::new(client).panic_direct().await.is_err());
+    close_mock.assert_async().await;
+}
@@ -1,5 +1,6 @@
 #[cfg(unix)]
 mod unix_tests {
+    use mihomo_rs::service::LogFile;
     use mihomo_rs::{MihomoError, ServiceManager, ServiceStatus};
     use std::os::unix::fs::PermissionsExt;
     use std::path::Path;
@@ -7,10 +8,17 @@ mod unix_tests {
     use tokio::fs;
 
     async fn write_fake_daemon(binary: &Path) {
-        let script = r#"#!/bin/sh
+        write_script(
+            binary,
+            r#"#!/bin/sh
 trap 'exit 0' TERM INT
 while true; do :; done
-"#;
+"#,
+        )
+        .await;
+    }
+
+    async fn write_script(binary: &Path, script: &str) {
         fs::write(binary, script).await.expect("write fake daemon");
 
         let mut perms = fs::metadata(binary)
@@ -23,6 +31,24 @@ while true; do :; done
             .expect("set execute permission");
     }
 
+    fn free_port() -> u16 {
+        std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .expect("bind ephemeral port")
+            .port()
+    }
+
+    /// Writes a config whose ports are free right now, so the pre-start port check
+    /// doesn't trip over whatever else is listening on the test host.
+    async fn write_config(config: &Path) {
+        let content = format!(
+            "port: {}\nexternal-controller: 127.0.0.1:{}\n",
+            free_port(),
+            free_port()
+        );
+        fs::write(config, content).await.expect("write config");
+    }
+
     #[tokio::test]
     async fn start_stop_status_and_duplicate_start_flow() {
         let dir = tempdir().expect("create temp dir");
@@ -31,9 +57,7 @@ while true; do :; done
         let pid_file = dir.path().join("mihomo.pid");
 
         write_fake_daemon(&binary).await;
-        fs::write(&config, "port: 7890\nexternal-controller: 127.0.0.1:9090\n")
-            .await
-            .expect("write config");
+        write_config(&config).await;
 
         let manager = ServiceManager::with_pid_file(binary, config, pid_file)
             .with_stop_wait(100, std::time::Duration::from_millis(20));
@@ -64,17 +88,63 @@ while true; do :; done
     }
 
     #[tokio::test]
-    async fn restart_replaces_running_process() {
+    async fn start_launches_profile_with_includes_resolved() {
         let dir = tempdir().expect("create temp dir");
         let binary = dir.path().join("mihomo");
         let config = dir.path().join("config.yaml");
         let pid_file = dir.path().join("mihomo.pid");
 
-        write_fake_daemon(&binary).await;
-        fs::write(&config, "port: 7890\nexternal-controller: 127.0.0.1:9090\n")
+        let script = r#"#!/bin/sh
+printf '%s\n' "$@" > "$(dirname "$0")/args"
+trap 'exit 0' TERM INT
+while true; do :; done
+"#;
+        write_script(&binary, script).await;
+        write_config(&dir.path().join("base.yaml")).await;
+        fs::write(&config, "include: base.yaml\nrules: ['MATCH,DIRECT']\n")
             .await
             .expect("write config");
 
+        let manager = ServiceManager::with_pid_file(binary, config, pid_file)
+            .with_stop_wait(100, std::time::Duration::from_millis(20));
+        manager.start().await.expect("start daemon");
+
+        let resolved = dir.path().join(".config.yaml.resolved");
+        let mut args = String::new();
+        for _ in 0..50 {
+            args = fs::read_to_string(dir.path().join("args"))
+                .await
+                .unwrap_or_default();
+            if !args.is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        manager.stop().await.expect("stop daemon");
+
+        assert!(
+            args.lines().any(|arg| Path::new(arg) == resolved),
+            "{}",
+            args
+        );
+        let merged: serde_yaml::Value =
+            serde_yaml::from_str(&fs::read_to_string(&resolved).await.expect("read resolved"))
+                .expect("parse resolved");
+        assert!(merged.get("include").is_none());
+        assert!(merged.get("external-controller").is_some());
+        assert_eq!(merged["rules"][0].as_str(), Some("MATCH,DIRECT"));
+    }
+
+    #[tokio::test]
+    async fn restart_replaces_running_process() {
+        let dir = tempdir().expect("create temp dir");
+        let binary = dir.path().join("mihomo");
+        let config = dir.path().join("config.yaml");
+        let pid_file = dir.path().join("mihomo.pid");
+
+        write_fake_daemon(&binary).await;
+        write_config(&config).await;
+
         let manager = ServiceManager::with_pid_file(binary, config, pid_file)
             .with_stop_wait(100, std::time::Duration::from_millis(20));
 
@@ -113,9 +183,7 @@ while true; do :; done
             .await
             .expect("set execute permission");
 
-        fs::write(&config, "port: 7890\nexternal-controller: 127.0.0.1:9090\n")
-            .await
-            .expect("write config");
+        write_config(&config).await;
 
         let manager = ServiceManager::with_pid_file(binary, config, pid_file.clone())
             .with_stop_wait(5, std::time::Duration::from_millis(10));
@@ -145,4 +213,147 @@ while true; do :; done
         );
         assert!(!pid_file.exists());
     }
+
+    #[tokio::test]
+    async fn start_captures_output_and_rotates_oversized_log() {
+        let dir = tempdir().expect("create temp dir");
+        let binary = dir.path().join("mihomo");
+        let config = dir.path().join("config.yaml");
+        let pid_file = dir.path().join("mihomo.pid");
+        let log = LogFile::new(dir.path().join("logs/mihomo.log")).with_max_bytes(16);
+
+        fs::write(
+            &binary,
+            "#!/bin/sh\necho started\necho oops >&2\ntrap 'exit 0' TERM INT\nwhile true; do sleep 0.05; done\n",
+        )
+        .await
+        .expect("write daemon");
+        let mut perms = fs::metadata(&binary).await.unwrap().permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(&binary, perms).await.unwrap();
+        write_config(&config).await;
+        fs::create_dir_all(dir.path().join("logs")).await.unwrap();
+        fs::write(&log.path, "previous run output\n").await.unwrap();
+
+        let manager = ServiceManager::with_pid_file(binary, config, pid_file)
+            .with_managed(true)
+            .with_log_file(log.clone());
+        manager.start().await.expect("start daemon");
+
+        assert_eq!(
+            fs::read_to_string(log.rotated_path()).await.unwrap(),
+            "previous run output\n"
+        );
+        let output = fs::read_to_string(&log.path).await.unwrap();
+        assert!(
+            output.contains("started") && output.contains("oops"),
+            "{}",
+            output
+        );
+        manager.stop().await.expect("stop daemon");
+    }
+
+    #[tokio::test]
+    async fn managed_manager_kills_its_process_on_drop() {
+        let dir = tempdir().expect("create temp dir");
+        let binary = dir.path().join("mihomo");
+        let config = dir.path().join("config.yaml");
+        let pid_file = dir.path().join("mihomo.pid");
+
+        write_fake_daemon(&binary).await;
+        write_config(&config).await;
+
+        let manager =
+            ServiceManager::with_pid_file(binary, config, pid_file.clone()).with_managed(true);
+        manager.start().await.expect("start daemon");
+        let pid = match manager.status().await.expect("running status") {
+            ServiceStatus::Running(pid) => pid,
+            other => panic!("expected running service, got: {:?}", other),
+        };
+
+        drop(manager);
+
+        let alive = std::process::Command::new("kill")
+            .arg("-0")
+            .arg(pid.to_string())
+            .stderr(std::process::Stdio::null())
+            .status()
+            .expect("run kill -0")
+            .success();
+        assert!(!alive, "managed process {} should be gone", pid);
+        assert!(!pid_file.exists());
+    }
+
+    #[tokio::test]
+    async fn stop_escalates_only_when_force_stop_is_enabled() {
+        let dir = tempdir().expect("create temp dir");
+        let binary = dir.path().join("mihomo");
+        let config = dir.path().join("config.yaml");
+        let pid_file = dir.path().join("mihomo.pid");
+
+        fs::write(&binary, "#!/bin/sh\ntrap '' TERM\nwhile true; do :; done\n")
+            .await
+            .expect("write stubborn daemon");
+        let mut perms = fs::metadata(&binary)
+            .await
+            .expect("read daemon metadata")
+            .permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(&binary, perms)
+            .await
+            .expect("set execute permission");
+        write_config(&config).await;
+
+        let interval = std::time::Duration::from_millis(20);
+        let grace = std::time::Duration::from_millis(200);
+        let gentle =
+            ServiceManager::with_pid_file(binary.clone(), config.clone(), pid_file.clone())
+                .with_stop_wait(100, interval)
+                .with_stop_grace(grace)
+                .with_force_stop(false);
+        gentle.start().await.expect("start daemon");
+
+        let err = gentle.stop().await.expect_err("TERM is ignored");
+        assert!(err.to_string().contains("did not stop"));
+        assert!(gentle.is_running().await);
+
+        let forceful = ServiceManager::with_pid_file(binary, config, pid_file)
+            .with_stop_wait(100, interval)
+            .with_stop_grace(grace);
+        forceful.stop().await.expect("escalate to kill");
+        assert_eq!(
+            forceful.status().await.expect("stopped status"),
+            ServiceStatus::Stopped
+        );
+    }
+
+    #[tokio::test]
+    async fn start_reports_port_conflicts_before_spawning() {
+        let dir = tempdir().expect("create temp dir");
+        let binary = dir.path().join("mihomo");
+        let config = dir.path().join("config.yaml");
+        let pid_file = dir.path().join("mihomo.pid");
+
+        let taken = std::net::TcpListener::bind("127.0.0.1:0").expect("bind port");
+        let port = taken.local_addr().expect("local addr").port();
+
+        write_fake_daemon(&binary).await;
+        fs::write(
+            &config,
+            format!("mixed-port: {}\nexternal-controller: 127.0.0.1:0\n", port),
+        )
+        .await
+        .expect("write config");
+
+        let manager = ServiceManager::with_pid_file(binary, config, pid_file.clone());
+        let err = manager.start().await.expect_err("port is taken");
+
+        match err {
+            MihomoError::Service(msg) => {
+                assert_eq!(msg, format!("Port {} (mixed) is already in use", port))
+            }
+            other => panic!("expected service error, got: {}", other),
+        }
+        assert!(!pid_file.exists());
+    }
 }
@@ -45,7 +45,11 @@ async fn start_stop_restart_return_expected_errors_without_real_process() {
     let temp = common::setup_temp_home();
     let home = common::temp_home_path(&temp);
     let config = home.join("config.yaml");
-    fs::write(&config, "port: 7890\n")
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("bind ephemeral port")
+        .port();
+    fs::write(&config, format!("port: {}\n", port))
         .await
         .expect("write config file");
 
@@ -91,3 +95,36 @@ async fn stop_removes_stale_pid_record_and_reports_not_running() {
     }
     assert!(!pid_file.exists());
 }
+
+#[tokio::test]
+async fn client_uses_controller_and_secret_from_config_file() {
+    let mut server = mockito::Server::new_async().await;
+    let version_mock = server
+        .mock("GET", "/version")
+        .match_header("authorization", "Bearer s3cret")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"version":"v1.19.0"}"#)
+        .create_async()
+        .await;
+
+    let temp = common::setup_temp_home();
+    let home = common::temp_home_path(&temp);
+    let config = home.join("config.yaml");
+    fs::write(
+        &config,
+        format!(
+            "port: 7890\nexternal-controller: {}\nsecret: s3cret\n",
+            server.host_with_port()
+        ),
+    )
+    .await
+    .expect("write config");
+
+    let manager = ServiceManager::with_home(PathBuf::from("/unused/mihomo"), config, home);
+    let client = manager.client().await.expect("client from config");
+    let version = client.get_version().await.expect("authenticated request");
+
+    version_mock.assert_async().await;
+    assert_eq!(version.version, "v1.19.0");
+}
@@ -211,6 +211,32 @@ async fn install_fails_fast_when_version_already_exists() {
     assert!(matches!(err, MihomoError::Version(_)));
 }
 
+#[tokio::test]
+async fn ensure_installed_returns_existing_binary_without_download() {
+    let temp = setup_temp_home();
+    let home = temp_home_path(&temp);
+    let manager = VersionManager::with_home(home.clone()).expect("create version manager");
+
+    let binary = install_fake_version(&home, "v5.1.0").await;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755))
+            .await
+            .expect("mark fake binary executable");
+    }
+
+    let path = manager
+        .ensure_installed("v5.1.0", false)
+        .await
+        .expect("existing install should be reused");
+    assert_eq!(path, binary);
+    assert_eq!(
+        fs::read(&binary).await.expect("read binary"),
+        b"fake-binary"
+    );
+}
+
 #[tokio::test]
 async fn list_installed_orders_semver_before_non_semver_names() {
     let temp = setup_temp_home();